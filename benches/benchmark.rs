@@ -9,7 +9,7 @@ use rgx::core::{Rect, Rgba};
 use rgx::kit::shape2d::*;
 use rgx::kit::ZDepth;
 
-fn bench_triangulate_circle() {
+fn circle() -> Shape {
     Shape::Circle(
         Circle {
             position: Point2::new(0., 0.),
@@ -17,13 +17,13 @@ fn bench_triangulate_circle() {
             sides: 64,
         },
         ZDepth::default(),
+        Rotation::ZERO,
         Stroke::new(1., Rgba::WHITE),
         Fill::Solid(Rgba::WHITE),
     )
-    .triangulate();
 }
 
-fn bench_triangulate_rectangle() {
+fn rectangle() -> Shape {
     Shape::Rectangle(
         Rect::new(1., 1., 3., 3.),
         ZDepth::default(),
@@ -31,14 +31,78 @@ fn bench_triangulate_rectangle() {
         Stroke::new(1., Rgba::WHITE),
         Fill::Solid(Rgba::WHITE),
     )
-    .triangulate();
 }
 
-fn benchmark(c: &mut Criterion) {
-    c.bench_function("triangulate circle", |b| b.iter(bench_triangulate_circle));
-    c.bench_function("triangulate rectangle", |b| {
-        b.iter(bench_triangulate_rectangle)
+fn polygon() -> Shape {
+    // A 64-point star with a hole.
+    let star = (0..64).map(|i| {
+        let r = if i % 2 == 0 { 10. } else { 6. };
+        let a = i as f32 * std::f32::consts::PI / 32.;
+        Point2::new(r * a.cos(), r * a.sin())
     });
+    let hole = vec![[-2., -2.], [2., -2.], [2., 2.], [-2., 2.]];
+
+    Shape::Polygon(
+        Polygon::new(star).hole(hole),
+        ZDepth::default(),
+        Rotation::ZERO,
+        Stroke::new(0.5, Rgba::WHITE),
+        Fill::Solid(Rgba::WHITE),
+    )
+}
+
+fn polyline() -> Shape {
+    // A 256-point sine wave, as drawn by a chart.
+    let points = (0..256).map(|i| Point2::new(i as f32, (i as f32 / 8.).sin() * 32.));
+
+    Shape::polyline(points)
+        .stroke(2., Rgba::WHITE)
+        .join(LineJoin::Round)
+}
+
+fn shapes() -> Vec<(&'static str, Shape)> {
+    vec![
+        ("circle", circle()),
+        ("rectangle", rectangle()),
+        ("polygon", polygon()),
+        ("polyline", polyline()),
+    ]
+}
+
+/// Print the size of each shape's geometry, as a triangle list and as
+/// indexed vertices, with 16-bit indices as uploaded by default.
+fn report_memory() {
+    let vertex = std::mem::size_of::<Vertex>();
+    let index = std::mem::size_of::<u16>();
+
+    for (name, shape) in shapes() {
+        let list = shape.triangulate().len() * vertex;
+        let (verts, indices) = shape.triangulate_indexed();
+        let indexed = verts.len() * vertex + indices.len() * index;
+
+        println!(
+            "{:<10} triangle list: {:>7} bytes, indexed: {:>7} bytes ({:.0}% saved)",
+            name,
+            list,
+            indexed,
+            100. * (1. - indexed as f32 / list as f32)
+        );
+    }
+}
+
+fn benchmark(c: &mut Criterion) {
+    report_memory();
+
+    for (name, shape) in shapes() {
+        let indexed = shape.clone();
+
+        c.bench_function(&format!("triangulate {}", name), move |b| {
+            b.iter(|| shape.triangulate())
+        });
+        c.bench_function(&format!("triangulate {} indexed", name), move |b| {
+            b.iter(|| indexed.triangulate_indexed())
+        });
+    }
 }
 
 criterion_group!(benches, benchmark);
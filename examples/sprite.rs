@@ -2,10 +2,9 @@
 #![allow(clippy::unreadable_literal)]
 #![allow(clippy::single_match)]
 
-use std::collections::vec_deque::VecDeque;
-
 use rgx::core::*;
 use rgx::kit;
+use rgx::kit::clock::Clock;
 use rgx::kit::sprite2d;
 use rgx::kit::*;
 
@@ -17,7 +16,7 @@ use winit::{
     window::Window,
 };
 
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 fn main() -> Result<(), std::io::Error> {
     let event_loop = EventLoop::new();
@@ -73,8 +72,7 @@ fn main() -> Result<(), std::io::Error> {
     let mut x = 0.0;
 
     let frame_batch = 120;
-    let mut last_frame = Instant::now();
-    let mut fts: VecDeque<f64> = VecDeque::with_capacity(frame_batch);
+    let mut clock = Clock::from_hz(120).history(frame_batch);
     let mut frames_total = 0;
 
     ///////////////////////////////////////////////////////////////////////////
@@ -140,20 +138,14 @@ fn main() -> Result<(), std::io::Error> {
                 let rows = (win.height as f32 / sh) as u32;
                 let cols = (win.width as f32 / (sw / 2.0)) as u32;
 
-                let delta: Duration;
-                {
-                    let now = Instant::now();
-                    delta = now.duration_since(last_frame);
-                    last_frame = now;
-                }
-
                 ///////////////////////////////////////////////////////////////////////////
                 // Update state
                 ///////////////////////////////////////////////////////////////////////////
 
-                anim.step(delta);
-                fts.push_front(delta.as_millis() as f64);
-                fts.truncate(frame_batch);
+                for _ in 0..clock.tick() {
+                    anim.step(clock.timestep);
+                    x += clock.timestep.as_millis() as f32 / move_speed;
+                }
 
                 ///////////////////////////////////////////////////////////////////////////
                 // Prepare sprite batch
@@ -163,8 +155,6 @@ fn main() -> Result<(), std::io::Error> {
 
                 let mut batch = sprite2d::Batch::new(sprite.w, sprite.h);
 
-                x += delta.as_millis() as f32 / move_speed;
-
                 for i in 0..rows {
                     let y = i as f32 * sh;
 
@@ -222,7 +212,7 @@ fn main() -> Result<(), std::io::Error> {
                 r.present(frame);
 
                 if frames_total >= frame_batch && frames_total % frame_batch == 0 {
-                    let average_ft = fts.iter().sum::<f64>() / fts.len() as f64;
+                    let average_ft = clock.stats().avg().unwrap_or_default().as_secs_f64() * 1000.;
 
                     println!("sprites/frame: {}", rows * cols);
                     println!("time/frame:    {:.2}ms\n", average_ft);
@@ -0,0 +1,118 @@
+#![deny(clippy::all)]
+#![allow(clippy::unreadable_literal)]
+#![allow(clippy::single_match)]
+
+use rgx::core::*;
+use rgx::kit;
+use rgx::kit::shape2d::{Batch, Fill, Shape};
+
+use rgx::math::*;
+
+use winit::{
+    event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::Window,
+};
+
+use std::time;
+
+/// Number of copies of the batch drawn each frame.
+const COPIES: usize = 8;
+
+fn main() -> Result<(), std::io::Error> {
+    let event_loop = EventLoop::new();
+    let window = Window::new(&event_loop).unwrap();
+
+    ///////////////////////////////////////////////////////////////////////////
+    // Setup renderer
+    ///////////////////////////////////////////////////////////////////////////
+
+    let mut r = Renderer::new(&window)?;
+    let mut win = window.inner_size();
+
+    let pip: kit::shape2d::Pipeline = r.pipeline(Blending::default());
+    let mut chain = r.swap_chain(win.width, win.height, PresentMode::default());
+
+    // A single batch, uploaded once, and drawn with a different model
+    // transform for each copy.
+    let buffer = Batch::singleton(
+        Shape::rect([0., -16.], [128., 16.])
+            .stroke(2., Rgba::WHITE)
+            .fill(Fill::Solid(Rgba::new(0.2, 0.6, 1.0, 0.5))),
+    )
+    .finish(&r);
+    let mut transforms = pip.transforms_with_capacity(&r, COPIES);
+    let start = time::Instant::now();
+
+    ///////////////////////////////////////////////////////////////////////////
+    // Render loop
+    ///////////////////////////////////////////////////////////////////////////
+
+    event_loop.run(move |event, _, control_flow| match event {
+        Event::WindowEvent { event, .. } => match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::Escape),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            }
+            | WindowEvent::CloseRequested => {
+                *control_flow = ControlFlow::Exit;
+            }
+            WindowEvent::Resized(size) => {
+                win = size;
+                chain = r.swap_chain(win.width, win.height, PresentMode::default());
+            }
+            _ => {}
+        },
+        Event::MainEventsCleared => {
+            *control_flow = ControlFlow::Poll;
+
+            let t = start.elapsed().as_secs_f32();
+            let center = Vector3::new(win.width as f32 / 2., win.height as f32 / 2., 0.);
+
+            // Spokes around the center of the window, slowly turning.
+            let models: Vec<Matrix4<f32>> = (0..COPIES)
+                .map(|i| {
+                    let angle = t * 0.5 + i as f32 * std::f32::consts::PI * 2. / COPIES as f32;
+                    Matrix4::from_translation(center) * Matrix4::from_angle_z(angle)
+                })
+                .collect();
+
+            ///////////////////////////////////////////////////////////////////////////
+            // Create frame
+            ///////////////////////////////////////////////////////////////////////////
+
+            let mut frame = r.frame();
+            let out = chain.next();
+
+            r.update_pipeline(
+                &pip,
+                kit::ortho(out.width, out.height, Default::default()),
+                &mut frame,
+            );
+            transforms.update(&models, &r, &mut frame);
+
+            ///////////////////////////////////////////////////////////////////////////
+            // Draw frame
+            ///////////////////////////////////////////////////////////////////////////
+
+            {
+                let pass = &mut frame.pass(PassOp::Clear(Rgba::TRANSPARENT), &out);
+
+                pass.set_pipeline(&pip);
+
+                // Bind each transform in turn, by its offset in the buffer.
+                for i in 0..transforms.len() {
+                    pass.set_binding(&transforms.binding, &[transforms.offset(i)]);
+                    pass.draw_buffer(&buffer);
+                }
+            }
+            r.present(frame);
+        }
+        _ => {}
+    });
+}
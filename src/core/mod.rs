@@ -19,6 +19,18 @@ pub trait Renderable {
     }
 }
 
+/// Like [`Renderable`], for geometry which is uploaded as indexed vertices.
+pub trait IndexedRenderable {
+    fn indexed_buffer(&self, r: &Renderer) -> IndexedBuffer;
+
+    fn finish_indexed(self, r: &Renderer) -> IndexedBuffer
+    where
+        Self: std::marker::Sized,
+    {
+        self.indexed_buffer(r)
+    }
+}
+
 impl Rgba {
     fn to_wgpu(&self) -> wgpu::Color {
         wgpu::Color {
@@ -533,9 +545,42 @@ impl Draw for VertexBuffer {
 
 #[derive(Debug)]
 pub struct IndexBuffer {
+    pub size: u32,
     wgpu: wgpu::Buffer,
 }
 
+/// The type of the indices of an [`IndexBuffer`]. Pipelines draw 16-bit
+/// indices, unless created with [`Renderer::indexed_pipeline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    Uint16,
+    Uint32,
+}
+
+impl IndexFormat {
+    fn to_wgpu(self) -> wgpu::IndexFormat {
+        match self {
+            IndexFormat::Uint16 => wgpu::IndexFormat::Uint16,
+            IndexFormat::Uint32 => wgpu::IndexFormat::Uint32,
+        }
+    }
+}
+
+/// A vertex buffer drawn through an index buffer, so that vertices shared by
+/// several triangles are only stored once.
+#[derive(Debug)]
+pub struct IndexedBuffer {
+    pub vertices: VertexBuffer,
+    pub indices: IndexBuffer,
+}
+
+impl Draw for IndexedBuffer {
+    fn draw(&self, binding: &BindingGroup, pass: &mut Pass) {
+        pass.set_binding(binding, &[]);
+        pass.draw_buffer_indexed(self);
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum VertexFormat {
     Float,
@@ -881,6 +926,11 @@ impl<'a> Pass<'a> {
         self.set_vertex_buffer(buf);
         self.wgpu.draw(range, 0..1);
     }
+    pub fn draw_buffer_indexed(&mut self, buf: &IndexedBuffer) {
+        self.set_vertex_buffer(&buf.vertices);
+        self.set_index_buffer(&buf.indices);
+        self.wgpu.draw_indexed(0..buf.indices.size, 0, 0..1);
+    }
     pub fn draw_indexed(&mut self, indices: Range<u32>, instances: Range<u32>) {
         self.wgpu.draw_indexed(indices, 0, instances)
     }
@@ -1054,6 +1104,37 @@ impl Renderer {
         self.device.create_buffer(verts)
     }
 
+    pub fn index_buffer(&self, indices: &[u16]) -> IndexBuffer {
+        self.device.create_index(indices)
+    }
+
+    /// Create a buffer of 32-bit indices, to be drawn with a pipeline from
+    /// [`Renderer::indexed_pipeline`].
+    pub fn index_buffer32(&self, indices: &[u32]) -> IndexBuffer {
+        self.device.create_index32(indices)
+    }
+
+    pub fn indexed_buffer<T>(&self, verts: &[T], indices: &[u16]) -> IndexedBuffer
+    where
+        T: 'static + Copy,
+    {
+        IndexedBuffer {
+            vertices: self.device.create_buffer(verts),
+            indices: self.device.create_index(indices),
+        }
+    }
+
+    /// Like [`Renderer::indexed_buffer`], with 32-bit indices.
+    pub fn indexed_buffer32<T>(&self, verts: &[T], indices: &[u32]) -> IndexedBuffer
+    where
+        T: 'static + Copy,
+    {
+        IndexedBuffer {
+            vertices: self.device.create_buffer(verts),
+            indices: self.device.create_index32(indices),
+        }
+    }
+
     pub fn uniform_buffer<T>(&self, buf: &[T]) -> UniformBuffer
     where
         T: 'static + Copy,
@@ -1070,6 +1151,15 @@ impl Renderer {
     }
 
     pub fn pipeline<T>(&self, blending: Blending) -> T
+    where
+        T: AbstractPipeline<'static>,
+    {
+        self.indexed_pipeline(blending, IndexFormat::Uint16)
+    }
+
+    /// Like [`Renderer::pipeline`], for drawing index buffers of the given
+    /// format. A pipeline only draws indices of a single format.
+    pub fn indexed_pipeline<T>(&self, blending: Blending, format: IndexFormat) -> T
     where
         T: AbstractPipeline<'static>,
     {
@@ -1087,7 +1177,7 @@ impl Renderer {
 
         T::setup(
             self.device
-                .create_pipeline(pip_layout, vertex_layout, blending, &vs, &fs),
+                .create_pipeline(pip_layout, vertex_layout, blending, format, &vs, &fs),
             &self.device,
         )
     }
@@ -1149,16 +1239,30 @@ impl Renderer {
 
     // MUTABLE API ////////////////////////////////////////////////////////////
 
-    pub fn update_pipeline<'a, T>(&mut self, pip: &'a T, p: T::PrepareContext, f: &mut Frame)
+    pub fn update_pipeline<'a, T, P>(&mut self, pip: &'a T, p: P, f: &mut Frame)
     where
         T: AbstractPipeline<'a>,
+        P: Into<T::PrepareContext>,
     {
-        if let Some((buf, unifs)) = pip.prepare(p) {
+        if let Some((buf, unifs)) = pip.prepare(p.into()) {
             self.device
                 .update_uniform_buffer::<T::Uniforms>(unifs.as_slice(), buf, &mut f.encoder);
         }
     }
 
+    /// Overwrite part of a vertex buffer as part of the given frame, starting
+    /// at the vertex `offset`.
+    pub fn update_vertex_buffer<T: Copy + 'static>(
+        &mut self,
+        buf: &VertexBuffer,
+        offset: usize,
+        verts: &[T],
+        f: &mut Frame,
+    ) {
+        self.device
+            .update_vertex_buffer(verts, offset, buf, &mut f.encoder);
+    }
+
     pub fn frame(&mut self) -> Frame {
         let encoder = self.device.create_command_encoder();
         Frame::new(encoder)
@@ -1399,7 +1503,10 @@ impl Device {
         VertexBuffer {
             wgpu: self
                 .device
-                .create_buffer_mapped(vertices.len(), wgpu::BufferUsage::VERTEX)
+                .create_buffer_mapped(
+                    vertices.len(),
+                    wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+                )
                 .fill_from_slice(vertices),
             size: vertices.len() as u32,
         }
@@ -1429,7 +1536,21 @@ impl Device {
             .device
             .create_buffer_mapped(indices.len(), wgpu::BufferUsage::INDEX)
             .fill_from_slice(indices);
-        IndexBuffer { wgpu: index_buf }
+        IndexBuffer {
+            wgpu: index_buf,
+            size: indices.len() as u32,
+        }
+    }
+
+    pub fn create_index32(&self, indices: &[u32]) -> IndexBuffer {
+        let index_buf = self
+            .device
+            .create_buffer_mapped(indices.len(), wgpu::BufferUsage::INDEX)
+            .fill_from_slice(indices);
+        IndexBuffer {
+            wgpu: index_buf,
+            size: indices.len() as u32,
+        }
     }
 
     pub fn create_sampler(&self, min_filter: Filter, mag_filter: Filter) -> Sampler {
@@ -1491,6 +1612,31 @@ impl Device {
         );
     }
 
+    /// Overwrite part of a vertex buffer, starting at the vertex `offset`.
+    pub fn update_vertex_buffer<T: Copy + 'static>(
+        &self,
+        slice: &[T],
+        offset: usize,
+        buf: &VertexBuffer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let src = self
+            .device
+            .create_buffer_mapped::<T>(
+                slice.len(),
+                wgpu::BufferUsage::COPY_SRC | wgpu::BufferUsage::MAP_WRITE,
+            )
+            .fill_from_slice(slice);
+
+        encoder.copy_buffer_to_buffer(
+            &src,
+            0,
+            &buf.wgpu,
+            (std::mem::size_of::<T>() * offset) as wgpu::BufferAddress,
+            std::mem::size_of_val(slice) as wgpu::BufferAddress,
+        );
+    }
+
     // MUTABLE API ////////////////////////////////////////////////////////////
 
     pub fn submit(&mut self, cmds: &[wgpu::CommandBuffer]) {
@@ -1504,6 +1650,7 @@ impl Device {
         pipeline_layout: PipelineLayout,
         vertex_layout: VertexLayout,
         blending: Blending,
+        index_format: IndexFormat,
         vs: &Shader,
         fs: &Shader,
     ) -> Pipeline {
@@ -1564,7 +1711,7 @@ impl Device {
                     stencil_read_mask: 0,
                     stencil_write_mask: 0,
                 }),
-                index_format: wgpu::IndexFormat::Uint16,
+                index_format: index_format.to_wgpu(),
                 vertex_buffers: &[vertex_attrs],
                 sample_count: 1,
                 sample_mask: !0,
@@ -63,6 +63,23 @@ impl TransformBuffer {
         }
     }
 
+    /// The dynamic offset of the transform at the given index.
+    pub fn offset(&self, index: usize) -> u64 {
+        assert!(
+            index < self.size,
+            "TransformBuffer::offset: index out of bounds"
+        );
+        index as u64 * AlignedBuffer::ALIGNMENT
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
     pub fn offsets(&self) -> std::iter::StepBy<std::ops::Range<u64>> {
         let max: u64 = self.size as u64 * AlignedBuffer::ALIGNMENT;
         (0..max).step_by(AlignedBuffer::ALIGNMENT as usize)
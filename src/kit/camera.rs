@@ -0,0 +1,407 @@
+use crate::kit::{self, Origin, View};
+use crate::math::{Matrix4, Point2, Vector2};
+use crate::rect::Rect;
+
+///////////////////////////////////////////////////////////////////////////
+// Camera2D
+///////////////////////////////////////////////////////////////////////////
+
+/// A 2D camera, with pan, zoom and rotation.
+///
+/// The camera `position` is the world point shown at the center of the
+/// viewport. Screen coordinates are window coordinates in pixels, with the
+/// origin at the top-left and the y-axis pointing down, as reported by
+/// windowing libraries. The camera's [`Origin`] determines how the pixel
+/// space of the projection relates to screen coordinates.
+///
+/// ```
+/// use rgx::kit::camera::Camera2D;
+/// use rgx::kit::Origin;
+/// use rgx::math::Point2;
+///
+/// let mut cam = Camera2D::new(640, 480, Origin::TopLeft);
+/// cam.look_at(Point2::new(0., 0.));
+/// cam.zoom_at(2., Point2::new(320., 240.));
+///
+/// assert_eq!(cam.world_to_screen(Point2::new(10., 10.)), Point2::new(340., 260.));
+/// assert_eq!(cam.screen_to_world(Point2::new(340., 260.)), Point2::new(10., 10.));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera2D {
+    /// World point at the center of the viewport.
+    pub position: Point2<f32>,
+    /// Zoom factor, where `1.0` maps one world unit to one pixel.
+    pub zoom: f32,
+    /// Camera rotation in radians.
+    pub rotation: f32,
+    /// Viewport width in pixels.
+    pub width: u32,
+    /// Viewport height in pixels.
+    pub height: u32,
+    /// Origin of the projection's pixel space.
+    pub origin: Origin,
+    /// World-space area the viewport is kept within.
+    pub bounds: Option<Rect<f32>>,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Snap to integer zoom levels and whole pixels, for crisp pixel art.
+    pub pixel_snap: bool,
+}
+
+impl Camera2D {
+    /// Create a camera showing the world exactly as `kit::ortho` would.
+    pub fn new(width: u32, height: u32, origin: Origin) -> Self {
+        Self {
+            position: Point2::new(width as f32 / 2., height as f32 / 2.),
+            zoom: 1.,
+            rotation: 0.,
+            width,
+            height,
+            origin,
+            bounds: None,
+            min_zoom: 1. / 64.,
+            max_zoom: 64.,
+            pixel_snap: false,
+        }
+    }
+
+    pub fn bounds(mut self, bounds: Rect<f32>) -> Self {
+        self.bounds = Some(bounds);
+        self.clamp();
+        self
+    }
+
+    pub fn zoom_limits(mut self, min: f32, max: f32) -> Self {
+        assert!(
+            min > 0. && min <= max,
+            "Camera2D::zoom_limits: invalid limits"
+        );
+        self.min_zoom = min;
+        self.max_zoom = max;
+        self.zoom = self.zoom.clamp(min, max);
+        self.clamp();
+        self
+    }
+
+    pub fn pixel_snap(mut self, enabled: bool) -> Self {
+        self.pixel_snap = enabled;
+        self
+    }
+
+    /// Resize the viewport, keeping the camera centered on the same point.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.clamp();
+    }
+
+    /// Center the camera on the given world point.
+    pub fn look_at(&mut self, position: Point2<f32>) {
+        self.position = position;
+        self.clamp();
+    }
+
+    /// Pan the camera by a screen-space delta, eg. from a mouse drag. The
+    /// world moves along with the delta.
+    pub fn pan(&mut self, delta: Vector2<f32>) {
+        let zero = self.screen_to_world(Point2::new(0., 0.));
+        let moved = self.screen_to_world(Point2::new(delta.x, delta.y));
+
+        self.position = self.position - (moved - zero);
+        self.clamp();
+    }
+
+    /// Translate the camera by a world-space vector.
+    pub fn translate(&mut self, v: Vector2<f32>) {
+        self.position = self.position + v;
+        self.clamp();
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+        self.clamp();
+    }
+
+    /// Zoom in or out, keeping the world point under the given screen
+    /// point fixed.
+    pub fn zoom_at(&mut self, zoom: f32, screen: Point2<f32>) {
+        let before = self.screen_to_world(screen);
+        self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+        let after = self.screen_to_world(screen);
+
+        self.position = self.position + (before - after);
+        self.clamp();
+    }
+
+    /// Set the camera rotation, in radians, around its position.
+    pub fn set_rotation(&mut self, angle: f32) {
+        self.rotation = angle;
+        self.clamp();
+    }
+
+    pub fn rotate(&mut self, angle: f32) {
+        self.set_rotation(self.rotation + angle);
+    }
+
+    /// The zoom factor actually used for rendering. With `pixel_snap`, this
+    /// is an integer, or the reciprocal of an integer when zoomed out.
+    pub fn effective_zoom(&self) -> f32 {
+        if !self.pixel_snap {
+            return self.zoom;
+        }
+        if self.zoom >= 1. {
+            self.zoom.round()
+        } else {
+            1. / (1. / self.zoom).round()
+        }
+    }
+
+    /// The camera position actually used for rendering. With `pixel_snap`
+    /// and no rotation, the position is adjusted so that world-space integer
+    /// coordinates land on whole pixels.
+    pub fn effective_position(&self) -> Point2<f32> {
+        if !self.pixel_snap || self.rotation != 0. {
+            return self.position;
+        }
+        let z = self.effective_zoom();
+        let (cx, cy) = self.center();
+
+        Point2::new(
+            (cx - (cx - self.position.x * z).round()) / z,
+            (cy - (cy - self.position.y * z).round()) / z,
+        )
+    }
+
+    /// The view matrix, transforming world space into pixel space.
+    pub fn view(&self) -> Matrix4<f32> {
+        let z = self.effective_zoom();
+        let p = self.effective_position();
+        let (cx, cy) = self.center();
+        let (sin, cos) = (-self.rotation).sin_cos();
+
+        // Translate by `-p`, scale by `z`, rotate, then center in the viewport.
+        #[rustfmt::skip]
+        let m = Matrix4::new(
+            cos * z,  sin * z,  0., 0.,
+            -sin * z, cos * z,  0., 0.,
+            0.,       0.,       1., 0.,
+            cx - z * (cos * p.x - sin * p.y),
+            cy - z * (sin * p.x + cos * p.y),
+            0., 1.,
+        );
+        m
+    }
+
+    /// The projection matrix, transforming pixel space into clip space.
+    pub fn projection(&self) -> Matrix4<f32> {
+        kit::ortho(self.width, self.height, self.origin)
+    }
+
+    /// The combined projection and view matrix.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        self.projection() * self.view()
+    }
+
+    /// Convert a world-space point to screen coordinates.
+    pub fn world_to_screen(&self, p: Point2<f32>) -> Point2<f32> {
+        let z = self.effective_zoom();
+        let c = self.effective_position();
+        let (cx, cy) = self.center();
+        let (sin, cos) = (-self.rotation).sin_cos();
+        let (dx, dy) = (p.x - c.x, p.y - c.y);
+
+        let px = cx + z * (cos * dx - sin * dy);
+        let py = cy + z * (sin * dx + cos * dy);
+
+        self.flip(Point2::new(px, py))
+    }
+
+    /// Convert screen coordinates to a world-space point.
+    pub fn screen_to_world(&self, p: Point2<f32>) -> Point2<f32> {
+        let z = self.effective_zoom();
+        let c = self.effective_position();
+        let (cx, cy) = self.center();
+        let (sin, cos) = self.rotation.sin_cos();
+        let p = self.flip(p);
+        let (dx, dy) = ((p.x - cx) / z, (p.y - cy) / z);
+
+        Point2::new(c.x + cos * dx - sin * dy, c.y + sin * dx + cos * dy)
+    }
+
+    /// The world-space bounding box of the area visible in the viewport.
+    pub fn visible(&self) -> Rect<f32> {
+        let (hw, hh) = self.half_extents();
+        let c = self.effective_position();
+
+        Rect::new(c.x - hw, c.y - hh, c.x + hw, c.y + hh)
+    }
+
+    ///////////////////////////////////////////////////////////////////////
+
+    fn center(&self) -> (f32, f32) {
+        (self.width as f32 / 2., self.height as f32 / 2.)
+    }
+
+    /// Convert between screen coordinates and pixel space. This is its own
+    /// inverse.
+    fn flip(&self, p: Point2<f32>) -> Point2<f32> {
+        match self.origin {
+            Origin::TopLeft => p,
+            Origin::BottomLeft => Point2::new(p.x, self.height as f32 - p.y),
+        }
+    }
+
+    /// Half the size of the visible area in world units, accounting for
+    /// rotation.
+    fn half_extents(&self) -> (f32, f32) {
+        let z = self.effective_zoom();
+        let (cx, cy) = self.center();
+        let (sin, cos) = self.rotation.sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+
+        ((cx * cos + cy * sin) / z, (cx * sin + cy * cos) / z)
+    }
+
+    /// Keep the visible area within the camera bounds. If the visible area
+    /// is larger than the bounds, the camera is centered on them.
+    fn clamp(&mut self) {
+        let bounds = match self.bounds {
+            Some(b) => b,
+            None => return,
+        };
+        let (min, max) = (bounds.min(), bounds.max());
+        let (hw, hh) = self.half_extents();
+
+        let clamp = |v: f32, lo: f32, hi: f32, half: f32| {
+            if hi - lo <= half * 2. {
+                (lo + hi) / 2.
+            } else {
+                v.clamp(lo + half, hi - half)
+            }
+        };
+        self.position = Point2::new(
+            clamp(self.position.x, min.x, max.x, hw),
+            clamp(self.position.y, min.y, max.y, hh),
+        );
+    }
+}
+
+impl From<&Camera2D> for View {
+    fn from(cam: &Camera2D) -> Self {
+        View {
+            projection: cam.projection(),
+            transform: cam.view(),
+        }
+    }
+}
+
+impl From<Camera2D> for View {
+    fn from(cam: Camera2D) -> Self {
+        View::from(&cam)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::Vector4;
+
+    fn approx(a: Point2<f32>, b: Point2<f32>) -> bool {
+        (a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3
+    }
+
+    /// Project a world point through the camera matrices, and convert the
+    /// resulting clip-space coordinates to screen coordinates.
+    fn project(cam: &Camera2D, p: Point2<f32>) -> Point2<f32> {
+        let clip = cam.matrix() * Vector4::new(p.x, p.y, 0., 1.);
+        let (w, h) = (cam.width as f32, cam.height as f32);
+
+        // Clip space has y pointing up.
+        Point2::new((clip.x + 1.) / 2. * w, (1. - clip.y) / 2. * h)
+    }
+
+    #[test]
+    fn test_matrices_agree() {
+        for origin in &[Origin::TopLeft, Origin::BottomLeft] {
+            let mut cam = Camera2D::new(320, 200, *origin);
+            cam.look_at(Point2::new(-40., 25.));
+            cam.set_zoom(3.);
+            cam.set_rotation(0.6);
+
+            for p in &[
+                Point2::new(0., 0.),
+                Point2::new(-13., 7.5),
+                Point2::new(90., -3.),
+            ] {
+                let screen = cam.world_to_screen(*p);
+
+                assert!(approx(screen, project(&cam, *p)), "{:?}", origin);
+                assert!(approx(cam.screen_to_world(screen), *p), "{:?}", origin);
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_matches_ortho() {
+        let cam = Camera2D::new(320, 200, Origin::BottomLeft);
+        let p = Point2::new(10., 20.);
+
+        assert_eq!(cam.view(), Matrix4::identity());
+        assert_eq!(cam.world_to_screen(p), Point2::new(10., 180.));
+    }
+
+    #[test]
+    fn test_zoom_at() {
+        let mut cam = Camera2D::new(320, 200, Origin::TopLeft);
+        let cursor = Point2::new(40., 30.);
+        let before = cam.screen_to_world(cursor);
+
+        cam.zoom_at(4., cursor);
+
+        assert!(approx(cam.screen_to_world(cursor), before));
+    }
+
+    #[test]
+    fn test_pan() {
+        let mut cam = Camera2D::new(320, 200, Origin::BottomLeft);
+        cam.set_zoom(2.);
+        let p = Point2::new(100., 100.);
+        let before = cam.world_to_screen(p);
+
+        cam.pan(Vector2::new(10., -6.));
+
+        assert!(approx(
+            cam.world_to_screen(p),
+            Point2::new(before.x + 10., before.y - 6.)
+        ));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut cam =
+            Camera2D::new(100, 100, Origin::TopLeft).bounds(Rect::new(0., 0., 400., 300.));
+
+        cam.look_at(Point2::new(-100., 1000.));
+        assert_eq!(cam.visible(), Rect::new(0., 200., 100., 300.));
+
+        // Zoomed out beyond the bounds, the camera centers on them.
+        cam.set_zoom(0.1);
+        assert_eq!(cam.position, Point2::new(200., 150.));
+    }
+
+    #[test]
+    fn test_pixel_snap() {
+        let mut cam = Camera2D::new(101, 100, Origin::TopLeft).pixel_snap(true);
+
+        cam.set_zoom(2.7);
+        assert_eq!(cam.effective_zoom(), 3.);
+        cam.set_zoom(0.3);
+        assert_eq!(cam.effective_zoom(), 1. / 3.);
+
+        cam.set_zoom(3.);
+        cam.look_at(Point2::new(10.1, 20.45));
+
+        let s = cam.world_to_screen(Point2::new(4., 7.));
+        assert!(approx(s, Point2::new(s.x.round(), s.y.round())));
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::VecDeque;
+use std::time;
+
+///////////////////////////////////////////////////////////////////////////
+// Clock
+///////////////////////////////////////////////////////////////////////////
+
+/// A fixed-timestep clock.
+///
+/// Frame deltas are accumulated and consumed in steps of a fixed size, which
+/// keeps the simulation deterministic regardless of the frame rate. The time
+/// left over in the accumulator is exposed as an interpolation factor via
+/// [`Clock::alpha`], to blend between the previous and current state when
+/// rendering.
+///
+/// ```
+/// use rgx::kit::clock::Clock;
+/// use std::time::Duration;
+///
+/// let mut clock = Clock::new(Duration::from_millis(10));
+///
+/// assert_eq!(clock.advance(Duration::from_millis(25)), 2);
+/// assert_eq!(clock.alpha(), 0.5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Clock {
+    /// Duration of a single fixed step.
+    pub timestep: time::Duration,
+    /// Frame deltas are clamped to this duration.
+    pub max_delta: time::Duration,
+    /// Maximum number of steps consumed per frame.
+    pub max_steps: u32,
+
+    accumulator: time::Duration,
+    elapsed: time::Duration,
+    ticks: u64,
+    last: Option<time::Instant>,
+    stats: FrameStats,
+}
+
+impl Clock {
+    /// Number of frames kept in the statistics window by default.
+    pub const HISTORY: usize = 120;
+
+    pub fn new(timestep: time::Duration) -> Self {
+        Self {
+            timestep,
+            max_delta: time::Duration::from_millis(250),
+            max_steps: 8,
+            accumulator: time::Duration::from_secs(0),
+            elapsed: time::Duration::from_secs(0),
+            ticks: 0,
+            last: None,
+            stats: FrameStats::new(Self::HISTORY),
+        }
+    }
+
+    /// Create a clock ticking `hz` times per second.
+    pub fn from_hz(hz: u32) -> Self {
+        assert!(hz > 0, "Clock::from_hz: frequency must be non-zero");
+
+        Self::new(time::Duration::from_secs(1) / hz)
+    }
+
+    pub fn max_delta(mut self, max_delta: time::Duration) -> Self {
+        self.max_delta = max_delta;
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn history(mut self, frames: usize) -> Self {
+        self.stats = FrameStats::new(frames);
+        self
+    }
+
+    /// Measure the time since the last call and advance the clock by it.
+    /// Returns the number of fixed steps to run this frame.
+    pub fn tick(&mut self) -> u32 {
+        let now = time::Instant::now();
+        let delta = match self.last {
+            Some(last) => now.duration_since(last),
+            None => time::Duration::from_secs(0),
+        };
+        self.last = Some(now);
+        self.advance(delta)
+    }
+
+    /// Advance the clock by the given frame delta. Returns the number of fixed
+    /// steps to run this frame.
+    ///
+    /// To avoid a "spiral of death", where each frame takes longer to simulate
+    /// than the last, the delta is clamped to `max_delta`, and any time that
+    /// would require more than `max_steps` steps is dropped.
+    pub fn advance(&mut self, delta: time::Duration) -> u32 {
+        self.stats.record(delta);
+
+        let delta = delta.min(self.max_delta);
+        let mut steps = 0;
+
+        self.accumulator += delta;
+
+        while self.accumulator >= self.timestep && self.timestep.as_nanos() > 0 {
+            if steps == self.max_steps {
+                // Drop the time we can't catch up on, but keep the remainder
+                // so that interpolation remains smooth.
+                self.accumulator = time::Duration::from_nanos(
+                    (self.accumulator.as_nanos() % self.timestep.as_nanos()) as u64,
+                );
+                break;
+            }
+            self.accumulator -= self.timestep;
+            self.elapsed += self.timestep;
+            self.ticks += 1;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Interpolation factor between the previous and the current step,
+    /// in the range `[0, 1)`.
+    pub fn alpha(&self) -> f32 {
+        if self.timestep.as_nanos() == 0 {
+            return 0.;
+        }
+        (self.accumulator.as_secs_f64() / self.timestep.as_secs_f64()) as f32
+    }
+
+    /// Total simulated time, ie. the sum of all steps taken.
+    pub fn elapsed(&self) -> time::Duration {
+        self.elapsed
+    }
+
+    /// Total number of steps taken.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Frame-timing statistics.
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    /// Reset the clock, keeping its configuration.
+    pub fn reset(&mut self) {
+        self.accumulator = time::Duration::from_secs(0);
+        self.elapsed = time::Duration::from_secs(0);
+        self.ticks = 0;
+        self.last = None;
+        self.stats.clear();
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// FrameStats
+///////////////////////////////////////////////////////////////////////////
+
+/// Rolling frame-time statistics over a fixed window of frames.
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    frames: VecDeque<time::Duration>,
+    capacity: usize,
+    total: time::Duration,
+}
+
+impl FrameStats {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "FrameStats::new: capacity must be non-zero");
+
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+            total: time::Duration::from_secs(0),
+        }
+    }
+
+    /// Record a frame time, evicting the oldest one if the window is full.
+    pub fn record(&mut self, frame: time::Duration) {
+        if self.frames.len() == self.capacity {
+            if let Some(oldest) = self.frames.pop_front() {
+                self.total -= oldest;
+            }
+        }
+        self.frames.push_back(frame);
+        self.total += frame;
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.total = time::Duration::from_secs(0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Most recently recorded frame time.
+    pub fn last(&self) -> Option<time::Duration> {
+        self.frames.back().copied()
+    }
+
+    pub fn min(&self) -> Option<time::Duration> {
+        self.frames.iter().min().copied()
+    }
+
+    pub fn max(&self) -> Option<time::Duration> {
+        self.frames.iter().max().copied()
+    }
+
+    pub fn avg(&self) -> Option<time::Duration> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        Some(self.total / self.frames.len() as u32)
+    }
+
+    /// Frames per second, based on the average frame time.
+    pub fn fps(&self) -> f64 {
+        match self.avg() {
+            Some(avg) if avg.as_nanos() > 0 => 1. / avg.as_secs_f64(),
+            _ => 0.,
+        }
+    }
+
+    /// Frame time at the given percentile, using the nearest-rank method.
+    /// For example, `percentile(99.)` is the frame time that 99% of frames
+    /// are equal to or faster than.
+    pub fn percentile(&self, p: f64) -> Option<time::Duration> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<time::Duration> = self.frames.iter().copied().collect();
+        sorted.sort();
+
+        let p = p.clamp(0., 100.);
+        let rank = (p / 100. * sorted.len() as f64).ceil() as usize;
+
+        Some(sorted[rank.max(1) - 1])
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(Clock::HISTORY)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    #[should_panic(expected = "frequency must be non-zero")]
+    fn test_from_hz_zero() {
+        Clock::from_hz(0);
+    }
+
+    #[test]
+    fn test_fixed_steps() {
+        let mut clock = Clock::new(ms(10));
+
+        assert_eq!(clock.advance(ms(5)), 0);
+        assert_eq!(clock.alpha(), 0.5);
+        assert_eq!(clock.advance(ms(5)), 1);
+        assert_eq!(clock.alpha(), 0.);
+        assert_eq!(clock.advance(ms(34)), 3);
+        assert!((clock.alpha() - 0.4).abs() < 1e-6);
+        assert_eq!(clock.ticks(), 4);
+        assert_eq!(clock.elapsed(), ms(40));
+    }
+
+    #[test]
+    fn test_spiral_of_death() {
+        let mut clock = Clock::new(ms(10)).max_delta(ms(100)).max_steps(4);
+
+        // Clamped to 100ms, which would be 10 steps, capped to 4.
+        assert_eq!(clock.advance(ms(1000)), 4);
+        assert_eq!(clock.alpha(), 0.);
+        assert_eq!(clock.advance(ms(15)), 1);
+        assert_eq!(clock.alpha(), 0.5);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut clock = Clock::new(ms(10)).history(4);
+
+        for n in &[10, 20, 30, 40, 50] {
+            clock.advance(ms(*n));
+        }
+        let stats = clock.stats();
+
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats.last(), Some(ms(50)));
+        assert_eq!(stats.min(), Some(ms(20)));
+        assert_eq!(stats.max(), Some(ms(50)));
+        assert_eq!(stats.avg(), Some(ms(35)));
+        assert_eq!(stats.percentile(50.), Some(ms(30)));
+        assert_eq!(stats.percentile(99.), Some(ms(50)));
+        assert_eq!(stats.percentile(0.), Some(ms(20)));
+        assert!((stats.fps() - 1000. / 35.).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,568 @@
+//! Collision and intersection tests between rectangles, circles, segments
+//! and rays.
+//!
+//! Overlap tests return a [`Contact`], whose normal and depth say how to move
+//! the first shape out of the second. Ray and sweep tests return a [`Hit`],
+//! with the time of impact along the ray or motion:
+//!
+//! ```
+//! use rgx::kit::collision::{self, Ray};
+//! use rgx::kit::shape2d::Circle;
+//! use rgx::math::{Point2, Vector2};
+//! use rgx::rect::Rect;
+//!
+//! let wall = Rect::new(10., 0., 20., 10.);
+//! let ball = Circle {
+//!     position: Point2::new(8., 5.),
+//!     radius: 4.,
+//!     sides: Circle::AUTO,
+//! };
+//!
+//! let contact = collision::circle_rect(&ball, &wall).unwrap();
+//! assert_eq!(contact.normal, Vector2::new(-1., 0.));
+//! assert_eq!(contact.depth, 2.);
+//!
+//! let ray = Ray::new(Point2::new(0., 5.), Vector2::new(1., 0.));
+//! let hit = collision::ray_rect(&ray, &wall).unwrap();
+//! assert_eq!(hit.time, 10.);
+//! assert_eq!(hit.point, Point2::new(10., 5.));
+//! ```
+//!
+//! Rectangles may be given in any orientation; they are normalized with
+//! [`Rect::min`] and [`Rect::max`].
+use crate::kit::shape2d::polygon::{cross, normal};
+use crate::kit::shape2d::{Circle, Line, Rotation};
+use crate::math::{Point2, Vector2};
+use crate::rect::Rect;
+
+/// The overlap between two shapes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contact {
+    /// Unit normal pointing from the second shape towards the first.
+    pub normal: Vector2<f32>,
+    /// Distance the first shape has to move along the normal to stop
+    /// overlapping the second.
+    pub depth: f32,
+    /// A point where the shapes touch.
+    pub point: Point2<f32>,
+}
+
+/// Where a ray, segment or moving shape hits another shape.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hit {
+    /// Time of impact, as a multiple of the ray direction, segment or
+    /// motion.
+    pub time: f32,
+    /// Point of impact.
+    pub point: Point2<f32>,
+    /// Unit normal of the surface that was hit, facing the incoming ray or
+    /// shape. Zero if the ray starts inside the shape.
+    pub normal: Vector2<f32>,
+}
+
+/// A half-line, starting at an origin and extending along a direction.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Point2<f32>,
+    pub direction: Vector2<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Point2<f32>, direction: Vector2<f32>) -> Self {
+        Self { origin, direction }
+    }
+
+    /// The point at the given time along the ray.
+    pub fn at(&self, time: f32) -> Point2<f32> {
+        self.origin + self.direction * time
+    }
+
+    /// A hit at the ray origin, for rays starting inside a shape.
+    fn inside(&self) -> Hit {
+        Hit {
+            time: 0.,
+            point: self.origin,
+            normal: Vector2::new(0., 0.),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Segments and rays
+///////////////////////////////////////////////////////////////////////////////
+
+/// Intersect two segments. The time of the hit is the fraction of `a` at
+/// which it crosses `b`, and the normal is that of `b`, facing the start of
+/// `a`. Parallel segments never intersect, even when they overlap.
+pub fn segment_segment(a: &Line, b: &Line) -> Option<Hit> {
+    let r = a.p2 - a.p1;
+    let s = b.p2 - b.p1;
+    let denom = r.x * s.y - r.y * s.x;
+
+    // Compare the cross product to the lengths of the segments, so that short
+    // segments which cross aren't mistaken for parallel ones.
+    if denom.abs() <= f32::EPSILON * r.magnitude() * s.magnitude() {
+        return None;
+    }
+    let q = b.p1 - a.p1;
+    let t = (q.x * s.y - q.y * s.x) / denom;
+    let u = (q.x * r.y - q.y * r.x) / denom;
+
+    if !(0. ..=1.).contains(&t) || !(0. ..=1.).contains(&u) {
+        return None;
+    }
+    let n = normal(s);
+    let normal = if Vector2::dot(n, r) > 0. { n * -1. } else { n };
+
+    Some(Hit {
+        time: t,
+        point: a.p1 + r * t,
+        normal,
+    })
+}
+
+/// Cast a ray against a rectangle.
+pub fn ray_rect(ray: &Ray, rect: &Rect<f32>) -> Option<Hit> {
+    let (min, max) = (rect.min(), rect.max());
+    let mut near = f32::NEG_INFINITY;
+    let mut far = f32::INFINITY;
+    let mut normal = Vector2::new(0., 0.);
+
+    for axis in [Vector2::new(1., 0.), Vector2::new(0., 1.)].iter().copied() {
+        let along = |p: Point2<f32>| Vector2::dot(p.into(), axis);
+        let (o, d) = (along(ray.origin), Vector2::dot(ray.direction, axis));
+        let (lo, hi) = (along(min), along(max));
+
+        if d == 0. {
+            if o < lo || o > hi {
+                return None;
+            }
+            continue;
+        }
+        let (t1, t2) = ((lo - o) / d, (hi - o) / d);
+        let (t1, t2) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+
+        if t1 > near {
+            near = t1;
+            normal = axis * -d.signum();
+        }
+        far = far.min(t2);
+    }
+
+    if near > far || far < 0. {
+        return None;
+    }
+    if near < 0. {
+        return Some(ray.inside());
+    }
+    Some(Hit {
+        time: near,
+        point: ray.at(near),
+        normal,
+    })
+}
+
+/// Cast a ray against a circle.
+pub fn ray_circle(ray: &Ray, circle: &Circle) -> Option<Hit> {
+    let m = ray.origin - circle.position;
+    let a = Vector2::dot(ray.direction, ray.direction);
+    let b = Vector2::dot(m, ray.direction);
+    let c = Vector2::dot(m, m) - circle.radius * circle.radius;
+
+    if c <= 0. {
+        return Some(ray.inside());
+    }
+    let disc = b * b - a * c;
+
+    if a == 0. || disc < 0. {
+        return None;
+    }
+    let t = (-b - disc.sqrt()) / a;
+
+    if t < 0. {
+        return None;
+    }
+    let point = ray.at(t);
+
+    Some(Hit {
+        time: t,
+        point,
+        normal: (point - circle.position).normalize(),
+    })
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Overlap tests
+///////////////////////////////////////////////////////////////////////////////
+
+/// Test two circles for overlap.
+pub fn circle_circle(a: &Circle, b: &Circle) -> Option<Contact> {
+    let d = a.position - b.position;
+    let dist = d.magnitude();
+    let radii = a.radius + b.radius;
+
+    if dist >= radii {
+        return None;
+    }
+    let normal = if dist > 0. {
+        d * (1. / dist)
+    } else {
+        Vector2::new(1., 0.)
+    };
+
+    Some(Contact {
+        normal,
+        depth: radii - dist,
+        point: b.position + normal * b.radius,
+    })
+}
+
+/// Test a circle and a rectangle for overlap.
+pub fn circle_rect(circle: &Circle, rect: &Rect<f32>) -> Option<Contact> {
+    let (min, max) = (rect.min(), rect.max());
+    let c = circle.position;
+    let closest = Point2::new(c.x.clamp(min.x, max.x), c.y.clamp(min.y, max.y));
+    let d = c - closest;
+    let dist = d.magnitude();
+
+    if dist >= circle.radius {
+        return None;
+    }
+    if dist > 0. {
+        return Some(Contact {
+            normal: d * (1. / dist),
+            depth: circle.radius - dist,
+            point: closest,
+        });
+    }
+
+    // The center is inside the rectangle: push it out through the nearest
+    // edge.
+    let edges = [
+        (c.x - min.x, Vector2::new(-1., 0.), Point2::new(min.x, c.y)),
+        (max.x - c.x, Vector2::new(1., 0.), Point2::new(max.x, c.y)),
+        (c.y - min.y, Vector2::new(0., -1.), Point2::new(c.x, min.y)),
+        (max.y - c.y, Vector2::new(0., 1.), Point2::new(c.x, max.y)),
+    ];
+    let (dist, normal, point) = edges
+        .iter()
+        .copied()
+        .fold(edges[0], |a, b| if b.0 < a.0 { b } else { a });
+
+    Some(Contact {
+        normal,
+        depth: circle.radius + dist,
+        point,
+    })
+}
+
+/// Test two axis-aligned rectangles for overlap. The contact is along the
+/// axis of least penetration, and its point is the center of the overlap.
+pub fn rect_rect(a: &Rect<f32>, b: &Rect<f32>) -> Option<Contact> {
+    let (amin, amax) = (a.min(), a.max());
+    let (bmin, bmax) = (b.min(), b.max());
+
+    let (x1, x2) = (amin.x.max(bmin.x), amax.x.min(bmax.x));
+    let (y1, y2) = (amin.y.max(bmin.y), amax.y.min(bmax.y));
+    let (dx, dy) = (x2 - x1, y2 - y1);
+
+    if dx <= 0. || dy <= 0. {
+        return None;
+    }
+    let point = Point2::new((x1 + x2) / 2., (y1 + y2) / 2.);
+    let (ca, cb) = (amin + (amax - amin) * 0.5, bmin + (bmax - bmin) * 0.5);
+
+    let (normal, depth) = if dx < dy {
+        let s = if ca.x < cb.x { -1. } else { 1. };
+        (Vector2::new(s, 0.), dx)
+    } else {
+        let s = if ca.y < cb.y { -1. } else { 1. };
+        (Vector2::new(0., s), dy)
+    };
+
+    Some(Contact {
+        normal,
+        depth,
+        point,
+    })
+}
+
+/// Test two rotated rectangles for overlap, using the separating axis
+/// theorem. Rotations are applied the same way as for
+/// [`crate::kit::shape2d::Shape::Rectangle`].
+pub fn rotated_rects(
+    a: &Rect<f32>,
+    a_rotation: Rotation,
+    b: &Rect<f32>,
+    b_rotation: Rotation,
+) -> Option<Contact> {
+    let a = corners(a, a_rotation);
+    let b = corners(b, b_rotation);
+
+    let mut best: Option<(Vector2<f32>, f32)> = None;
+
+    for axis in axes(&a).iter().chain(axes(&b).iter()) {
+        let (amin, amax) = project(&a, *axis);
+        let (bmin, bmax) = project(&b, *axis);
+        let overlap = amax.min(bmax) - amin.max(bmin);
+
+        if overlap <= 0. {
+            return None;
+        }
+        let deeper = match best {
+            Some((_, depth)) => overlap < depth,
+            None => true,
+        };
+        if deeper {
+            best = Some((*axis, overlap));
+        }
+    }
+    let (axis, depth) = best?;
+    let d = centroid(&a) - centroid(&b);
+    let normal = if Vector2::dot(d, axis) < 0. {
+        axis * -1.
+    } else {
+        axis
+    };
+
+    // The deepest corner of either rectangle inside the other.
+    let deepest = |corners: &[Point2<f32>; 4], dir: Vector2<f32>| {
+        corners.iter().copied().fold(corners[0], |p, q| {
+            if Vector2::dot(q.into(), dir) < Vector2::dot(p.into(), dir) {
+                q
+            } else {
+                p
+            }
+        })
+    };
+    let p = deepest(&a, normal);
+    let point = if inside(p, &b) {
+        p
+    } else {
+        deepest(&b, normal * -1.)
+    };
+
+    Some(Contact {
+        normal,
+        depth,
+        point,
+    })
+}
+
+/// Sweep an axis-aligned rectangle along a motion vector, and find the
+/// first time it hits a target rectangle, as a fraction of the motion. The
+/// point of the hit is the center of the moving rectangle at that time.
+///
+/// If the rectangles already overlap, the hit is at time zero, with the
+/// normal of their [`rect_rect`] contact.
+pub fn sweep_rect(moving: &Rect<f32>, motion: Vector2<f32>, target: &Rect<f32>) -> Option<Hit> {
+    let (min, max) = (moving.min(), moving.max());
+    let half = (max - min) * 0.5;
+    let center = min + half;
+
+    if let Some(contact) = rect_rect(moving, target) {
+        return Some(Hit {
+            time: 0.,
+            point: center,
+            normal: contact.normal,
+        });
+    }
+
+    // Grow the target by the moving rectangle, and cast its center instead.
+    let (tmin, tmax) = (target.min(), target.max());
+    let grown = Rect::new(
+        tmin.x - half.x,
+        tmin.y - half.y,
+        tmax.x + half.x,
+        tmax.y + half.y,
+    );
+    let hit = ray_rect(&Ray::new(center, motion), &grown)?;
+
+    // Rectangles only touching at the start, or moving apart.
+    if hit.time > 1. || Vector2::dot(hit.normal, motion) >= 0. {
+        return None;
+    }
+    Some(hit)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Separating axes
+///////////////////////////////////////////////////////////////////////////////
+
+fn corners(r: &Rect<f32>, rotation: Rotation) -> [Point2<f32>; 4] {
+    [
+        rotation.apply(Point2::new(r.x1, r.y1)),
+        rotation.apply(Point2::new(r.x2, r.y1)),
+        rotation.apply(Point2::new(r.x2, r.y2)),
+        rotation.apply(Point2::new(r.x1, r.y2)),
+    ]
+}
+
+fn axes(corners: &[Point2<f32>; 4]) -> [Vector2<f32>; 2] {
+    [
+        normal(corners[1] - corners[0]),
+        normal(corners[2] - corners[1]),
+    ]
+}
+
+fn project(corners: &[Point2<f32>; 4], axis: Vector2<f32>) -> (f32, f32) {
+    corners
+        .iter()
+        .map(|p| Vector2::dot(Vector2::from(*p), axis))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| {
+            (lo.min(d), hi.max(d))
+        })
+}
+
+fn centroid(corners: &[Point2<f32>; 4]) -> Point2<f32> {
+    corners[0] + (corners[2] - corners[0]) * 0.5
+}
+
+/// Whether a point is inside or on the edges of a convex quad, in either
+/// winding order.
+fn inside(p: Point2<f32>, corners: &[Point2<f32>; 4]) -> bool {
+    let sides: Vec<f32> = (0..4)
+        .map(|i| cross(corners[i], corners[(i + 1) % 4], p))
+        .collect();
+
+    sides.iter().all(|s| *s >= -1e-4) || sides.iter().all(|s| *s <= 1e-4)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn circle(x: f32, y: f32, radius: f32) -> Circle {
+        Circle {
+            position: Point2::new(x, y),
+            radius,
+            sides: Circle::AUTO,
+        }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_segments_and_rays() {
+        let a = Line::new([0., 0.], [4., 4.]);
+        let b = Line::new([0., 4.], [4., 0.]);
+        let hit = segment_segment(&a, &b).unwrap();
+
+        assert!(close(hit.time, 0.5));
+        assert_eq!(hit.point, Point2::new(2., 2.));
+        assert!(Vector2::dot(hit.normal, a.p2 - a.p1) < 0.);
+
+        let c = Line::new([5., 0.], [5., 4.]);
+        assert_eq!(segment_segment(&a, &c), None);
+        assert_eq!(segment_segment(&a, &Line::new([1., 0.], [5., 4.])), None);
+
+        // Short segments still cross, whatever their scale.
+        let tiny = |l: &Line| Line::new(l.p1 * 1e-4, l.p2 * 1e-4);
+        let hit = segment_segment(&tiny(&a), &tiny(&b)).unwrap();
+        assert!(close(hit.time, 0.5));
+
+        let rect = Rect::new(2., 2., 4., 4.);
+        let ray = Ray::new(Point2::new(3., 0.), Vector2::new(0., 2.));
+        let hit = ray_rect(&ray, &rect).unwrap();
+        assert_eq!(hit.time, 1.);
+        assert_eq!(hit.point, Point2::new(3., 2.));
+        assert_eq!(hit.normal, Vector2::new(0., -1.));
+
+        let away = Ray::new(Point2::new(3., 0.), Vector2::new(0., -1.));
+        assert_eq!(ray_rect(&away, &rect), None);
+        let miss = Ray::new(Point2::new(0., 0.), Vector2::new(1., 0.));
+        assert_eq!(ray_rect(&miss, &rect), None);
+        let inside = Ray::new(Point2::new(3., 3.), Vector2::new(1., 0.));
+        assert_eq!(ray_rect(&inside, &rect).unwrap().time, 0.);
+
+        let circle = circle(10., 0., 2.);
+        let ray = Ray::new(Point2::new(0., 0.), Vector2::new(1., 0.));
+        let hit = ray_circle(&ray, &circle).unwrap();
+        assert!(close(hit.time, 8.));
+        assert_eq!(hit.normal, Vector2::new(-1., 0.));
+        assert_eq!(
+            ray_circle(&Ray::new(Point2::new(0., 3.), ray.direction), &circle),
+            None
+        );
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let a = circle(0., 0., 2.);
+        let b = circle(3., 0., 2.);
+        let contact = circle_circle(&a, &b).unwrap();
+        assert_eq!(contact.normal, Vector2::new(-1., 0.));
+        assert!(close(contact.depth, 1.));
+        assert_eq!(contact.point, Point2::new(1., 0.));
+        assert_eq!(circle_circle(&a, &circle(5., 0., 2.)), None);
+
+        // Circle centered inside a rectangle, nearest to the top edge.
+        let rect = Rect::new(0., 0., 10., 10.);
+        let c = circle(5., 9., 2.);
+        let contact = circle_rect(&c, &rect).unwrap();
+        assert_eq!(contact.normal, Vector2::new(0., 1.));
+        assert!(close(contact.depth, 3.));
+
+        let c = circle(12., 12., 2.);
+        assert_eq!(circle_rect(&c, &rect), None);
+
+        let a = Rect::new(0., 0., 4., 4.);
+        let b = Rect::new(3., 1., 8., 3.);
+        let contact = rect_rect(&a, &b).unwrap();
+        assert_eq!(contact.normal, Vector2::new(-1., 0.));
+        assert_eq!(contact.depth, 1.);
+        assert_eq!(contact.point, Point2::new(3.5, 2.));
+        assert_eq!(rect_rect(&a, &Rect::new(4., 0., 8., 4.)), None);
+    }
+
+    #[test]
+    fn test_rotated_rects() {
+        let a = Rect::new(0., 0., 2., 2.);
+        let b = Rect::new(2.2, 0., 4.2, 2.);
+
+        // Axis-aligned rectangles agree with the AABB test.
+        assert_eq!(rotated_rects(&a, Rotation::ZERO, &b, Rotation::ZERO), None);
+
+        // Rotating the first by 45° makes its corner reach the second.
+        let r = Rotation::new(std::f32::consts::FRAC_PI_4, Point2::new(1., 1.));
+        let contact = rotated_rects(&a, r, &b, Rotation::ZERO).unwrap();
+        assert_eq!(contact.normal, Vector2::new(-1., 0.));
+        assert!(close(contact.depth, 2f32.sqrt() + 1. - 2.2));
+        assert!(close(contact.point.x, 1. + 2f32.sqrt()));
+        assert!(close(contact.point.y, 1.));
+
+        // Two diamonds whose bounding boxes overlap, but not the shapes.
+        let b = Rect::new(2.2, 2.2, 4.2, 4.2);
+        let s = Rotation::new(std::f32::consts::FRAC_PI_4, Point2::new(3.2, 3.2));
+        assert!(rect_rect(&Rect::new(-0.4, -0.4, 2.4, 2.4), &b).is_some());
+        assert_eq!(rotated_rects(&a, r, &b, s), None);
+    }
+
+    #[test]
+    fn test_sweep_rect() {
+        let moving = Rect::new(0., 0., 2., 2.);
+        let target = Rect::new(6., -4., 8., 4.);
+
+        let hit = sweep_rect(&moving, Vector2::new(8., 0.), &target).unwrap();
+        assert_eq!(hit.time, 0.5);
+        assert_eq!(hit.point, Point2::new(5., 1.));
+        assert_eq!(hit.normal, Vector2::new(-1., 0.));
+
+        // Too short, or moving away.
+        assert_eq!(sweep_rect(&moving, Vector2::new(3., 0.), &target), None);
+        assert_eq!(sweep_rect(&moving, Vector2::new(-8., 0.), &target), None);
+
+        // Sliding along an edge doesn't count.
+        let floor = Rect::new(-10., 2., 10., 4.);
+        assert_eq!(sweep_rect(&moving, Vector2::new(5., 0.), &floor), None);
+        let hit = sweep_rect(&moving, Vector2::new(1., 1.), &floor).unwrap();
+        assert_eq!(hit.time, 0.);
+        assert_eq!(hit.normal, Vector2::new(0., -1.));
+
+        // Already overlapping.
+        let hit = sweep_rect(&moving, Vector2::new(1., 0.), &Rect::new(1., 1., 3., 3.)).unwrap();
+        assert_eq!(hit.time, 0.);
+    }
+}
@@ -0,0 +1,447 @@
+//! Immediate-mode debug drawing.
+//!
+//! Shapes can be drawn from anywhere, including other threads, through the
+//! [`global`] overlay, without passing a [`Batch`] around. Once per frame,
+//! the overlay is flushed into a batch, which should be drawn after the rest
+//! of the scene, so that it ends up on top:
+//!
+//! ```
+//! use rgx::kit::debug;
+//! use rgx::kit::Rgba;
+//! use rgx::math::Point2;
+//! use rgx::rect::Rect;
+//!
+//! // Anywhere in the code.
+//! let debug = debug::global();
+//!
+//! debug.rect(Rect::new(0., 0., 32., 32.), Rgba::RED);
+//! debug.cross(Point2::new(16., 16.), Rgba::WHITE).frames(60);
+//! debug.text(Point2::new(0., 40.), "hit!", Rgba::WHITE).seconds(1.5);
+//!
+//! // Once per frame, in the render loop.
+//! let batch = debug.flush();
+//! assert_eq!(batch.is_empty(), false);
+//! ```
+//!
+//! By default, shapes are drawn for a single frame. Text is drawn with a
+//! built-in pixel font, and assumes a top-left origin.
+use crate::color::Rgba;
+use crate::kit::shape2d::{Batch, Fill, Shape};
+use crate::kit::ZDepth;
+use crate::math::Point2;
+use crate::rect::Rect;
+
+use std::sync::{Mutex, MutexGuard};
+use std::time;
+
+static GLOBAL: Overlay = Overlay::new();
+
+/// The overlay shared by the whole program.
+pub fn global() -> &'static Overlay {
+    &GLOBAL
+}
+
+/// How long a debug shape stays on screen.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Lifetime {
+    /// A single flush.
+    #[default]
+    Frame,
+    /// A number of flushes.
+    Frames(u32),
+    /// A duration, measured between flushes. Shapes are always drawn at
+    /// least once.
+    Duration(time::Duration),
+}
+
+impl Lifetime {
+    /// Age by one frame of the given duration. Returns whether the shape
+    /// should be kept.
+    fn age(&mut self, delta: time::Duration) -> bool {
+        match self {
+            Self::Frame => false,
+            Self::Frames(n) => {
+                *n = n.saturating_sub(1);
+                *n > 0
+            }
+            Self::Duration(d) => {
+                *d = d.saturating_sub(delta);
+                *d > time::Duration::from_secs(0)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Item {
+    shapes: Vec<Shape>,
+    lifetime: Lifetime,
+}
+
+#[derive(Debug)]
+struct State {
+    items: Vec<Item>,
+    last: Option<time::Instant>,
+}
+
+/// A thread-safe collector of debug shapes.
+#[derive(Debug)]
+pub struct Overlay {
+    state: Mutex<State>,
+}
+
+impl Overlay {
+    /// Half the size of a cross.
+    pub const CROSS_SIZE: f32 = 4.;
+    /// Size of a pixel of the text font. Glyphs are three pixels wide and
+    /// five pixels high.
+    pub const TEXT_SCALE: f32 = 2.;
+    /// Depth of the shapes: the nearest depth visible with [`ortho`], which
+    /// maps it to a clip depth of zero. Drawn after the rest of the scene,
+    /// the shapes pass the depth test wherever they overlap it.
+    ///
+    /// [`ortho`]: crate::kit::ortho
+    pub const DEPTH: ZDepth = ZDepth(0.);
+
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                items: Vec::new(),
+                last: None,
+            }),
+        }
+    }
+
+    /// Draw the outline of a rectangle.
+    pub fn rect(&self, rect: Rect<f32>, color: Rgba) -> Draw<'_> {
+        let shape = Shape::Rectangle(
+            rect,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Fill::Empty,
+        );
+        self.draw(vec![shape.stroke(1., color)])
+    }
+
+    /// Draw a line.
+    pub fn line<P: Into<Point2<f32>>>(&self, p1: P, p2: P, color: Rgba) -> Draw<'_> {
+        self.draw(vec![Shape::line(p1, p2).stroke(1., color)])
+    }
+
+    /// Draw a diagonal cross centered on a point.
+    pub fn cross<P: Into<Point2<f32>>>(&self, point: P, color: Rgba) -> Draw<'_> {
+        let p = point.into();
+        let s = Self::CROSS_SIZE;
+
+        self.draw(vec![
+            Shape::line([p.x - s, p.y - s], [p.x + s, p.y + s]).stroke(1., color),
+            Shape::line([p.x - s, p.y + s], [p.x + s, p.y - s]).stroke(1., color),
+        ])
+    }
+
+    /// Draw a line of text, with its top-left corner at the given position.
+    /// Letters are drawn in upper case, and characters missing from the font
+    /// are drawn as boxes.
+    pub fn text<P: Into<Point2<f32>>>(&self, position: P, text: &str, color: Rgba) -> Draw<'_> {
+        let origin = position.into();
+        let px = Self::TEXT_SCALE;
+        let mut shapes = Vec::new();
+        let (mut x, mut y) = (origin.x, origin.y);
+
+        for c in text.chars() {
+            if c == '\n' {
+                x = origin.x;
+                y += 6. * px;
+                continue;
+            }
+            for (row, bits) in glyph(c).iter().enumerate() {
+                let y = y + row as f32 * px;
+
+                // Draw each horizontal run of pixels as a single rectangle.
+                let mut col = 0;
+                while col < 3 {
+                    if bits & (0b100 >> col) == 0 {
+                        col += 1;
+                        continue;
+                    }
+                    let start = col;
+                    while col < 3 && bits & (0b100 >> col) != 0 {
+                        col += 1;
+                    }
+                    shapes.push(
+                        Shape::rect([x + start as f32 * px, y], [x + col as f32 * px, y + px])
+                            .stroke(0., Rgba::TRANSPARENT)
+                            .fill(Fill::Solid(color)),
+                    );
+                }
+            }
+            x += 4. * px;
+        }
+        self.draw(shapes)
+    }
+
+    /// Draw arbitrary shapes.
+    pub fn shapes(&self, shapes: Vec<Shape>) -> Draw<'_> {
+        self.draw(shapes)
+    }
+
+    /// Collect all live shapes into a batch, and age them by the time since
+    /// the last flush.
+    pub fn flush(&self) -> Batch {
+        let now = time::Instant::now();
+        let last = self.lock().last.replace(now);
+        let delta = last.map_or(time::Duration::from_secs(0), |last| now - last);
+
+        self.flush_delta(delta)
+    }
+
+    /// Collect all live shapes into a batch, and age them by a given frame
+    /// duration.
+    pub fn flush_delta(&self, delta: time::Duration) -> Batch {
+        let mut state = self.lock();
+        let mut batch = Batch::new();
+
+        for item in state.items.iter() {
+            for shape in item.shapes.iter() {
+                batch.add(shape.clone());
+            }
+        }
+        state.items.retain_mut(|item| item.lifetime.age(delta));
+        batch
+    }
+
+    /// Remove all shapes.
+    pub fn clear(&self) {
+        self.lock().items.clear();
+    }
+
+    /// Number of shapes waiting to be flushed.
+    pub fn len(&self) -> usize {
+        self.lock().items.iter().map(|i| i.shapes.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn draw(&self, shapes: Vec<Shape>) -> Draw<'_> {
+        Draw {
+            overlay: self,
+            item: Some(Item {
+                shapes: shapes.into_iter().map(|s| s.zdepth(Self::DEPTH)).collect(),
+                lifetime: Lifetime::default(),
+            }),
+        }
+    }
+
+    /// Debug drawing should keep working after a panic on another thread.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A pending debug drawing, added to its overlay when dropped. Use it to set
+/// the lifetime of the drawing.
+#[derive(Debug)]
+pub struct Draw<'a> {
+    overlay: &'a Overlay,
+    item: Option<Item>,
+}
+
+impl Draw<'_> {
+    pub fn lifetime(mut self, lifetime: Lifetime) -> Self {
+        if let Some(item) = &mut self.item {
+            item.lifetime = lifetime;
+        }
+        self
+    }
+
+    /// Keep the drawing for a number of frames.
+    pub fn frames(self, n: u32) -> Self {
+        self.lifetime(Lifetime::Frames(n))
+    }
+
+    /// Keep the drawing for a number of seconds.
+    pub fn seconds(self, s: f32) -> Self {
+        self.lifetime(Lifetime::Duration(time::Duration::from_secs_f32(s)))
+    }
+}
+
+impl Drop for Draw<'_> {
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            self.overlay.lock().items.push(item);
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Font
+///////////////////////////////////////////////////////////////////////////////
+
+/// The rows of a three by five pixel glyph, most significant bit on the left.
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        ';' => [0b000, 0b010, 0b000, 0b010, 0b100],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '\\' => [0b100, 0b100, 0b010, 0b001, 0b001],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '[' => [0b011, 0b010, 0b010, 0b010, 0b011],
+        ']' => [0b110, 0b010, 0b010, 0b010, 0b110],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '*' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '|' => [0b010, 0b010, 0b010, 0b010, 0b010],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '"' => [0b101, 0b101, 0b000, 0b000, 0b000],
+        _ => [0b111, 0b111, 0b111, 0b111, 0b111],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::kit::{ortho, Origin};
+    use crate::math::Vector4;
+
+    #[test]
+    fn test_lifetimes() {
+        let debug = Overlay::new();
+        let frame = time::Duration::from_millis(100);
+
+        debug.rect(Rect::new(0., 0., 8., 8.), Rgba::RED);
+        debug.line([0., 0.], [8., 8.], Rgba::RED).frames(3);
+        debug.cross([4., 4.], Rgba::RED).seconds(0.25);
+        assert_eq!(debug.len(), 4);
+
+        assert_eq!(debug.flush_delta(frame).len(), 4);
+        assert_eq!(debug.flush_delta(frame).len(), 3);
+        assert_eq!(debug.flush_delta(frame).len(), 3);
+        assert_eq!(debug.flush_delta(frame).len(), 0);
+
+        // Even a zero duration is drawn once.
+        debug.rect(Rect::new(0., 0., 8., 8.), Rgba::RED).seconds(0.);
+        assert_eq!(debug.flush_delta(frame).len(), 1);
+        assert!(debug.flush_delta(frame).is_empty());
+
+        debug.line([0., 0.], [8., 8.], Rgba::RED).frames(10);
+        debug.clear();
+        assert!(debug.is_empty());
+    }
+
+    #[test]
+    fn test_text() {
+        let debug = Overlay::new();
+        debug.text([10., 20.], "T", Rgba::WHITE);
+
+        // One run for the top bar, and one for each row of the stem.
+        let batch = debug.flush_delta(time::Duration::from_secs(0));
+        assert_eq!(batch.len(), 5);
+
+        let px = Overlay::TEXT_SCALE;
+        match &batch.shapes()[0] {
+            Shape::Rectangle(r, ..) => assert_eq!(*r, Rect::new(10., 20., 10. + 3. * px, 20. + px)),
+            _ => panic!("expected a rectangle"),
+        }
+
+        // Unknown characters are boxes, and spaces draw nothing.
+        debug.text([0., 0.], " \u{2603}\nab", Rgba::WHITE);
+        assert_eq!(debug.len(), 5 + 8 + 7);
+    }
+
+    #[test]
+    fn test_depth() {
+        let debug = Overlay::new();
+        debug
+            .shapes(vec![Shape::rect([0., 0.], [8., 8.]).zdepth(0.5)])
+            .frames(1);
+
+        // Shapes are moved to the overlay depth.
+        let batch = debug.flush_delta(time::Duration::from_secs(0));
+        assert!(!batch.vertices().is_empty());
+        assert!(batch
+            .vertices()
+            .iter()
+            .all(|v| v.position.z == Overlay::DEPTH.0));
+
+        // The depth is within the clip depth range once projected.
+        for origin in [Origin::TopLeft, Origin::BottomLeft].iter().copied() {
+            let clip = ortho(640, 480, origin) * Vector4::new(0., 0., Overlay::DEPTH.0, 1.);
+            assert!(clip.z >= 0. && clip.z <= clip.w, "clip depth {}", clip.z);
+        }
+    }
+
+    #[test]
+    fn test_threads() {
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    global().cross([i as f32, 0.], Rgba::GREEN).frames(2);
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(global().len(), 8);
+        assert_eq!(global().flush().len(), 8);
+        assert_eq!(global().flush().len(), 8);
+        assert!(global().flush().is_empty());
+    }
+}
@@ -0,0 +1,823 @@
+//! Polygon geometry: boolean operations, convex hulls, simplification,
+//! offsetting and point-in-polygon tests.
+//!
+//! Everything here works on [`Point2<f32>`] and [`Polygon`], independently
+//! of the renderer, and results can be drawn directly as shapes:
+//!
+//! ```
+//! use rgx::kit::geometry;
+//! use rgx::kit::shape2d::{Batch, Polygon, Shape};
+//!
+//! let a = Polygon::new(vec![[0., 0.], [2., 0.], [2., 2.], [0., 2.]]);
+//! let b = Polygon::new(vec![[1., 1.], [3., 1.], [3., 3.], [1., 3.]]);
+//!
+//! let union = geometry::union(&a, &b);
+//! assert_eq!(union.len(), 1);
+//! assert!((union[0].area() - 7.).abs() < 1e-4);
+//!
+//! let mut batch = Batch::new();
+//! for polygon in union {
+//!     batch.add(Shape::polygon(polygon));
+//! }
+//! ```
+//!
+//! Boolean operations split all edges at their intersections and keep the
+//! pieces which separate the inside of the result from its outside. This
+//! handles overlapping edges, touching corners and self-intersecting input,
+//! with a cost quadratic in the number of edges. Points closer than
+//! [`EPSILON`] are considered equal.
+use crate::kit::shape2d::polygon::{
+    self, cross, distance_to_segment, normal, signed_area, winding,
+};
+use crate::kit::shape2d::{LineJoin, Polygon, Stroke};
+use crate::math::*;
+
+use std::collections::HashMap;
+use std::f32;
+
+/// Distance below which points are considered equal.
+pub const EPSILON: f32 = 1e-4;
+
+///////////////////////////////////////////////////////////////////////////
+// Boolean operations
+///////////////////////////////////////////////////////////////////////////
+
+/// A boolean operation between two sets of polygons.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// The area covered by either set.
+    Union,
+    /// The area covered by both sets.
+    Intersection,
+    /// The area covered by the first set but not the second.
+    Difference,
+    /// The area covered by exactly one of the sets.
+    Xor,
+}
+
+/// The area covered by either polygon.
+pub fn union(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    boolean(
+        std::slice::from_ref(a),
+        std::slice::from_ref(b),
+        Operation::Union,
+    )
+}
+
+/// The area covered by both polygons.
+pub fn intersection(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    boolean(
+        std::slice::from_ref(a),
+        std::slice::from_ref(b),
+        Operation::Intersection,
+    )
+}
+
+/// The area covered by `a` but not `b`.
+pub fn difference(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    boolean(
+        std::slice::from_ref(a),
+        std::slice::from_ref(b),
+        Operation::Difference,
+    )
+}
+
+/// The area covered by exactly one of the polygons.
+pub fn xor(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    boolean(
+        std::slice::from_ref(a),
+        std::slice::from_ref(b),
+        Operation::Xor,
+    )
+}
+
+/// Apply a boolean operation between two sets of polygons, such as the
+/// results of earlier operations. Polygons within a set may overlap.
+///
+/// The result is a list of polygons whose outer contours and holes don't
+/// overlap. Polygons touching at a single point are kept apart.
+pub fn boolean(a: &[Polygon], b: &[Polygon], op: Operation) -> Vec<Polygon> {
+    let a: Vec<Vec<Point2<f32>>> = a.iter().flat_map(Polygon::contours).collect();
+    let b: Vec<Vec<Point2<f32>>> = b.iter().flat_map(Polygon::contours).collect();
+
+    resolve(&a, &b, |wa, wb| {
+        let (a, b) = (wa != 0, wb != 0);
+
+        match op {
+            Operation::Union => a || b,
+            Operation::Intersection => a && b,
+            Operation::Difference => a && !b,
+            Operation::Xor => a != b,
+        }
+    })
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Offsetting
+///////////////////////////////////////////////////////////////////////////
+
+/// Grow a polygon by `distance` on all sides, or shrink it if the distance
+/// is negative, with corners joined according to `join`. Round corners are
+/// flattened to within [`TOLERANCE`], and miters are limited to
+/// [`Stroke::MITER_LIMIT`] times the distance.
+///
+/// Shrinking may split a polygon in several pieces, or remove it entirely,
+/// while growing may merge holes into the outer contour.
+///
+/// [`TOLERANCE`]: crate::kit::shape2d::TOLERANCE
+/// [`Stroke::MITER_LIMIT`]: crate::kit::shape2d::Stroke::MITER_LIMIT
+pub fn offset(polygon: &Polygon, distance: f32, join: LineJoin) -> Vec<Polygon> {
+    if distance == 0. {
+        return resolve(&polygon.contours(), &[], |w, _| w > 0);
+    }
+    let contours: Vec<Vec<Point2<f32>>> = polygon
+        .contours()
+        .iter()
+        .map(|c| offset_contour(c, distance, join))
+        .collect();
+
+    // The raw offset contours loop back on themselves at concave corners,
+    // and where the polygon is too thin. These loops wind the other way, and
+    // are removed by only keeping the area wound counter-clockwise.
+    resolve(&contours, &[], |w, _| w > 0)
+}
+
+/// Offset a contour to the right of its edges, ie. away from the filled
+/// region, which lies to the left.
+fn offset_contour(points: &[Point2<f32>], distance: f32, join: LineJoin) -> Vec<Point2<f32>> {
+    let points = dedup(points);
+
+    if points.len() < 3 {
+        return Vec::new();
+    }
+    polygon::offset_joined(&points, -distance, join, Stroke::MITER_LIMIT)
+}
+
+/// The z-component of the cross product of two vectors.
+fn cross_v(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Point-in-polygon
+///////////////////////////////////////////////////////////////////////////
+
+/// Whether the point lies inside the polygon and outside its holes. Points
+/// on the boundary are inside.
+pub fn contains(polygon: &Polygon, p: Point2<f32>) -> bool {
+    let contours = polygon.contours();
+
+    on_boundary(&contours, p) || winding(&contours, p) != 0
+}
+
+fn on_boundary(contours: &[Vec<Point2<f32>>], p: Point2<f32>) -> bool {
+    contours.iter().any(|c| {
+        (0..c.len()).any(|i| distance_to_segment(p, c[i], c[(i + 1) % c.len()]) <= EPSILON)
+    })
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Convex hull
+///////////////////////////////////////////////////////////////////////////
+
+/// The convex hull of a set of points, wound counter-clockwise, without
+/// collinear points.
+pub fn convex_hull(points: &[Point2<f32>]) -> Vec<Point2<f32>> {
+    let mut points = points.to_vec();
+
+    points.sort_by(|a, b| {
+        (a.x, a.y)
+            .partial_cmp(&(b.x, b.y))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    points.dedup();
+
+    if points.len() < 3 {
+        return points;
+    }
+    // Andrew's monotone chain: build the lower and upper hulls separately.
+    let mut hull: Vec<Point2<f32>> = Vec::with_capacity(points.len() * 2);
+
+    for pass in 0..2 {
+        let start = hull.len();
+
+        for &p in points.iter() {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+        if pass == 0 {
+            points.reverse();
+        }
+    }
+    hull
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Simplification
+///////////////////////////////////////////////////////////////////////////
+
+/// Simplify a polyline with the Ramer-Douglas-Peucker algorithm, removing
+/// points until no removed point is further than `epsilon` from the result.
+/// If `closed` is true, the points form a closed contour.
+pub fn simplify(points: &[Point2<f32>], epsilon: f32, closed: bool) -> Vec<Point2<f32>> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    if !closed {
+        return rdp(points, epsilon);
+    }
+    // Split the contour at the point furthest from the first one, and
+    // simplify both halves as polylines.
+    let first = points[0];
+    let far = (1..points.len())
+        .max_by(|i, j| {
+            let (a, b) = (
+                (points[*i] - first).magnitude(),
+                (points[*j] - first).magnitude(),
+            );
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(0);
+
+    let mut second = points[far..].to_vec();
+    second.push(first);
+
+    let mut out = rdp(&points[..=far], epsilon);
+    out.pop();
+    out.extend(rdp(&second, epsilon));
+    out.pop();
+    out
+}
+
+fn rdp(points: &[Point2<f32>], epsilon: f32) -> Vec<Point2<f32>> {
+    let mut keep = vec![false; points.len()];
+    let mut stack = vec![(0, points.len() - 1)];
+
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    while let Some((start, end)) = stack.pop() {
+        let (a, b) = (points[start], points[end]);
+        let furthest = (start + 1..end)
+            .map(|i| (i, distance_to_segment(points[i], a, b)))
+            .fold(None, |max: Option<(usize, f32)>, (i, d)| match max {
+                Some((_, m)) if m >= d => max,
+                _ => Some((i, d)),
+            });
+
+        if let Some((i, d)) = furthest {
+            if d > epsilon {
+                keep[i] = true;
+                stack.push((start, i));
+                stack.push((i, end));
+            }
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter(|(_, k)| *k)
+        .map(|(p, _)| *p)
+        .collect()
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Helpers
+///////////////////////////////////////////////////////////////////////////
+
+/// Remove consecutive duplicate points from a closed contour.
+fn dedup(points: &[Point2<f32>]) -> Vec<Point2<f32>> {
+    let mut out: Vec<Point2<f32>> = Vec::with_capacity(points.len());
+
+    for &p in points {
+        let distinct = match out.last() {
+            Some(q) => (p - *q).magnitude() > EPSILON,
+            None => true,
+        };
+        if distinct {
+            out.push(p);
+        }
+    }
+    while out.len() > 1 && (out[0] - out[out.len() - 1]).magnitude() <= EPSILON {
+        out.pop();
+    }
+    out
+}
+
+/// A cell of a grid of [`EPSILON`].
+type Cell = (i64, i64);
+
+/// The distinct points of a set of edges. Points closer than [`EPSILON`] are
+/// merged into one.
+#[derive(Default)]
+struct Vertices {
+    points: Vec<Point2<f32>>,
+    cells: HashMap<Cell, Vec<usize>>,
+}
+
+impl Vertices {
+    /// The index of the point, adding it if there is no point close to it.
+    fn insert(&mut self, p: Point2<f32>) -> usize {
+        let (cx, cy) = (
+            (p.x / EPSILON).floor() as i64,
+            (p.y / EPSILON).floor() as i64,
+        );
+        for x in cx - 1..=cx + 1 {
+            for y in cy - 1..=cy + 1 {
+                if let Some(found) = self.cells.get(&(x, y)).and_then(|ids| {
+                    ids.iter()
+                        .find(|i| (self.points[**i] - p).magnitude() <= EPSILON)
+                }) {
+                    return *found;
+                }
+            }
+        }
+        let id = self.points.len();
+
+        self.points.push(p);
+        self.cells.entry((cx, cy)).or_default().push(id);
+
+        id
+    }
+}
+
+/// An edge of one of the operands.
+struct Segment {
+    p: Point2<f32>,
+    q: Point2<f32>,
+    operand: usize,
+}
+
+/// A piece of one or more segments, between two split points.
+struct Piece {
+    from: usize,
+    to: usize,
+    /// The middle of the piece, before snapping its ends.
+    mid: Point2<f32>,
+    /// The direction of the first segment the piece comes from.
+    dir: Vector2<f32>,
+    /// The segments the piece comes from.
+    sources: Vec<usize>,
+}
+
+/// An edge of the result between two vertices, with the inside on its left.
+struct Edge {
+    from: usize,
+    to: usize,
+    used: bool,
+}
+
+/// Build the polygons covering the area where `inside` holds, given the
+/// winding numbers of both sets of contours.
+fn resolve(
+    a: &[Vec<Point2<f32>>],
+    b: &[Vec<Point2<f32>>],
+    inside: impl Fn(i32, i32) -> bool,
+) -> Vec<Polygon> {
+    let mut segments: Vec<Segment> = Vec::new();
+
+    for (operand, contours) in [a, b].iter().enumerate() {
+        for c in contours.iter() {
+            for i in 0..c.len() {
+                let (p, q) = (c[i], c[(i + 1) % c.len()]);
+
+                if (q - p).magnitude() > EPSILON {
+                    segments.push(Segment { p, q, operand });
+                }
+            }
+        }
+    }
+
+    // Split all segments where they cross or overlap.
+    let mut splits: Vec<Vec<f32>> = vec![Vec::new(); segments.len()];
+    for i in 0..segments.len() {
+        for j in i + 1..segments.len() {
+            intersect(&segments[i], &segments[j], i, j, &mut splits);
+        }
+    }
+
+    // Collect the pieces between split points. Pieces shared by several
+    // segments are kept once, along with the segments they come from.
+    let mut vertices = Vertices::default();
+    let mut pieces: Vec<Piece> = Vec::new();
+    let mut index: HashMap<(usize, usize), usize> = HashMap::new();
+
+    for (s, mut ts) in splits.into_iter().enumerate() {
+        let Segment { p, q, .. } = segments[s];
+
+        ts.push(0.);
+        ts.push(1.);
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        for t in ts.windows(2) {
+            let (from, to) = (p + (q - p) * t[0], p + (q - p) * t[1]);
+            let (i, j) = (vertices.insert(from), vertices.insert(to));
+
+            if i == j {
+                continue;
+            }
+            let piece = *index.entry((i.min(j), i.max(j))).or_insert_with(|| {
+                pieces.push(Piece {
+                    from: i,
+                    to: j,
+                    mid: from + (to - from) * 0.5,
+                    dir: q - p,
+                    sources: Vec::new(),
+                });
+                pieces.len() - 1
+            });
+            pieces[piece].sources.push(s);
+        }
+    }
+
+    // Keep the pieces separating the inside from the outside, oriented with
+    // the inside on their left.
+    let mut edges: Vec<Edge> = Vec::new();
+
+    for piece in pieces {
+        let [(la, ra), (lb, rb)] = sides(&segments, &piece);
+        let (left, right) = (inside(la, lb), inside(ra, rb));
+
+        if left != right {
+            let (from, to) = if left {
+                (piece.from, piece.to)
+            } else {
+                (piece.to, piece.from)
+            };
+            edges.push(Edge {
+                from,
+                to,
+                used: false,
+            });
+        }
+    }
+    let contours = link(&mut edges, &vertices.points);
+
+    // Counter-clockwise contours are outer contours; clockwise ones are
+    // holes, which belong to the smallest outer contour around them.
+    let (outers, holes): (Vec<_>, Vec<_>) = contours
+        .into_iter()
+        .filter(|c| signed_area(c).abs() > EPSILON * EPSILON)
+        .partition(|c| signed_area(c) > 0.);
+
+    let mut polygons: Vec<Polygon> = outers
+        .into_iter()
+        .map(|points| Polygon {
+            points,
+            holes: Vec::new(),
+        })
+        .collect();
+
+    for hole in holes {
+        // A point just inside the filled region along the hole.
+        let (p, q) = (hole[0], hole[1]);
+        let sample = p + (q - p) * 0.5 + normal(q - p) * EPSILON;
+
+        let owner = polygons
+            .iter_mut()
+            .filter(|o| winding(std::slice::from_ref(&o.points), sample) != 0)
+            .min_by(|a, b| {
+                let (a, b) = (signed_area(&a.points), signed_area(&b.points));
+                a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        if let Some(owner) = owner {
+            owner.holes.push(hole);
+        }
+    }
+    polygons
+}
+
+/// The winding numbers of both operands on the left and right of a piece.
+///
+/// Rather than testing points on either side, which is unreliable next to
+/// other edges, this casts a ray from the middle of the piece to its right,
+/// and counts the segments the piece comes from to cross to its left.
+fn sides(segments: &[Segment], piece: &Piece) -> [(i32, i32); 2] {
+    let (mid, dir) = (piece.mid, piece.dir);
+    let ray = normal(dir) * -1.;
+    let mut right = [0, 0];
+    let mut along = [0, 0];
+
+    for (i, s) in segments.iter().enumerate() {
+        if piece.sources.contains(&i) {
+            along[s.operand] += if Vector2::dot(s.q - s.p, dir) > 0. {
+                1
+            } else {
+                -1
+            };
+            continue;
+        }
+        let (sa, sb) = (cross_v(ray, s.p - mid), cross_v(ray, s.q - mid));
+
+        if (sa <= 0.) != (sb <= 0.) {
+            let x = s.p + (s.q - s.p) * (sa / (sa - sb));
+
+            if Vector2::dot(x - mid, ray) > 0. {
+                right[s.operand] += if sb > sa { 1 } else { -1 };
+            }
+        }
+    }
+    [
+        (right[0] + along[0], right[0]),
+        (right[1] + along[1], right[1]),
+    ]
+}
+
+/// Record where two segments cross or overlap, as offsets along each of them.
+fn intersect(
+    &Segment { p, q: p2, .. }: &Segment,
+    &Segment { p: q, q: q2, .. }: &Segment,
+    i: usize,
+    j: usize,
+    splits: &mut [Vec<f32>],
+) {
+    if p.x.max(p2.x) + EPSILON < q.x.min(q2.x)
+        || q.x.max(q2.x) + EPSILON < p.x.min(p2.x)
+        || p.y.max(p2.y) + EPSILON < q.y.min(q2.y)
+        || q.y.max(q2.y) + EPSILON < p.y.min(p2.y)
+    {
+        return;
+    }
+    let (r, s) = (p2 - p, q2 - q);
+    let denom = cross_v(r, s);
+    let inner = |t: f32| t > 0. && t < 1.;
+
+    if denom.abs() > EPSILON * r.magnitude() * s.magnitude() {
+        let t = cross_v(q - p, s) / denom;
+        let u = cross_v(q - p, r) / denom;
+
+        if (-EPSILON..=1. + EPSILON).contains(&t) && (-EPSILON..=1. + EPSILON).contains(&u) {
+            if inner(t) {
+                splits[i].push(t);
+            }
+            if inner(u) {
+                splits[j].push(u);
+            }
+        }
+        return;
+    }
+    // Parallel segments only matter if they are collinear, in which case
+    // each is split at the ends of the other.
+    if distance_to_line(q, p, p2) > EPSILON {
+        return;
+    }
+    let along = |x: Point2<f32>, a: Point2<f32>, d: Vector2<f32>| {
+        Vector2::dot(x - a, d) / Vector2::dot(d, d)
+    };
+
+    for t in [along(q, p, r), along(q2, p, r)].iter() {
+        if inner(*t) {
+            splits[i].push(*t);
+        }
+    }
+    for u in [along(p, q, s), along(p2, q, s)].iter() {
+        if inner(*u) {
+            splits[j].push(*u);
+        }
+    }
+}
+
+/// The distance between a point and the infinite line through `a` and `b`.
+fn distance_to_line(p: Point2<f32>, a: Point2<f32>, b: Point2<f32>) -> f32 {
+    let len = (b - a).magnitude();
+
+    if len == 0. {
+        (p - a).magnitude()
+    } else {
+        cross(a, b, p).abs() / len
+    }
+}
+
+/// Link edges into closed contours. Where several edges leave the same
+/// point, the contour turns as far left as possible, which keeps regions
+/// touching at a point apart.
+fn link(edges: &mut [Edge], points: &[Point2<f32>]) -> Vec<Vec<Point2<f32>>> {
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, e) in edges.iter().enumerate() {
+        outgoing.entry(e.from).or_default().push(i);
+    }
+    let mut contours = Vec::new();
+
+    for start in 0..edges.len() {
+        if edges[start].used {
+            continue;
+        }
+        let mut contour = Vec::new();
+        let mut curr = start;
+
+        let closed = loop {
+            edges[curr].used = true;
+            contour.push(points[edges[curr].from]);
+
+            let (from, to) = (edges[curr].from, edges[curr].to);
+            if to == edges[start].from {
+                break true;
+            }
+            let dir = points[to] - points[from];
+            let next = outgoing.get(&to).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .filter(|i| !edges[**i].used)
+                    .map(|i| {
+                        let out = points[edges[*i].to] - points[edges[*i].from];
+                        (*i, cross_v(dir, out).atan2(Vector2::dot(dir, out)))
+                    })
+                    .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(i, _)| i)
+            });
+            match next {
+                Some(i) => curr = i,
+                None => break false,
+            }
+        };
+        let contour = clean(contour);
+
+        if closed && contour.len() >= 3 {
+            contours.push(contour);
+        }
+    }
+    contours
+}
+
+/// Remove points lying on the line between their neighbours.
+fn clean(mut points: Vec<Point2<f32>>) -> Vec<Point2<f32>> {
+    let mut i = 0;
+
+    while points.len() >= 3 && i < points.len() {
+        let n = points.len();
+        let (prev, curr, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+
+        if distance_to_line(curr, prev, next) <= EPSILON {
+            points.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Polygon {
+        Polygon::new(vec![
+            [x, y],
+            [x + size, y],
+            [x + size, y + size],
+            [x, y + size],
+        ])
+    }
+
+    fn area(polygons: &[Polygon]) -> f32 {
+        polygons.iter().map(Polygon::area).sum()
+    }
+
+    #[test]
+    fn test_boolean() {
+        let (a, b) = (square(0., 0., 2.), square(1., 1., 2.));
+
+        assert!((area(&union(&a, &b)) - 7.).abs() < 1e-4);
+        assert!((area(&intersection(&a, &b)) - 1.).abs() < 1e-4);
+        assert!((area(&difference(&a, &b)) - 3.).abs() < 1e-4);
+        assert!((area(&xor(&a, &b)) - 6.).abs() < 1e-4);
+
+        // The intersection of two squares is a square.
+        let i = intersection(&a, &b);
+        assert_eq!(i.len(), 1);
+        assert_eq!(i[0].points.len(), 4);
+
+        // Disjoint polygons, and polygons touching at a corner, stay apart.
+        assert_eq!(union(&a, &square(5., 5., 1.)).len(), 2);
+        assert_eq!(union(&a, &square(2., 2., 1.)).len(), 2);
+        assert!(intersection(&a, &square(5., 5., 1.)).is_empty());
+
+        // Polygons sharing an edge are merged.
+        let merged = union(&a, &square(2., 0., 2.));
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].points.len(), 4);
+
+        // Cutting out the middle of a polygon leaves a hole.
+        let ring = difference(&square(0., 0., 4.), &square(1., 1., 2.));
+        assert_eq!(ring.len(), 1);
+        assert_eq!(ring[0].holes.len(), 1);
+        assert!((ring[0].area() - 12.).abs() < 1e-4);
+
+        // Holes are taken into account, and results can be combined further.
+        let filled = boolean(&ring, &[square(1., 1., 2.)], Operation::Union);
+        assert_eq!(filled.len(), 1);
+        assert!(filled[0].holes.is_empty());
+        assert!((area(&filled) - 16.).abs() < 1e-4);
+
+        // Self-intersecting input is filled by its winding.
+        let bowtie = Polygon::new(vec![[0., 0.], [2., 2.], [2., 0.], [0., 2.]]);
+        assert!((area(&union(&bowtie, &bowtie)) - 2.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_offset() {
+        let s = square(0., 0., 4.);
+
+        assert!((area(&offset(&s, 1., LineJoin::Miter)) - 36.).abs() < 1e-3);
+        assert!((area(&offset(&s, 1., LineJoin::Bevel)) - 34.).abs() < 1e-3);
+
+        // Round corners lie between the bevel and the true arc.
+        let round = area(&offset(&s, 1., LineJoin::Round));
+        assert!(round > 34. && round < 32. + f32::consts::PI);
+
+        assert!((area(&offset(&s, -1., LineJoin::Miter)) - 4.).abs() < 1e-3);
+        assert!(offset(&s, -3., LineJoin::Miter).is_empty());
+
+        // Shrinking a thin waist splits the polygon.
+        let dumbbell = union(&square(0., 0., 4.), &square(8., 0., 4.))
+            .into_iter()
+            .chain(Some(Polygon::new(vec![
+                [4., 1.5],
+                [8., 1.5],
+                [8., 2.5],
+                [4., 2.5],
+            ])))
+            .collect::<Vec<_>>();
+        let dumbbell = boolean(&dumbbell, &[], Operation::Union);
+        assert_eq!(dumbbell.len(), 1);
+        assert_eq!(offset(&dumbbell[0], -1., LineJoin::Miter).len(), 2);
+
+        // Growing a ring closes its hole.
+        let ring = difference(&square(0., 0., 4.), &square(1.5, 1.5, 1.));
+        assert_eq!(ring[0].holes.len(), 1);
+        assert!(offset(&ring[0], 1., LineJoin::Miter)[0].holes.is_empty());
+    }
+
+    #[test]
+    fn test_contains() {
+        let ring = square(0., 0., 4.).hole(vec![[1., 1.], [3., 1.], [3., 3.], [1., 3.]]);
+
+        assert!(contains(&ring, Point2::new(0.5, 0.5)));
+        assert!(contains(&ring, Point2::new(4., 2.)));
+        assert!(contains(&ring, Point2::new(1., 2.)));
+        assert!(!contains(&ring, Point2::new(2., 2.)));
+        assert!(!contains(&ring, Point2::new(5., 2.)));
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let points: Vec<Point2<f32>> = vec![
+            Point2::new(0., 0.),
+            Point2::new(1., 1.),
+            Point2::new(2., 0.),
+            Point2::new(1., 0.),
+            Point2::new(2., 2.),
+            Point2::new(0., 2.),
+            Point2::new(0.5, 1.5),
+        ];
+        let hull = convex_hull(&points);
+
+        assert_eq!(
+            hull,
+            vec![
+                Point2::new(0., 0.),
+                Point2::new(2., 0.),
+                Point2::new(2., 2.),
+                Point2::new(0., 2.)
+            ]
+        );
+        assert!(signed_area(&hull) > 0.);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let line: Vec<Point2<f32>> = (0..=10)
+            .map(|i| Point2::new(i as f32, if i % 2 == 0 { 0. } else { 0.05 }))
+            .collect();
+
+        assert_eq!(
+            simplify(&line, 0.1, false),
+            vec![Point2::new(0., 0.), Point2::new(10., 0.)]
+        );
+        assert_eq!(simplify(&line, 0.01, false).len(), line.len());
+
+        let circle: Vec<Point2<f32>> = (0..64)
+            .map(|i| {
+                let a = i as f32 * f32::consts::PI / 32.;
+                Point2::new(a.cos() * 10., a.sin() * 10.)
+            })
+            .collect();
+        let simplified = simplify(&circle, 0.5, true);
+
+        assert!(simplified.len() < circle.len() && simplified.len() >= 8);
+        for p in circle.iter() {
+            let d = (0..simplified.len())
+                .map(|i| {
+                    distance_to_segment(*p, simplified[i], simplified[(i + 1) % simplified.len()])
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!(d <= 0.5);
+        }
+    }
+}
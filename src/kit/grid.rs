@@ -0,0 +1,350 @@
+//! Grid, ruler and guide overlays for editor views.
+//!
+//! Overlays are generated in pixel space, for the area of the world visible
+//! at a given zoom, with [`Rect::min`] of the visible area at the pixel space
+//! origin. Every line is a filled rectangle one pixel wide, on whole pixels,
+//! so that it stays crisp at any zoom and camera position. Draw the batch
+//! with [`crate::kit::ortho`] for the viewport size and origin, and no view
+//! transform:
+//!
+//! ```
+//! use rgx::kit::camera::Camera2D;
+//! use rgx::kit::grid::Grid;
+//! use rgx::kit::Origin;
+//!
+//! let mut cam = Camera2D::new(640, 480, Origin::TopLeft);
+//! cam.set_zoom(16.);
+//!
+//! let grid = Grid::default();
+//! let batch = grid.batch(cam.visible(), cam.effective_zoom());
+//!
+//! // One line per pixel column and row.
+//! assert_eq!(batch.len(), 640 / 16 + 1 + 480 / 16 + 1);
+//! ```
+//!
+//! Lines fade out as they get closer together on screen, so a pixel grid
+//! disappears when zooming out, while its major lines remain visible a little
+//! longer.
+use crate::color::Rgba;
+use crate::kit::shape2d::{Batch, Fill, Shape};
+use crate::math::Point2;
+use crate::rect::Rect;
+
+/// An axis of the world.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// A grid of evenly spaced lines, with every few lines drawn as a major line.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Grid {
+    /// Distance between lines, in world units.
+    pub spacing: f32,
+    /// Number of lines between major lines, or zero for no major lines.
+    pub major: u32,
+    pub minor_color: Rgba,
+    pub major_color: Rgba,
+    /// On-screen distance between lines, in pixels, below which lines are
+    /// hidden, and above which they are fully visible. Lines fade in between.
+    /// For a grid with a spacing of one, this is the zoom.
+    pub fade: (f32, f32),
+    /// Background of rulers.
+    pub ruler_color: Rgba,
+}
+
+impl Default for Grid {
+    /// A pixel grid, with major lines every eight pixels.
+    fn default() -> Self {
+        Self {
+            spacing: 1.,
+            major: 8,
+            minor_color: Rgba::new(1., 1., 1., 0.15),
+            major_color: Rgba::new(1., 1., 1., 0.35),
+            fade: (4., 8.),
+            ruler_color: Rgba::new(0.1, 0.1, 0.1, 1.),
+        }
+    }
+}
+
+impl Grid {
+    pub fn new(spacing: f32) -> Self {
+        Self {
+            spacing,
+            ..Self::default()
+        }
+    }
+
+    pub fn major(mut self, major: u32) -> Self {
+        self.major = major;
+        self
+    }
+
+    pub fn colors(mut self, minor: Rgba, major: Rgba) -> Self {
+        self.minor_color = minor;
+        self.major_color = major;
+        self
+    }
+
+    pub fn fade(mut self, hidden: f32, visible: f32) -> Self {
+        self.fade = (hidden, visible);
+        self
+    }
+
+    pub fn ruler_color(mut self, color: Rgba) -> Self {
+        self.ruler_color = color;
+        self
+    }
+
+    /// Generate the grid lines covering the visible area.
+    pub fn batch(&self, visible: Rect<f32>, zoom: f32) -> Batch {
+        let view = View::new(visible, zoom);
+        let mut batch = Batch::new();
+
+        for axis in [Axis::X, Axis::Y].iter().copied() {
+            let length = view.length(axis.other());
+
+            for (position, major) in self.lines(&view, axis) {
+                let color = self.color(zoom, major);
+                batch.add(view.line(axis, position, 0., length, color));
+            }
+        }
+        batch
+    }
+
+    /// Generate a ruler along the top of the view for the x-axis, or along
+    /// its left side for the y-axis, with the given thickness in pixels. Major
+    /// lines have full-length ticks, and minor lines a third of that. With a
+    /// bottom-left origin, the x-axis ruler is along the bottom.
+    pub fn ruler(&self, visible: Rect<f32>, zoom: f32, axis: Axis, thickness: f32) -> Batch {
+        let view = View::new(visible, zoom);
+        let thickness = thickness.round();
+        let mut batch = Batch::new();
+
+        let background = match axis {
+            Axis::X => Rect::new(0., 0., view.length(Axis::X), thickness),
+            Axis::Y => Rect::new(0., 0., thickness, view.length(Axis::Y)),
+        };
+        batch.add(fill(background, self.ruler_color));
+
+        for (position, major) in self.lines(&view, axis) {
+            let color = self.color(zoom, major);
+            let length = if major {
+                thickness
+            } else {
+                (thickness / 3.).round()
+            };
+            batch.add(view.line(axis, position, thickness - length, thickness, color));
+        }
+        batch
+    }
+
+    /// The lines crossing the given axis within the view, as world positions
+    /// along it, and whether they are major lines. Lines which are invisible
+    /// at the current zoom are skipped.
+    fn lines(&self, view: &View, axis: Axis) -> Vec<(f32, bool)> {
+        let (min, max) = match axis {
+            Axis::X => (view.min.x, view.max.x),
+            Axis::Y => (view.min.y, view.max.y),
+        };
+        let minor = self.alpha(self.spacing * view.zoom) > 0.;
+        let major = self.major > 0 && self.alpha(self.spacing * self.major as f32 * view.zoom) > 0.;
+
+        if self.spacing <= 0. || (!minor && !major) {
+            return Vec::new();
+        }
+        if !minor {
+            // Only visit the major lines, which may be far fewer than the
+            // minor lines when zoomed out.
+            let step = self.spacing * self.major as f32;
+            let first = (min / step).ceil() as i64;
+            let last = (max / step).floor() as i64;
+
+            return (first..=last)
+                .map(|k| ((k * self.major as i64) as f32 * self.spacing, true))
+                .collect();
+        }
+        let first = (min / self.spacing).ceil() as i64;
+        let last = (max / self.spacing).floor() as i64;
+
+        (first..=last)
+            .map(|k| (k, self.major > 0 && k.rem_euclid(self.major as i64) == 0))
+            .filter(|(_, is_major)| if *is_major { major } else { minor })
+            .map(|(k, is_major)| (k as f32 * self.spacing, is_major))
+            .collect()
+    }
+
+    /// The color of minor or major lines at the given zoom.
+    fn color(&self, zoom: f32, major: bool) -> Rgba {
+        let (color, spacing) = if major {
+            (self.major_color, self.spacing * self.major as f32)
+        } else {
+            (self.minor_color, self.spacing)
+        };
+        Rgba {
+            a: color.a * self.alpha(spacing * zoom),
+            ..color
+        }
+    }
+
+    /// The opacity of lines the given number of pixels apart. Lines less than
+    /// a pixel apart are never drawn.
+    fn alpha(&self, pixels: f32) -> f32 {
+        let (hidden, visible) = self.fade;
+
+        if pixels < 1. || pixels <= hidden {
+            0.
+        } else if pixels >= visible {
+            1.
+        } else {
+            (pixels - hidden) / (visible - hidden)
+        }
+    }
+}
+
+/// A guide across the whole view, at a world position along the given axis:
+/// a vertical line for the x-axis, or a horizontal line for the y-axis.
+pub fn guide(visible: Rect<f32>, zoom: f32, axis: Axis, position: f32, color: Rgba) -> Shape {
+    let view = View::new(visible, zoom);
+
+    view.line(axis, position, 0., view.length(axis.other()), color)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+impl Axis {
+    fn other(self) -> Self {
+        match self {
+            Self::X => Self::Y,
+            Self::Y => Self::X,
+        }
+    }
+}
+
+/// The visible area of the world, and its mapping to pixel space.
+struct View {
+    min: Point2<f32>,
+    max: Point2<f32>,
+    zoom: f32,
+}
+
+impl View {
+    fn new(visible: Rect<f32>, zoom: f32) -> Self {
+        Self {
+            min: visible.min(),
+            max: visible.max(),
+            zoom,
+        }
+    }
+
+    /// Size of the view in whole pixels, along an axis.
+    fn length(&self, axis: Axis) -> f32 {
+        match axis {
+            Axis::X => ((self.max.x - self.min.x) * self.zoom).ceil(),
+            Axis::Y => ((self.max.y - self.min.y) * self.zoom).ceil(),
+        }
+    }
+
+    /// A one pixel wide line crossing an axis at a world position, from and
+    /// to the given pixel offsets along the other axis.
+    fn line(&self, axis: Axis, position: f32, from: f32, to: f32, color: Rgba) -> Shape {
+        let rect = match axis {
+            Axis::X => {
+                let x = ((position - self.min.x) * self.zoom).round();
+                Rect::new(x, from, x + 1., to)
+            }
+            Axis::Y => {
+                let y = ((position - self.min.y) * self.zoom).round();
+                Rect::new(from, y, to, y + 1.)
+            }
+        };
+        fill(rect, color)
+    }
+}
+
+fn fill(rect: Rect<f32>, color: Rgba) -> Shape {
+    Shape::rect(rect.min(), rect.max())
+        .stroke(0., Rgba::TRANSPARENT)
+        .fill(Fill::Solid(color))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rects(batch: &Batch) -> Vec<(Rect<f32>, f32)> {
+        batch
+            .shapes()
+            .iter()
+            .map(|s| match s {
+                Shape::Rectangle(r, _, _, _, Fill::Solid(c)) => (*r, c.a),
+                _ => panic!("expected a filled rectangle"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = Grid::default().colors(Rgba::WHITE, Rgba::WHITE);
+        let visible = Rect::new(-0.5, 0., 16.5, 2.);
+        let lines = rects(&grid.batch(visible, 10.));
+
+        // Vertical lines at x = 0..=16, and horizontal lines at y = 0..=2,
+        // all a single pixel wide and on whole pixels.
+        assert_eq!(lines.len(), 17 + 3);
+        assert_eq!(lines[0].0, Rect::new(5., 0., 6., 20.));
+        assert_eq!(lines[16].0, Rect::new(165., 0., 166., 20.));
+        assert_eq!(lines[17].0, Rect::new(0., 0., 170., 1.));
+        for (r, _) in lines.iter() {
+            assert_eq!(r.x1.fract(), 0.);
+            assert_eq!(r.y1.fract(), 0.);
+            assert!(r.width() == 1. || r.height() == 1.);
+        }
+
+        // Minor lines fade out, major lines at x = 0, 8 and 16 remain.
+        let lines = rects(&grid.batch(visible, 6.));
+        assert_eq!(lines[0].1, 1.);
+        assert_eq!(lines[1].1, 0.5);
+        let lines = rects(&grid.batch(visible, 4.));
+        assert_eq!(lines.len(), 3 + 1);
+        assert_eq!(lines[1].0.x1, ((8. + 0.5) * 4f32).round());
+
+        // Nothing is visible when zoomed out far enough.
+        assert!(grid.batch(visible, 0.25).is_empty());
+        assert!(Grid::new(1.).major(0).batch(visible, 4.).is_empty());
+
+        // Far out, only the major lines are generated.
+        let grid = Grid::new(1.).major(1000);
+        let lines = rects(&grid.batch(Rect::new(-1e6, 0., 1e6, 1.), 0.01));
+        assert_eq!(lines.len(), 2001 + 1);
+        assert_eq!(lines[0].0.x1, 0.);
+        assert_eq!(lines[1].0.x1, 10.);
+        assert_eq!(lines[2000].0.x1, 20000.);
+
+        // Odd zoom levels still yield whole pixels.
+        let lines = rects(&grid.batch(Rect::new(0.3, 0.3, 9.7, 9.7), 7.3));
+        for (r, _) in lines.iter() {
+            assert_eq!(r.x1.fract(), 0.);
+            assert_eq!(r.y1.fract(), 0.);
+        }
+    }
+
+    #[test]
+    fn test_ruler_and_guides() {
+        let grid = Grid::default();
+        let visible = Rect::new(0., 0., 16., 16.);
+        let ruler = rects(&grid.ruler(visible, 10., Axis::Y, 12.));
+
+        assert_eq!(ruler[0].0, Rect::new(0., 0., 12., 160.));
+        assert_eq!(ruler.len(), 1 + 17);
+        // Major tick, then a minor one.
+        assert_eq!(ruler[1].0, Rect::new(0., 0., 12., 1.));
+        assert_eq!(ruler[2].0, Rect::new(8., 10., 12., 11.));
+
+        match guide(visible, 10., Axis::X, 3.25, Rgba::RED) {
+            Shape::Rectangle(r, ..) => assert_eq!(r, Rect::new(33., 0., 34., 160.)),
+            _ => panic!("expected a rectangle"),
+        }
+    }
+}
@@ -1,8 +1,17 @@
+pub mod camera;
+pub mod clock;
+pub mod collision;
+pub mod debug;
+pub mod geometry;
+pub mod grid;
 pub mod shape2d;
 pub mod sprite2d;
+pub mod svg;
+pub mod timeline;
 
 pub use crate::color::{Bgra8, Rgba, Rgba8};
-use crate::math::{Matrix4, Ortho, Vector4};
+use crate::math::{Matrix4, Ortho, Point2, Vector2, Vector4};
+use crate::rect::Rect;
 
 use std::time;
 
@@ -10,7 +19,7 @@ pub trait Geometry {
     fn transform(self, m: Matrix4<f32>) -> Self;
 }
 
-impl Geometry for crate::rect::Rect<f32> {
+impl Geometry for Rect<f32> {
     fn transform(self, m: Matrix4<f32>) -> Self {
         let p1 = m * Vector4::new(self.x1, self.y1, 0., 1.);
         let p2 = m * Vector4::new(self.x2, self.y2, 0., 1.);
@@ -19,6 +28,71 @@ impl Geometry for crate::rect::Rect<f32> {
     }
 }
 
+/// Linear interpolation between two values.
+pub trait Lerp {
+    /// Interpolate between `self` and `other`, where `t = 0` yields `self`
+    /// and `t = 1` yields `other`.
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Rgba {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Rgba::new(
+            self.r.lerp(other.r, t),
+            self.g.lerp(other.g, t),
+            self.b.lerp(other.b, t),
+            self.a.lerp(other.a, t),
+        )
+    }
+}
+
+impl Lerp for Point2<f32> {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Point2::new(self.x.lerp(other.x, t), self.y.lerp(other.y, t))
+    }
+}
+
+impl Lerp for Vector2<f32> {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Vector2::new(self.x.lerp(other.x, t), self.y.lerp(other.y, t))
+    }
+}
+
+impl Lerp for Rect<f32> {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Rect::new(
+            self.x1.lerp(other.x1, t),
+            self.y1.lerp(other.y1, t),
+            self.x2.lerp(other.x2, t),
+            self.y2.lerp(other.y2, t),
+        )
+    }
+}
+
+/// Projection and view matrices used to prepare the kit pipelines.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct View {
+    /// Transforms pixel space into clip space, eg. [`ortho`].
+    pub projection: Matrix4<f32>,
+    /// Transforms world space into pixel space, eg. [`camera::Camera2D::view`].
+    pub transform: Matrix4<f32>,
+}
+
+impl From<Matrix4<f32>> for View {
+    fn from(projection: Matrix4<f32>) -> Self {
+        Self {
+            projection,
+            transform: Matrix4::identity(),
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Origin {
     BottomLeft,
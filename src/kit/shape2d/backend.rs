@@ -1,5 +1,7 @@
 use crate::core;
+use crate::core::transform::TransformBuffer;
 use crate::core::{Binding, BindingType, Set, ShaderStage};
+use crate::kit;
 use crate::math::*;
 
 ///////////////////////////////////////////////////////////////////////////
@@ -21,12 +23,27 @@ pub struct Pipeline {
     pipeline: core::Pipeline,
     bindings: core::BindingGroup,
     buf: core::UniformBuffer,
+    model: TransformBuffer,
+}
+
+impl Pipeline {
+    /// Create a buffer of model transforms, to be bound per draw with
+    /// [`TransformBuffer::offset`]. Batches are drawn with the identity
+    /// transform unless one is bound.
+    pub fn transforms(&self, r: &core::Renderer, transforms: &[Matrix4<f32>]) -> TransformBuffer {
+        TransformBuffer::new(&self.pipeline.layout.sets[1], transforms, &r.device)
+    }
+
+    /// Create an empty buffer of model transforms with the given capacity.
+    pub fn transforms_with_capacity(&self, r: &core::Renderer, cap: usize) -> TransformBuffer {
+        TransformBuffer::with_capacity(cap, &self.pipeline.layout.sets[1], &r.device)
+    }
 }
 
 //////////////////////////////////////////////////////////////////////////
 
 impl<'a> core::AbstractPipeline<'a> for Pipeline {
-    type PrepareContext = Matrix4<f32>;
+    type PrepareContext = kit::View;
     type Uniforms = self::Uniforms;
 
     fn description() -> core::PipelineDescription<'a> {
@@ -41,10 +58,17 @@ impl<'a> core::AbstractPipeline<'a> for Pipeline {
                 // Color
                 core::VertexFormat::UByte4,
             ],
-            pipeline_layout: &[Set(&[Binding {
-                binding: BindingType::UniformBuffer,
-                stage: ShaderStage::Vertex,
-            }])],
+            pipeline_layout: &[
+                Set(&[Binding {
+                    binding: BindingType::UniformBuffer,
+                    stage: ShaderStage::Vertex,
+                }]),
+                // Model transform.
+                Set(&[Binding {
+                    binding: BindingType::UniformBufferDynamic,
+                    stage: ShaderStage::Vertex,
+                }]),
+            ],
             // TODO: Use `env("CARGO_MANIFEST_DIR")`
             vertex_shader: include_bytes!("data/shape.vert.spv"),
             fragment_shader: include_bytes!("data/shape.frag.spv"),
@@ -56,25 +80,151 @@ impl<'a> core::AbstractPipeline<'a> for Pipeline {
         let ortho = Matrix4::identity();
         let buf = dev.create_uniform_buffer(&[self::Uniforms { ortho, transform }]);
         let bindings = dev.create_binding_group(&pipeline.layout.sets[0], &[&buf]);
+        let model = TransformBuffer::new(&pipeline.layout.sets[1], &[Matrix4::identity()], dev);
 
         Self {
             pipeline,
             buf,
             bindings,
+            model,
         }
     }
 
     fn apply(&self, pass: &mut core::Pass) {
         pass.set_pipeline(&self.pipeline);
         pass.set_binding(&self.bindings, &[]);
+        pass.set_binding(&self.model.binding, &[0]);
     }
 
     fn prepare(
         &'a self,
-        ortho: Matrix4<f32>,
+        view: kit::View,
     ) -> Option<(&'a core::UniformBuffer, Vec<self::Uniforms>)> {
+        Some((
+            &self.buf,
+            vec![self::Uniforms {
+                ortho: view.projection,
+                transform: view.transform,
+            }],
+        ))
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// TexturePipeline
+///////////////////////////////////////////////////////////////////////////
+
+/// A pipeline for shapes with a [`super::Fill::Texture`], drawing the
+/// vertices returned by [`super::Batch::textured_vertices`].
+pub struct TexturePipeline {
+    pipeline: core::Pipeline,
+    bindings: core::BindingGroup,
+    buf: core::UniformBuffer,
+    model: TransformBuffer,
+}
+
+impl TexturePipeline {
+    pub fn binding(
+        &self,
+        renderer: &core::Renderer,
+        texture: &core::Texture,
+        sampler: &core::Sampler,
+    ) -> core::BindingGroup {
+        renderer
+            .device
+            .create_binding_group(&self.pipeline.layout.sets[1], &[texture, sampler])
+    }
+
+    /// Create a buffer of model transforms, to be bound per draw with
+    /// [`TransformBuffer::offset`]. Batches are drawn with the identity
+    /// transform unless one is bound.
+    pub fn transforms(&self, r: &core::Renderer, transforms: &[Matrix4<f32>]) -> TransformBuffer {
+        TransformBuffer::new(&self.pipeline.layout.sets[2], transforms, &r.device)
+    }
+
+    /// Create an empty buffer of model transforms with the given capacity.
+    pub fn transforms_with_capacity(&self, r: &core::Renderer, cap: usize) -> TransformBuffer {
+        TransformBuffer::with_capacity(cap, &self.pipeline.layout.sets[2], &r.device)
+    }
+}
+
+impl<'a> core::AbstractPipeline<'a> for TexturePipeline {
+    type PrepareContext = kit::View;
+    type Uniforms = self::Uniforms;
+
+    fn description() -> core::PipelineDescription<'a> {
+        core::PipelineDescription {
+            vertex_layout: &[
+                // Position
+                core::VertexFormat::Float3,
+                // Rotation angle.
+                core::VertexFormat::Float,
+                // Center of rotation.
+                core::VertexFormat::Float2,
+                // Texture coordinates.
+                core::VertexFormat::Float2,
+                // Color
+                core::VertexFormat::UByte4,
+            ],
+            pipeline_layout: &[
+                Set(&[Binding {
+                    binding: BindingType::UniformBuffer,
+                    stage: ShaderStage::Vertex,
+                }]),
+                Set(&[
+                    Binding {
+                        binding: BindingType::SampledTexture,
+                        stage: ShaderStage::Fragment,
+                    },
+                    Binding {
+                        binding: BindingType::Sampler,
+                        stage: ShaderStage::Fragment,
+                    },
+                ]),
+                // Model transform.
+                Set(&[Binding {
+                    binding: BindingType::UniformBufferDynamic,
+                    stage: ShaderStage::Vertex,
+                }]),
+            ],
+            // TODO: Use `env("CARGO_MANIFEST_DIR")`
+            vertex_shader: include_bytes!("data/texture.vert.spv"),
+            fragment_shader: include_bytes!("data/texture.frag.spv"),
+        }
+    }
+
+    fn setup(pipeline: core::Pipeline, dev: &core::Device) -> Self {
         let transform = Matrix4::identity();
-        Some((&self.buf, vec![self::Uniforms { transform, ortho }]))
+        let ortho = Matrix4::identity();
+        let buf = dev.create_uniform_buffer(&[self::Uniforms { ortho, transform }]);
+        let bindings = dev.create_binding_group(&pipeline.layout.sets[0], &[&buf]);
+        let model = TransformBuffer::new(&pipeline.layout.sets[2], &[Matrix4::identity()], dev);
+
+        Self {
+            pipeline,
+            buf,
+            bindings,
+            model,
+        }
+    }
+
+    fn apply(&self, pass: &mut core::Pass) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_binding(&self.bindings, &[]);
+        pass.set_binding(&self.model.binding, &[0]);
+    }
+
+    fn prepare(
+        &'a self,
+        view: kit::View,
+    ) -> Option<(&'a core::UniformBuffer, Vec<self::Uniforms>)> {
+        Some((
+            &self.buf,
+            vec![self::Uniforms {
+                ortho: view.projection,
+                transform: view.transform,
+            }],
+        ))
     }
 }
 
@@ -84,3 +234,57 @@ impl core::Renderable for super::Batch {
         r.device.create_buffer(buf.as_slice())
     }
 }
+
+/// Batches are drawn with 16-bit indices, and must have fewer than 65536
+/// vertices. Larger batches are drawn with [`super::Batch::indexed_buffer32`].
+impl core::IndexedRenderable for super::Batch {
+    fn indexed_buffer(&self, r: &core::Renderer) -> core::IndexedBuffer {
+        let (verts, indices) = self.indexed();
+        assert!(
+            verts.len() <= u16::MAX as usize + 1,
+            "Batch::indexed_buffer: too many vertices for 16-bit indices"
+        );
+        let indices: Vec<u16> = indices.into_iter().map(|i| i as u16).collect();
+
+        r.indexed_buffer(verts.as_slice(), indices.as_slice())
+    }
+}
+
+impl super::Batch {
+    /// Create an indexed buffer with 32-bit indices, to be drawn with a
+    /// pipeline created with [`core::Renderer::indexed_pipeline`] and
+    /// [`core::IndexFormat::Uint32`].
+    pub fn indexed_buffer32(&self, r: &core::Renderer) -> core::IndexedBuffer {
+        let (verts, indices) = self.indexed();
+        r.indexed_buffer32(verts.as_slice(), indices.as_slice())
+    }
+}
+
+impl core::Renderable for super::Retained {
+    fn buffer(&self, r: &core::Renderer) -> core::VertexBuffer {
+        r.device.create_buffer(self.vertices())
+    }
+}
+
+impl super::Retained {
+    /// Bring a buffer created from this batch up to date, and mark the batch
+    /// as clean. Only the dirty ranges are uploaded, unless the number of
+    /// vertices changed, in which case the buffer is recreated.
+    pub fn sync(
+        &mut self,
+        buf: &mut core::VertexBuffer,
+        r: &mut core::Renderer,
+        f: &mut core::Frame,
+    ) {
+        let verts = self.vertices();
+
+        if buf.size as usize != verts.len() {
+            *buf = r.device.create_buffer(verts);
+        } else {
+            for range in self.dirty() {
+                r.update_vertex_buffer(buf, range.start, &verts[range], f);
+            }
+        }
+        self.clean();
+    }
+}
@@ -0,0 +1,107 @@
+use crate::math::*;
+
+use super::polygon::{cross, normal};
+use super::Vertex;
+
+use std::collections::HashMap;
+
+/// Vertex positions are matched on a grid of this resolution, so that edges
+/// shared by adjacent triangles are found despite rounding errors.
+const GRID: f32 = 1024.;
+
+type Key = (i64, i64);
+
+fn key(v: &Vertex) -> Key {
+    (
+        (v.position.x * GRID).round() as i64,
+        (v.position.y * GRID).round() as i64,
+    )
+}
+
+/// Add an anti-aliasing fringe of the given width around a triangle list.
+///
+/// Edges which aren't shared by two triangles are on the outline of the
+/// shape. Each of them is extruded outwards into a quad, which fades from the
+/// color of the edge to full transparency.
+pub(super) fn feather(verts: &mut Vec<Vertex>, width: f32) {
+    // Outline edges, with their outward normal.
+    let mut edges: Vec<(usize, usize, Vector2<f32>, u32)> = Vec::new();
+    let mut index: HashMap<(Key, Key), usize> = HashMap::new();
+
+    for (t, tri) in verts.chunks(3).enumerate() {
+        if tri.len() < 3 {
+            break;
+        }
+        let p = |i: usize| Point2::new(tri[i].position.x, tri[i].position.y);
+
+        if cross(p(0), p(1), p(2)).abs() < f32::EPSILON {
+            continue;
+        }
+        for &(a, b, c) in &[(0, 1, 2), (1, 2, 0), (2, 0, 1)] {
+            let (ka, kb) = (key(&tri[a]), key(&tri[b]));
+            if ka == kb {
+                continue;
+            }
+            let k = if ka < kb { (ka, kb) } else { (kb, ka) };
+
+            let mut n = normal(p(b) - p(a));
+            if Vector2::dot(p(c) - p(a), n) > 0. {
+                n = n * -1.;
+            }
+            match index.get(&k) {
+                Some(&i) => edges[i].3 += 1,
+                None => {
+                    index.insert(k, edges.len());
+                    edges.push((t * 3 + a, t * 3 + b, n, 1));
+                }
+            }
+        }
+    }
+
+    let outline: Vec<(usize, usize, Vector2<f32>)> = edges
+        .into_iter()
+        .filter(|e| e.3 == 1)
+        .map(|e| (e.0, e.1, e.2))
+        .collect();
+
+    // Average the normals of the outline edges meeting at each vertex, so
+    // that the fringes of adjacent edges join without gaps.
+    let mut normals: HashMap<Key, (Vector2<f32>, u32)> = HashMap::new();
+    for (a, b, n) in outline.iter() {
+        for i in &[*a, *b] {
+            let e = normals
+                .entry(key(&verts[*i]))
+                .or_insert((Vector2::new(0., 0.), 0));
+            e.0 = e.0 + *n;
+            e.1 += 1;
+        }
+    }
+    let offset = |v: &Vertex, n: Vector2<f32>| -> Vector2<f32> {
+        let (sum, count) = normals[&key(v)];
+
+        if count != 2 || sum.magnitude() < 1e-3 {
+            return n * width;
+        }
+        let m = sum.normalize();
+        m * (width / Vector2::dot(m, n).max(0.25))
+    };
+
+    let mut fringe = Vec::with_capacity(outline.len() * 6);
+    for (a, b, n) in outline {
+        let (va, vb) = (verts[a], verts[b]);
+        let (oa, ob) = (offset(&va, n), offset(&vb, n));
+
+        let mut fa = va;
+        fa.position.x += oa.x;
+        fa.position.y += oa.y;
+        fa.color.a = 0;
+
+        let mut fb = vb;
+        fb.position.x += ob.x;
+        fb.position.y += ob.y;
+        fb.color.a = 0;
+
+        fringe.extend_from_slice(&[va, vb, fb, va, fb, fa]);
+    }
+    verts.extend(fringe);
+}
@@ -0,0 +1,285 @@
+use crate::color::Rgba;
+use crate::kit::Lerp;
+use crate::math::*;
+
+use super::polygon::cross;
+
+/// The color stops of a gradient, as offsets and colors. At most
+/// [`Stops::MAX`] stops are kept, inline, so that fills can be copied.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stops {
+    stops: [(f32, Rgba); Stops::MAX],
+    len: usize,
+}
+
+impl Stops {
+    pub const MAX: usize = 16;
+
+    pub fn new(stops: &[(f32, Rgba)]) -> Self {
+        assert!(stops.len() <= Self::MAX, "Stops::new: too many stops");
+
+        let mut inline = [(0., Rgba::TRANSPARENT); Self::MAX];
+        inline[..stops.len()].copy_from_slice(stops);
+
+        Self {
+            stops: inline,
+            len: stops.len(),
+        }
+    }
+}
+
+impl std::ops::Deref for Stops {
+    type Target = [(f32, Rgba)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.stops[..self.len]
+    }
+}
+
+impl From<&[(f32, Rgba)]> for Stops {
+    fn from(stops: &[(f32, Rgba)]) -> Self {
+        Self::new(stops)
+    }
+}
+
+/// The geometry of a gradient, which maps points to an offset along it.
+#[derive(Copy, Clone, Debug)]
+pub(super) enum Kind {
+    Linear(Point2<f32>, Point2<f32>),
+    Radial(Point2<f32>, f32),
+}
+
+impl Kind {
+    fn offset(&self, p: Point2<f32>) -> f32 {
+        match *self {
+            Kind::Linear(start, end) => {
+                let axis = end - start;
+                let len = Vector2::dot(axis, axis);
+
+                if len == 0. {
+                    0.
+                } else {
+                    Vector2::dot(p - start, axis) / len
+                }
+            }
+            Kind::Radial(center, radius) => {
+                if radius == 0. {
+                    0.
+                } else {
+                    (p - center).magnitude() / radius
+                }
+            }
+        }
+    }
+}
+
+/// The color of a gradient at the given offset. Offsets outside of the stops
+/// take the color of the nearest stop.
+pub(super) fn color(stops: &[(f32, Rgba)], t: f32) -> Rgba {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Rgba::TRANSPARENT,
+    };
+    if t <= first.0 {
+        return first.1;
+    }
+    for w in stops.windows(2) {
+        let ((t0, c0), (t1, c1)) = (w[0], w[1]);
+
+        if t <= t1 {
+            if t1 <= t0 {
+                return c1;
+            }
+            return c0.lerp(c1, (t - t0) / (t1 - t0));
+        }
+    }
+    last.1
+}
+
+/// Color a list of triangles with a gradient.
+///
+/// Since colors are interpolated linearly across triangles, triangles are
+/// split along the boundaries between stops, such that each piece lies
+/// between two consecutive stops. For radial gradients, whose offset isn't
+/// linear, triangles are first subdivided until the error is within the
+/// tolerance.
+pub(super) fn shade(
+    triangles: &[Point2<f32>],
+    kind: Kind,
+    stops: &[(f32, Rgba)],
+    tolerance: f32,
+) -> Vec<(Point2<f32>, Rgba)> {
+    let mut stops = stops.to_vec();
+    stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut tris: Vec<[Point2<f32>; 3]> = triangles
+        .chunks(3)
+        .filter(|t| t.len() == 3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect();
+
+    if let Kind::Radial(center, radius) = kind {
+        tris = subdivide(
+            tris,
+            center,
+            (8. * tolerance * radius).sqrt().max(tolerance),
+        );
+    }
+
+    let mut out = Vec::with_capacity(tris.len() * 3);
+    for tri in tris {
+        let poly: Vec<(Point2<f32>, f32)> = tri.iter().map(|p| (*p, kind.offset(*p))).collect();
+        let (lo, hi) = poly
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v.1), hi.max(v.1))
+            });
+
+        // Split the triangle into bands between the stops it crosses.
+        let mut bounds = vec![f32::NEG_INFINITY];
+        bounds.extend(stops.iter().map(|s| s.0).filter(|s| *s > lo && *s < hi));
+        bounds.push(f32::INFINITY);
+        bounds.dedup();
+
+        for band in bounds.windows(2) {
+            let piece = if bounds.len() == 2 {
+                poly.clone()
+            } else {
+                clip(&clip(&poly, band[0], 1.), band[1], -1.)
+            };
+            for i in 1..piece.len().saturating_sub(1) {
+                for (p, t) in [piece[0], piece[i], piece[i + 1]].iter() {
+                    out.push((*p, color(&stops, *t)));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Clip a convex polygon with per-vertex offsets, keeping the part where
+/// `sign * (t - bound) >= 0`.
+pub(super) fn clip(poly: &[(Point2<f32>, f32)], bound: f32, sign: f32) -> Vec<(Point2<f32>, f32)> {
+    if !bound.is_finite() {
+        return poly.to_vec();
+    }
+    let inside = |t: f32| sign * (t - bound) >= 0.;
+    let mut out = Vec::with_capacity(poly.len() + 2);
+
+    for i in 0..poly.len() {
+        let (a, b) = (poly[i], poly[(i + 1) % poly.len()]);
+
+        if inside(a.1) {
+            out.push(a);
+        }
+        if inside(a.1) != inside(b.1) {
+            let f = (bound - a.1) / (b.1 - a.1);
+            out.push((a.0 + (b.0 - a.0) * f, bound));
+        }
+    }
+    out
+}
+
+/// Subdivide triangles until their edges are no longer than `max`. Triangles
+/// containing the center are split there first, so that it becomes a vertex.
+fn subdivide(tris: Vec<[Point2<f32>; 3]>, center: Point2<f32>, max: f32) -> Vec<[Point2<f32>; 3]> {
+    let mut out = Vec::with_capacity(tris.len());
+    let mut stack: Vec<([Point2<f32>; 3], u32)> = Vec::with_capacity(tris.len());
+
+    for [a, b, c] in tris {
+        let (ab, bc, ca) = (
+            cross(a, b, center),
+            cross(b, c, center),
+            cross(c, a, center),
+        );
+        let contains = (ab > 0. && bc > 0. && ca > 0.) || (ab < 0. && bc < 0. && ca < 0.);
+
+        if contains {
+            stack.push(([a, b, center], 0));
+            stack.push(([b, c, center], 0));
+            stack.push(([c, a, center], 0));
+        } else {
+            stack.push(([a, b, c], 0));
+        }
+    }
+
+    while let Some(([a, b, c], depth)) = stack.pop() {
+        let longest = f32::max(
+            (b - a).magnitude(),
+            f32::max((c - b).magnitude(), (a - c).magnitude()),
+        );
+        if longest <= max || depth >= 6 {
+            out.push([a, b, c]);
+            continue;
+        }
+        let (ab, bc, ca) = (mid(a, b), mid(b, c), mid(c, a));
+
+        for t in [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]].iter() {
+            stack.push((*t, depth + 1));
+        }
+    }
+    out
+}
+
+fn mid(a: Point2<f32>, b: Point2<f32>) -> Point2<f32> {
+    Point2::new((a.x + b.x) / 2., (a.y + b.y) / 2.)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{area, positions};
+    use super::super::{Fill, Shape};
+    use super::*;
+    use crate::kit::Geometry;
+
+    #[test]
+    fn test_gradients() {
+        let stops = [(0., Rgba::RED), (0.5, Rgba::GREEN), (1., Rgba::BLUE)];
+
+        // A linear gradient across a rectangle is split at the middle stop.
+        let rect = Shape::rect([0., 0.], [4., 2.])
+            .stroke(0., Rgba::TRANSPARENT)
+            .fill(Fill::linear_gradient([0., 0.], [4., 0.], &stops));
+        let verts = rect.triangulate();
+
+        assert!((area(&positions(&rect)) - 8.).abs() < 1e-3);
+        for v in &verts {
+            let expected = match v.position.x {
+                x if x.abs() < 1e-4 => Rgba::RED,
+                x if (x - 2.).abs() < 1e-4 => Rgba::GREEN,
+                x if (x - 4.).abs() < 1e-4 => Rgba::BLUE,
+                x => panic!("unexpected vertex at x = {}", x),
+            };
+            assert_eq!(v.color, expected.into());
+        }
+
+        // The gradient moves along with the shape.
+        let moved = rect.transform(Matrix4::from_translation(Vector3::new(8., 0., 0.)));
+        for v in moved.triangulate() {
+            if (v.position.x - 8.).abs() < 1e-4 {
+                assert_eq!(v.color, Rgba::RED.into());
+            }
+        }
+
+        // A radial gradient is centered on its first stop.
+        let circle = Shape::circle([0., 0.], 8., 32)
+            .stroke(0., Rgba::TRANSPARENT)
+            .fill(Fill::radial_gradient([0., 0.], 8., &stops));
+        let verts = circle.triangulate();
+        let solid = Shape::circle([0., 0.], 8., 32)
+            .stroke(0., Rgba::TRANSPARENT)
+            .fill(Fill::solid(Rgba::RED));
+
+        assert!((area(&positions(&circle)) - area(&positions(&solid))).abs() < 1e-2);
+        for v in &verts {
+            let r = Vector2::new(v.position.x, v.position.y).magnitude();
+
+            if r < 1e-4 {
+                assert_eq!(v.color, Rgba::RED.into());
+            } else if (r - 8.).abs() < 1e-3 {
+                assert_eq!(v.color, Rgba::BLUE.into());
+            }
+        }
+        assert!(verts.iter().any(|v| v.color == Rgba::GREEN.into()));
+    }
+}
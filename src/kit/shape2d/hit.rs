@@ -0,0 +1,381 @@
+use crate::kit::Geometry;
+use crate::math::*;
+use crate::rect::Rect;
+
+use super::polygon::{cross, distance_to_segment, winding};
+use super::*;
+
+impl Shape {
+    /// The axis-aligned bounding box of the shape, including its stroke and
+    /// rotation. Shapes without any geometry have empty bounds.
+    pub fn bounds(&self) -> Rect<f32> {
+        match *self {
+            // Closed shapes grow by the part of their stroke outside of their
+            // outline, if any.
+            Shape::Rectangle(r, _, rotation, ref stroke, _)
+            | Shape::RoundedRectangle(r, _, _, rotation, ref stroke, _) => {
+                let e = outer_width(stroke);
+                let (x1, y1, x2, y2) = normalize(r);
+                let corners = [
+                    Point2::new(x1 - e, y1 - e),
+                    Point2::new(x2 + e, y1 - e),
+                    Point2::new(x2 + e, y2 + e),
+                    Point2::new(x1 - e, y2 + e),
+                ];
+                enclose(corners.iter().map(|p| rotation.apply(*p)))
+            }
+            Shape::Circle(
+                Circle {
+                    position, radius, ..
+                },
+                _,
+                rotation,
+                ref stroke,
+                _,
+            ) => {
+                let c = rotation.apply(position);
+                let radius = radius + outer_width(stroke);
+
+                Rect::new(c.x - radius, c.y - radius, c.x + radius, c.y + radius)
+            }
+            Shape::Ellipse(Ellipse { position, radii }, _, rotation, ref stroke, _) => {
+                let c = rotation.apply(position);
+                let radii = radii + Vector2::new(1., 1.) * outer_width(stroke);
+                let (sin, cos) = rotation.angle.sin_cos();
+                let w = ((radii.x * cos).powi(2) + (radii.y * sin).powi(2)).sqrt();
+                let h = ((radii.x * sin).powi(2) + (radii.y * cos).powi(2)).sqrt();
+
+                Rect::new(c.x - w, c.y - h, c.x + w, c.y + h)
+            }
+            Shape::Polygon(ref polygon, _, rotation, ref stroke, _)
+                if outer_width(stroke) <= 0. =>
+            {
+                enclose(polygon.points.iter().map(|p| rotation.apply(*p)))
+            }
+            _ => enclose(self.triangulate().into_iter().map(|v| {
+                let v = v.transform(Matrix4::identity());
+                Point2::new(v.position.x, v.position.y)
+            })),
+        }
+    }
+
+    /// Whether the point lies on the painted area of the shape, ie. on its
+    /// fill or its stroke. Dash gaps are considered part of the stroke.
+    pub fn contains(&self, p: Point2<f32>) -> bool {
+        self.contains_within(p, TOLERANCE)
+    }
+
+    /// Like [`Shape::contains`], with curves flattened within the given
+    /// tolerance. See [`Shape::tessellate`].
+    pub(crate) fn contains_within(&self, p: Point2<f32>, tolerance: f32) -> bool {
+        match *self {
+            Shape::Line(line, _, rotation, ref stroke) => {
+                let p = unrotate(rotation, p);
+                let w = stroke.width / 2.;
+                let d = line.p2 - line.p1;
+                let len = d.magnitude();
+
+                if stroke.cap == LineCap::Round || len == 0. {
+                    return distance_to_segment(p, line.p1, line.p2) <= w;
+                }
+                let extend = if stroke.cap == LineCap::Square { w } else { 0. };
+                let along = Vector2::dot(p - line.p1, d) / len;
+                let across = cross(line.p1, line.p2, p).abs() / len;
+
+                along >= -extend && along <= len + extend && across <= w
+            }
+            Shape::Rectangle(r, _, rotation, ref stroke, ref fill) => {
+                let p = unrotate(rotation, p);
+                let (x1, y1, x2, y2) = normalize(r);
+                let (e, w) = (outer_width(stroke), inner_width(stroke));
+                let inside = p.x >= x1 - e && p.x <= x2 + e && p.y >= y1 - e && p.y <= y2 + e;
+
+                inside
+                    && (!fill.is_empty()
+                        || (*stroke != Stroke::NONE
+                            && !(p.x > x1 + w && p.x < x2 - w && p.y > y1 + w && p.y < y2 - w)))
+            }
+            Shape::RoundedRectangle(r, radii, _, rotation, ref stroke, ref fill) => {
+                let p = unrotate(rotation, p);
+                let (e, w) = (outer_width(stroke), inner_width(stroke));
+                let inside = in_rounded_rect(p, normalize(r), radii, -e);
+
+                inside
+                    && (!fill.is_empty()
+                        || (*stroke != Stroke::NONE && !in_rounded_rect(p, normalize(r), radii, w)))
+            }
+            Shape::Circle(circle, _, rotation, ref stroke, ref fill) => {
+                let d = (unrotate(rotation, p) - circle.position).magnitude();
+
+                d <= circle.radius + outer_width(stroke)
+                    && (!fill.is_empty()
+                        || (*stroke != Stroke::NONE && d >= circle.radius - inner_width(stroke)))
+            }
+            Shape::Ellipse(Ellipse { position, radii }, _, rotation, ref stroke, ref fill) => {
+                let p = unrotate(rotation, p);
+                let inside = |rx: f32, ry: f32| {
+                    rx > 0.
+                        && ry > 0.
+                        && ((p.x - position.x) / rx).powi(2) + ((p.y - position.y) / ry).powi(2)
+                            <= 1.
+                };
+                let (e, w) = (outer_width(stroke), inner_width(stroke));
+
+                inside(radii.x + e, radii.y + e)
+                    && (!fill.is_empty()
+                        || (*stroke != Stroke::NONE && !inside(radii.x - w, radii.y - w)))
+            }
+            // Strokes of polygons and paths are tested against their
+            // triangles, which include their joins.
+            Shape::Polygon(ref polygon, _, rotation, ref stroke, ref fill) => {
+                let filled = winding(&polygon.contours(), unrotate(rotation, p)) != 0;
+
+                (!fill.is_empty() && filled)
+                    || (*stroke != Stroke::NONE && self.covers(p, tolerance))
+            }
+            Shape::Path(ref path, _, rotation, ref stroke, ref fill) => {
+                let q = unrotate(rotation, p);
+                let subpaths = path.flatten_with(tolerance);
+                let contours: Vec<Vec<Point2<f32>>> =
+                    subpaths.iter().map(|s| s.points.clone()).collect();
+                let filled = path.fill_rule.contains(winding(&contours, q));
+
+                (!fill.is_empty() && filled)
+                    || (*stroke != Stroke::NONE && self.covers(p, tolerance))
+            }
+            Shape::Transformed(ref shape, m) => match invert(&m) {
+                Some(inverse) => shape.contains_within(inverse * p, tolerance / scale_factor(&m)),
+                None => false,
+            },
+            // Arcs, pies and polylines are tested against their triangles.
+            _ => self.covers(p, tolerance),
+        }
+    }
+
+    /// Whether the point lies inside one of the shape's triangles, tessellated
+    /// with a solid stroke.
+    fn covers(&self, p: Point2<f32>, tolerance: f32) -> bool {
+        let solid = self.clone().dash(&[], 0.);
+
+        solid.tessellate(tolerance).chunks(3).any(|t| {
+            let q = |i: usize| {
+                let v = t[i].transform(Matrix4::identity());
+                Point2::new(v.position.x, v.position.y)
+            };
+            let (a, b, c) = (q(0), q(1), q(2));
+            let (ab, bc, ca) = (cross(a, b, p), cross(b, c, p), cross(c, a, p));
+
+            (ab >= 0. && bc >= 0. && ca >= 0.) || (ab <= 0. && bc <= 0. && ca <= 0.)
+        })
+    }
+}
+
+/// The smallest rectangle enclosing all points.
+fn enclose<I: Iterator<Item = Point2<f32>>>(points: I) -> Rect<f32> {
+    let mut bounds: Option<Rect<f32>> = None;
+
+    for p in points {
+        bounds = Some(match bounds {
+            Some(r) => Rect::new(r.x1.min(p.x), r.y1.min(p.y), r.x2.max(p.x), r.y2.max(p.y)),
+            None => Rect::new(p.x, p.y, p.x, p.y),
+        });
+    }
+    bounds.unwrap_or_else(Rect::zero)
+}
+
+fn normalize(r: Rect<f32>) -> (f32, f32, f32, f32) {
+    (
+        r.x1.min(r.x2),
+        r.y1.min(r.y2),
+        r.x1.max(r.x2),
+        r.y1.max(r.y2),
+    )
+}
+
+/// How far the stroke of a closed shape extends outside of its outline.
+fn outer_width(stroke: &Stroke) -> f32 {
+    stroke.width / 2. - stroke.inset()
+}
+
+/// How far the stroke of a closed shape extends inside of its outline.
+fn inner_width(stroke: &Stroke) -> f32 {
+    stroke.width / 2. + stroke.inset()
+}
+
+/// Undo a shape's rotation, bringing the point into the shape's own space.
+fn unrotate(rotation: Rotation, p: Point2<f32>) -> Point2<f32> {
+    Rotation::new(-rotation.angle, rotation.center).apply(p)
+}
+
+/// Whether a point is inside a rounded rectangle, inset by `inset`.
+fn in_rounded_rect(
+    p: Point2<f32>,
+    (x1, y1, x2, y2): (f32, f32, f32, f32),
+    radii: [f32; 4],
+    inset: f32,
+) -> bool {
+    let max = f32::min(x2 - x1, y2 - y1) / 2.;
+    let (x1, y1, x2, y2) = (x1 + inset, y1 + inset, x2 - inset, y2 - inset);
+
+    if !(p.x >= x1 && p.x <= x2 && p.y >= y1 && p.y <= y2) {
+        return false;
+    }
+    let corners = [
+        (x1, y1, 1., 1.),
+        (x2, y1, -1., 1.),
+        (x2, y2, -1., -1.),
+        (x1, y2, 1., -1.),
+    ];
+
+    corners
+        .iter()
+        .zip(radii.iter())
+        .all(|(&(x, y, sx, sy), r)| {
+            let r = (r.max(0.).min(max) - inset).max(0.);
+            let c = Point2::new(x + sx * r, y + sy * r);
+            let outside = sx * (p.x - c.x) < 0. && sy * (p.y - c.y) < 0.;
+
+            !outside || (p - c).magnitude() <= r
+        })
+}
+
+/// The inverse of a transform in the plane, if it is invertible.
+pub(crate) fn invert(m: &Matrix4<f32>) -> Option<Matrix4<f32>> {
+    let (a, b, c, d) = (m.x.x, m.x.y, m.y.x, m.y.y);
+    let (tx, ty) = (m.w.x, m.w.y);
+    let det = a * d - b * c;
+
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let (ia, ib, ic, id) = (d / det, -b / det, -c / det, a / det);
+
+    #[rustfmt::skip]
+    let inverse = Matrix4::new(
+        ia, ib, 0., 0.,
+        ic, id, 0., 0.,
+        0., 0., 1., 0.,
+        -(ia * tx + ic * ty), -(ib * tx + id * ty), 0., 1.,
+    );
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Rgba;
+    use std::f32;
+
+    #[test]
+    fn test_bounds() {
+        let rect = Shape::rect([0., 0.], [4., 2.]).stroke(1., Rgba::WHITE);
+        let b = rect.bounds();
+        assert_eq!((b.x1, b.y1, b.x2, b.y2), (0., 0., 4., 2.));
+
+        // A square rotated by 45 degrees around its center.
+        let rotated =
+            Shape::rect([-1., -1.], [1., 1.]).rotation(f32::consts::FRAC_PI_4, Point2::new(0., 0.));
+        let b = rotated.bounds();
+        let s = 2f32.sqrt();
+        for (x, y) in &[(b.x1, -s), (b.y1, -s), (b.x2, s), (b.y2, s)] {
+            assert!((x - y).abs() < 1e-4, "{} != {}", x, y);
+        }
+
+        // Lines extend by their stroke width and caps.
+        let line = Shape::line([0., 0.], [10., 0.])
+            .stroke(2., Rgba::WHITE)
+            .cap(LineCap::Square);
+        let b = line.bounds();
+        assert_eq!((b.x1, b.y1, b.x2, b.y2), (-1., -1., 11., 1.));
+
+        // A rotated ellipse.
+        let ellipse =
+            Shape::ellipse([0., 0.], 4., 1.).rotation(f32::consts::FRAC_PI_2, Point2::new(0., 0.));
+        let b = ellipse.bounds();
+        assert!((b.width() - 2.).abs() < 1e-4 && (b.height() - 8.).abs() < 1e-4);
+
+        let circle = Shape::circle([5., 5.], 2., 32);
+        assert_eq!(circle.bounds(), Rect::new(3., 3., 7., 7.));
+    }
+
+    #[test]
+    fn test_contains() {
+        // Lines are hit within half their stroke width.
+        let line = Shape::line([0., 0.], [10., 0.]).stroke(2., Rgba::WHITE);
+        assert!(line.contains(Point2::new(5., 0.9)));
+        assert!(!line.contains(Point2::new(5., 1.1)));
+        assert!(!line.contains(Point2::new(-0.5, 0.)));
+        assert!(line
+            .clone()
+            .cap(LineCap::Square)
+            .contains(Point2::new(-0.5, 0.)));
+        assert!(line.cap(LineCap::Round).contains(Point2::new(-0.5, 0.5)));
+
+        // Rotated rectangles.
+        let rect = Shape::rect([-2., -1.], [2., 1.])
+            .fill(Fill::solid(Rgba::RED))
+            .rotation(f32::consts::FRAC_PI_2, Point2::new(0., 0.));
+        assert!(rect.contains(Point2::new(0., 1.9)));
+        assert!(!rect.contains(Point2::new(1.9, 0.)));
+
+        // Without a fill, only the stroke is hit.
+        let outline = Shape::rect([0., 0.], [10., 10.]).stroke(1., Rgba::WHITE);
+        assert!(outline.contains(Point2::new(0.5, 5.)));
+        assert!(!outline.contains(Point2::new(5., 5.)));
+
+        let circle = Shape::circle([0., 0.], 4., 32).fill(Fill::solid(Rgba::RED));
+        assert!(circle.contains(Point2::new(2.8, 2.8)));
+        assert!(!circle.contains(Point2::new(2.9, 2.9)));
+
+        // Polygons respect their holes.
+        let polygon = Shape::polygon(
+            Polygon::new(vec![[0., 0.], [10., 0.], [10., 10.], [0., 10.]]).hole(vec![
+                [4., 4.],
+                [6., 4.],
+                [6., 6.],
+                [4., 6.],
+            ]),
+        )
+        .fill(Fill::solid(Rgba::RED));
+        assert!(polygon.contains(Point2::new(2., 2.)));
+        assert!(!polygon.contains(Point2::new(5., 5.)));
+        assert!(!polygon.contains(Point2::new(11., 5.)));
+
+        // Strokes include their miter joins, and their dash gaps.
+        let outline = Shape::polygon(Polygon::new(vec![
+            [0., 0.],
+            [10., 0.],
+            [10., 10.],
+            [0., 10.],
+        ]))
+        .stroke(2., Rgba::WHITE)
+        .alignment(StrokeAlignment::Outside);
+        assert!(outline.contains(Point2::new(-1.8, -1.8)));
+        assert!(!outline.contains(Point2::new(-2.2, -1.8)));
+        assert!(!outline.contains(Point2::new(5., 5.)));
+        let dashed = outline.dash(&[1., 1.], 0.);
+        assert!(dashed.contains(Point2::new(2.5, -1.)));
+
+        // Paths respect their fill rule.
+        let square = |path: Path, x: f32, s: f32| {
+            path.move_to([x, x])
+                .line_to([x + s, x])
+                .line_to([x + s, x + s])
+                .line_to([x, x + s])
+                .close()
+        };
+        let path = square(square(Path::default(), 0., 10.), 2., 6.);
+        let nonzero = Shape::path(path.clone()).fill(Fill::solid(Rgba::RED));
+        let evenodd = Shape::path(path.fill_rule(FillRule::EvenOdd)).fill(Fill::solid(Rgba::RED));
+        assert!(nonzero.contains(Point2::new(5., 5.)));
+        assert!(!evenodd.contains(Point2::new(5., 5.)));
+        assert!(evenodd.contains(Point2::new(1., 5.)));
+
+        // Transformed shapes.
+        let scaled = Shape::circle([0., 0.], 1., 32)
+            .fill(Fill::solid(Rgba::RED))
+            .transform(Matrix4::from_nonuniform_scale(4., 1., 1.));
+        assert!(scaled.contains(Point2::new(3.5, 0.)));
+        assert!(!scaled.contains(Point2::new(0., 1.5)));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::math::Point2;
+
+/// Indexed triangles: a list of vertices, and three indices into it per
+/// triangle. Tessellators emit meshes with the vertices shared between
+/// triangles, such as the corners of a stroke segment or the rim of a fan,
+/// stored once.
+#[derive(Debug)]
+pub(super) struct Mesh<T = Point2<f32>> {
+    pub vertices: Vec<T>,
+    pub indices: Vec<u32>,
+}
+
+impl<T: Copy> Mesh<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0, 0)
+    }
+
+    pub fn with_capacity(vertices: usize, indices: usize) -> Self {
+        Self {
+            vertices: Vec::with_capacity(vertices),
+            indices: Vec::with_capacity(indices),
+        }
+    }
+
+    /// A mesh with no shared vertices, from a triangle list.
+    pub fn from_triangles(triangles: Vec<T>) -> Self {
+        let indices = (0..triangles.len() as u32).collect();
+
+        Self {
+            vertices: triangles,
+            indices,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn reserve(&mut self, vertices: usize, indices: usize) {
+        self.vertices.reserve(vertices);
+        self.indices.reserve(indices);
+    }
+
+    /// Add a vertex, and return its index.
+    pub fn vertex(&mut self, v: T) -> u32 {
+        self.vertices.push(v);
+        self.vertices.len() as u32 - 1
+    }
+
+    pub fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.extend_from_slice(&[a, b, c]);
+    }
+
+    /// Add the triangles of another mesh.
+    pub fn append(&mut self, other: Self) {
+        let offset = self.vertices.len() as u32;
+
+        self.vertices.extend(other.vertices);
+        self.indices
+            .extend(other.indices.into_iter().map(|i| i + offset));
+    }
+
+    /// Concatenate two meshes, allocating the result once.
+    pub fn chain(self, other: Self) -> Self {
+        if other.is_empty() {
+            return self;
+        } else if self.is_empty() {
+            return other;
+        }
+        let mut mesh = Self::with_capacity(
+            self.vertices.len() + other.vertices.len(),
+            self.indices.len() + other.indices.len(),
+        );
+        mesh.append(self);
+        mesh.append(other);
+        mesh
+    }
+
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Mesh<U> {
+        Mesh {
+            vertices: self.vertices.into_iter().map(f).collect(),
+            indices: self.indices,
+        }
+    }
+
+    /// The mesh as a triangle list.
+    pub fn triangles(&self) -> Vec<T> {
+        self.indices
+            .iter()
+            .map(|i| self.vertices[*i as usize])
+            .collect()
+    }
+}
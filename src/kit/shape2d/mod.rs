@@ -3,6 +3,26 @@ mod backend;
 #[cfg(feature = "renderer")]
 pub use backend::*;
 
+mod feather;
+mod gradient;
+mod hit;
+mod index;
+mod path;
+mod pattern;
+pub(crate) mod polygon;
+mod retained;
+mod stroke;
+pub mod svg;
+pub use gradient::Stops;
+pub use path::{Command, FillRule, Path, Subpath};
+pub use pattern::Pattern;
+pub use polygon::Polygon;
+pub use retained::{Handle, Retained};
+pub use stroke::{LineCap, LineJoin, Stroke, StrokeAlignment};
+
+pub(crate) use hit::invert;
+use index::Mesh;
+
 use crate::color::Rgba;
 use crate::kit::{Geometry, Rgba8, ZDepth};
 use crate::math::*;
@@ -10,6 +30,9 @@ use crate::rect::Rect;
 
 use std::f32;
 
+/// Default maximum distance between a curve and its tessellation, in pixels.
+pub const TOLERANCE: f32 = 0.25;
+
 ///////////////////////////////////////////////////////////////////////////
 // Vertex
 ///////////////////////////////////////////////////////////////////////////
@@ -34,6 +57,24 @@ impl Vertex {
     }
 }
 
+/// Bakes the vertex rotation into its position before applying the transform,
+/// so that the result no longer depends on the shader's rotation.
+impl Geometry for Vertex {
+    fn transform(self, m: Matrix4<f32>) -> Self {
+        let rotation = Rotation::new(self.angle, Point2::new(self.center.x, self.center.y));
+        let p = m * rotation.apply(Point2::new(self.position.x, self.position.y));
+
+        Self::new(
+            p.x,
+            p.y,
+            self.position.z,
+            0.,
+            Point2::new(0., 0.),
+            self.color,
+        )
+    }
+}
+
 #[inline]
 pub const fn vertex(
     x: f32,
@@ -46,58 +87,286 @@ pub const fn vertex(
     Vertex::new(x, y, z, angle, center, color)
 }
 
-///////////////////////////////////////////////////////////////////////////////////////////////////
-// Shapes
-///////////////////////////////////////////////////////////////////////////////////////////////////
-
-#[derive(PartialEq, Copy, Clone, Debug)]
-pub struct Stroke {
-    width: f32,
-    color: Rgba,
+/// A vertex of a shape with a [`Fill::Texture`], drawn with the texture
+/// pipeline.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct TexturedVertex {
+    pub position: Vector3<f32>,
+    pub angle: f32,
+    pub center: Vector2<f32>,
+    pub uv: Vector2<f32>,
+    pub color: Rgba8,
 }
 
-impl Stroke {
-    pub const NONE: Self = Self {
-        width: 0.,
-        color: Rgba::TRANSPARENT,
-    };
-
-    pub fn new(width: f32, color: Rgba) -> Self {
-        Self { width, color }
+impl TexturedVertex {
+    fn new(v: Vertex, uv: Point2<f32>, color: Rgba8) -> Self {
+        Self {
+            position: v.position,
+            angle: v.angle,
+            center: v.center,
+            uv: Vector2::new(uv.x, uv.y),
+            color,
+        }
     }
 }
 
-impl Default for Stroke {
-    fn default() -> Self {
-        Self {
-            width: 1.,
-            color: Rgba::default(),
+/// Like [`Vertex`], bakes the rotation into the position. Texture coordinates
+/// are left untouched.
+impl Geometry for TexturedVertex {
+    fn transform(self, m: Matrix4<f32>) -> Self {
+        let v = Vertex {
+            position: self.position,
+            angle: self.angle,
+            center: self.center,
+            color: self.color,
         }
+        .transform(m);
+        let uv = Point2::new(self.uv.x, self.uv.y);
+
+        Self::new(v, uv, self.color)
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+///////////////////////////////////////////////////////////////////////////////////////////////////
+// Shapes
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, Default)]
 pub enum Fill {
+    #[default]
     Empty,
     Solid(Rgba),
+    /// A gradient along the line from `start` to `end`, with color stops at
+    /// offsets between `0` and `1` along it.
+    LinearGradient {
+        start: Point2<f32>,
+        end: Point2<f32>,
+        stops: Stops,
+    },
+    /// A gradient from `center` outwards, with color stops at offsets between
+    /// `0` at the center and `1` at the radius.
+    RadialGradient {
+        center: Point2<f32>,
+        radius: f32,
+        stops: Stops,
+    },
+    /// A repeating pattern of two colors. The transform maps shape coordinates
+    /// to pattern space, where cells are of unit size.
+    Pattern {
+        pattern: Pattern,
+        colors: [Rgba; 2],
+        transform: Matrix4<f32>,
+    },
+    /// A texture, whose texels are multiplied by the given color. The
+    /// transform maps shape coordinates to texture coordinates. Textured
+    /// fills aren't part of [`Shape::tessellate`]; they are drawn from
+    /// [`Shape::tessellate_textured`] with a texture pipeline.
+    Texture {
+        color: Rgba,
+        transform: Matrix4<f32>,
+    },
 }
 
 impl Fill {
     pub fn solid<T: Into<Rgba>>(color: T) -> Self {
         Self::Solid(color.into())
     }
+
+    pub fn linear_gradient<P: Into<Point2<f32>>>(start: P, end: P, stops: &[(f32, Rgba)]) -> Self {
+        Self::LinearGradient {
+            start: start.into(),
+            end: end.into(),
+            stops: Stops::new(stops),
+        }
+    }
+
+    pub fn radial_gradient<P: Into<Point2<f32>>>(
+        center: P,
+        radius: f32,
+        stops: &[(f32, Rgba)],
+    ) -> Self {
+        Self::RadialGradient {
+            center: center.into(),
+            radius,
+            stops: Stops::new(stops),
+        }
+    }
+
+    /// A checkerboard of square cells of the given size, aligned to the origin.
+    pub fn checkerboard<T: Into<Rgba>>(size: f32, a: T, b: T) -> Self {
+        Self::Pattern {
+            pattern: Pattern::Checkerboard,
+            colors: [a.into(), b.into()],
+            transform: Matrix4::from_scale(1. / size),
+        }
+    }
+
+    /// Stripes of the given width, running in the direction of `angle`, in
+    /// radians from the x-axis.
+    pub fn stripes<T: Into<Rgba>>(width: f32, angle: f32, a: T, b: T) -> Self {
+        Self::Pattern {
+            pattern: Pattern::Stripes,
+            colors: [a.into(), b.into()],
+            transform: Self::lines(width, angle),
+        }
+    }
+
+    /// Lines of the given width and color, `spacing` apart, running in the
+    /// direction of `angle`, in radians from the x-axis.
+    pub fn hatch<T: Into<Rgba>>(
+        spacing: f32,
+        width: f32,
+        angle: f32,
+        line: T,
+        background: T,
+    ) -> Self {
+        Self::Pattern {
+            pattern: Pattern::Hatch(width / spacing),
+            colors: [line.into(), background.into()],
+            transform: Self::lines(spacing, angle),
+        }
+    }
+
+    /// A texture stretched over the given rectangle, and repeated beyond it if
+    /// the sampler repeats. Like sprites, the texture's first row is at the
+    /// bottom of the rectangle, which must not be empty.
+    pub fn texture(rect: Rect<f32>) -> Self {
+        let (w, h) = (rect.x2 - rect.x1, rect.y2 - rect.y1);
+        assert!(
+            w != 0. && h != 0.,
+            "Fill::texture: the rectangle must have a non-zero width and height"
+        );
+
+        Self::Texture {
+            color: Rgba::WHITE,
+            transform: Matrix4::from_translation(Vector3::new(-rect.x1 / w, rect.y2 / h, 0.))
+                * Matrix4::from_nonuniform_scale(1. / w, -1. / h, 1.),
+        }
+    }
+
+    /// A transform to pattern space whose x-axis runs across lines of the
+    /// given spacing and angle.
+    fn lines(spacing: f32, angle: f32) -> Matrix4<f32> {
+        let (s, c) = angle.sin_cos();
+
+        #[rustfmt::skip]
+        let across = Matrix4::new(
+            -s, c,  0., 0.,
+            c,  s,  0., 0.,
+            0., 0., 1., 0.,
+            0., 0., 0., 1.,
+        );
+        Matrix4::from_scale(1. / spacing) * across
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    /// Whether the fill varies over the shape.
+    pub fn is_gradient(&self) -> bool {
+        matches!(
+            self,
+            Self::LinearGradient { .. } | Self::RadialGradient { .. }
+        )
+    }
+
+    /// Whether the fill is drawn with a texture pipeline.
+    pub fn is_texture(&self) -> bool {
+        matches!(self, Self::Texture { .. })
+    }
+
+    /// Color the triangles of a fill. Solid fills keep the sharing of
+    /// vertices in the mesh, while gradients and patterns subdivide it.
+    fn mesh(
+        &self,
+        mesh: Mesh,
+        z: f32,
+        angle: f32,
+        center: Point2<f32>,
+        tolerance: f32,
+    ) -> Mesh<Vertex> {
+        let (kind, stops) = match self {
+            Self::Empty | Self::Texture { .. } => return Mesh::new(),
+            Self::Solid(color) => {
+                let rgba8 = (*color).into();
+
+                return mesh.map(|p| vertex(p.x, p.y, z, angle, center, rgba8));
+            }
+            Self::LinearGradient { start, end, stops } => {
+                (gradient::Kind::Linear(*start, *end), stops)
+            }
+            Self::RadialGradient {
+                center,
+                radius,
+                stops,
+            } => (gradient::Kind::Radial(*center, *radius), stops),
+            Self::Pattern {
+                pattern,
+                colors,
+                transform,
+            } => {
+                return Mesh::from_triangles(
+                    pattern::shade(&mesh.triangles(), *pattern, *colors, *transform)
+                        .into_iter()
+                        .map(|(p, color)| vertex(p.x, p.y, z, angle, center, color.into()))
+                        .collect(),
+                )
+            }
+        };
+
+        Mesh::from_triangles(
+            gradient::shade(&mesh.triangles(), kind, stops, tolerance)
+                .into_iter()
+                .map(|(p, color)| vertex(p.x, p.y, z, angle, center, color.into()))
+                .collect(),
+        )
+    }
 }
 
-impl Default for Fill {
-    fn default() -> Self {
-        Self::Empty
+/// Gradients, patterns and textures are transformed along with the shape. Only
+/// similarity transforms preserve the shape of a gradient, while patterns and
+/// textures follow any invertible transform.
+impl Geometry for Fill {
+    fn transform(self, m: Matrix4<f32>) -> Self {
+        match self {
+            Self::LinearGradient { start, end, stops } => Self::LinearGradient {
+                start: m * start,
+                end: m * end,
+                stops,
+            },
+            Self::RadialGradient {
+                center,
+                radius,
+                stops,
+            } => Self::RadialGradient {
+                center: m * center,
+                radius: radius * scale_factor(&m),
+                stops,
+            },
+            Self::Pattern {
+                pattern,
+                colors,
+                transform,
+            } => Self::Pattern {
+                pattern,
+                colors,
+                transform: invert(&m).map_or(transform, |inv| transform * inv),
+            },
+            Self::Texture { color, transform } => Self::Texture {
+                color,
+                transform: invert(&m).map_or(transform, |inv| transform * inv),
+            },
+            other => other,
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Copy, Clone, Debug)]
 pub struct Rotation {
-    angle: f32,
-    center: Point2<f32>,
+    pub(crate) angle: f32,
+    pub(crate) center: Point2<f32>,
 }
 
 impl Rotation {
@@ -109,6 +378,26 @@ impl Rotation {
     pub fn new(angle: f32, center: Point2<f32>) -> Self {
         Self { angle, center }
     }
+
+    /// Rotate a point, the same way the shape shader does.
+    pub fn apply(&self, p: Point2<f32>) -> Point2<f32> {
+        let (s, c) = self.angle.sin_cos();
+        let (dx, dy) = (p.x - self.center.x, p.y - self.center.y);
+
+        Point2::new(
+            self.center.x + c * dx + s * dy,
+            self.center.y - s * dx + c * dy,
+        )
+    }
+
+    /// The rotation as a transform matrix.
+    fn matrix(&self) -> Matrix4<f32> {
+        let c = Vector3::new(self.center.x, self.center.y, 0.);
+
+        Matrix4::from_translation(c)
+            * Matrix4::from_angle_z(-self.angle)
+            * Matrix4::from_translation(Vector3::new(-c.x, -c.y, 0.))
+    }
 }
 
 impl Default for Rotation {
@@ -121,7 +410,22 @@ impl Default for Rotation {
 pub enum Shape {
     Line(Line, ZDepth, Rotation, Stroke),
     Rectangle(Rect<f32>, ZDepth, Rotation, Stroke, Fill),
-    Circle(Circle, ZDepth, Stroke, Fill),
+    Circle(Circle, ZDepth, Rotation, Stroke, Fill),
+    Polygon(Polygon, ZDepth, Rotation, Stroke, Fill),
+    /// A rectangle with the given corner radii, counter-clockwise from the
+    /// `(x1, y1)` corner.
+    RoundedRectangle(Rect<f32>, [f32; 4], ZDepth, Rotation, Stroke, Fill),
+    Ellipse(Ellipse, ZDepth, Rotation, Stroke, Fill),
+    /// An open arc. Its fill is the region between the arc and its chord.
+    Arc(Arc, ZDepth, Rotation, Stroke, Fill),
+    /// A circular sector, or pie slice.
+    Pie(Arc, ZDepth, Rotation, Stroke, Fill),
+    Path(Path, ZDepth, Rotation, Stroke, Fill),
+    /// A continuous stroke through a list of points, with optional per-point
+    /// widths overriding the stroke width, and whether it is closed.
+    Polyline(Vec<Point2<f32>>, ZDepth, Rotation, Stroke, Vec<f32>, bool),
+    /// A shape with an arbitrary transform applied to its vertices.
+    Transformed(Box<Shape>, Matrix4<f32>),
 }
 
 impl Shape {
@@ -135,6 +439,7 @@ impl Shape {
                 sides,
             },
             ZDepth::default(),
+            Rotation::default(),
             Stroke::default(),
             Fill::default(),
         )
@@ -161,13 +466,203 @@ impl Shape {
         )
     }
 
+    pub fn polygon(polygon: Polygon) -> Self {
+        Self::Polygon(
+            polygon,
+            ZDepth::default(),
+            Rotation::default(),
+            Stroke::default(),
+            Fill::default(),
+        )
+    }
+
+    pub fn triangle<P: Into<Point2<f32>>>(p1: P, p2: P, p3: P) -> Self {
+        Self::polygon(Polygon::new(vec![p1, p2, p3]))
+    }
+
+    /// A regular polygon with the given number of sides, inscribed in a circle
+    /// of the given radius. The first corner lies on the positive x-axis; use
+    /// [`Shape::rotation`] to orient it.
+    pub fn regular_polygon<P: Into<Point2<f32>>>(position: P, radius: f32, sides: u32) -> Self {
+        let position = position.into();
+        let sides = sides.max(3);
+
+        Self::polygon(Polygon::new((0..sides).map(|i| {
+            let a = 2. * f32::consts::PI * i as f32 / sides as f32;
+            position + Vector2::new(a.cos(), a.sin()) * radius
+        })))
+    }
+
+    /// A star with the given number of points, alternating between corners on
+    /// the outer and the inner radius. The first point lies on the positive
+    /// x-axis.
+    pub fn star<P: Into<Point2<f32>>>(position: P, points: u32, outer: f32, inner: f32) -> Self {
+        let position = position.into();
+        let n = points.max(2) * 2;
+
+        Self::polygon(Polygon::new((0..n).map(|i| {
+            let a = 2. * f32::consts::PI * i as f32 / n as f32;
+            let r = if i % 2 == 0 { outer } else { inner };
+
+            position + Vector2::new(a.cos(), a.sin()) * r
+        })))
+    }
+
+    /// An arrow from `from` to `to`, with a shaft of the given width, and a
+    /// head of the given width and length pointing at `to`. If the head is
+    /// longer than the arrow, only the head is drawn.
+    pub fn arrow<P: Into<Point2<f32>>>(
+        from: P,
+        to: P,
+        width: f32,
+        head_width: f32,
+        head_length: f32,
+    ) -> Self {
+        let (from, to) = (from.into(), to.into());
+        let len = (to - from).magnitude();
+        let dir = if len > 0. {
+            (to - from).normalize()
+        } else {
+            Vector2::new(0., 0.)
+        };
+        let normal = Vector2::new(-dir.y, dir.x);
+        let (w, hw) = (normal * (width / 2.), normal * (head_width / 2.));
+
+        if head_length >= len {
+            let base = to - dir * len;
+            return Self::triangle(base + hw, to, base - hw);
+        }
+        let base = to - dir * head_length;
+
+        Self::polygon(Polygon::new(vec![
+            from - w,
+            base - w,
+            base - hw,
+            to,
+            base + hw,
+            base + w,
+            from + w,
+        ]))
+    }
+
+    pub fn rounded_rect<P: Into<Point2<f32>>>(p1: P, p2: P, radii: [f32; 4]) -> Self {
+        let (p1, p2) = (p1.into(), p2.into());
+
+        Self::RoundedRectangle(
+            Rect::new(p1.x, p1.y, p2.x, p2.y),
+            radii,
+            ZDepth::default(),
+            Rotation::default(),
+            Stroke::default(),
+            Fill::default(),
+        )
+    }
+
+    pub fn ellipse<P: Into<Point2<f32>>>(position: P, rx: f32, ry: f32) -> Self {
+        Self::Ellipse(
+            Ellipse {
+                position: position.into(),
+                radii: Vector2::new(rx, ry),
+            },
+            ZDepth::default(),
+            Rotation::default(),
+            Stroke::default(),
+            Fill::default(),
+        )
+    }
+
+    /// An arc from the `start` to the `end` angle, in radians, counter-clockwise
+    /// from the positive x-axis.
+    pub fn arc<P: Into<Point2<f32>>>(position: P, radius: f32, start: f32, end: f32) -> Self {
+        Self::Arc(
+            Arc {
+                position: position.into(),
+                radius,
+                start,
+                end,
+            },
+            ZDepth::default(),
+            Rotation::default(),
+            Stroke::default(),
+            Fill::default(),
+        )
+    }
+
+    /// A pie slice from the `start` to the `end` angle, in radians.
+    pub fn pie<P: Into<Point2<f32>>>(position: P, radius: f32, start: f32, end: f32) -> Self {
+        Self::Pie(
+            Arc {
+                position: position.into(),
+                radius,
+                start,
+                end,
+            },
+            ZDepth::default(),
+            Rotation::default(),
+            Stroke::default(),
+            Fill::default(),
+        )
+    }
+
+    /// A path, stroked centered on its outline by default.
+    pub fn path(path: Path) -> Self {
+        Self::Path(
+            path,
+            ZDepth::default(),
+            Rotation::default(),
+            Stroke::default().alignment(StrokeAlignment::Center),
+            Fill::default(),
+        )
+    }
+
+    /// A polyline, stroked centered on its points by default.
+    pub fn polyline<P: Into<Point2<f32>>, I: IntoIterator<Item = P>>(points: I) -> Self {
+        Self::Polyline(
+            points.into_iter().map(|p| p.into()).collect(),
+            ZDepth::default(),
+            Rotation::default(),
+            Stroke::default().alignment(StrokeAlignment::Center),
+            Vec::new(),
+            false,
+        )
+    }
+
+    /// Set the width at each point of a polyline, eg. for pressure-sensitive
+    /// brush strokes. There should be as many widths as points.
+    pub fn widths(mut self, w: Vec<f32>) -> Self {
+        match self {
+            Self::Polyline(_, _, _, _, ref mut widths, _) => *widths = w,
+            Self::Transformed(shape, m) => return Self::Transformed(Box::new(shape.widths(w)), m),
+            _ => {}
+        }
+        self
+    }
+
+    /// Close a polyline into a loop.
+    pub fn closed(mut self, c: bool) -> Self {
+        match self {
+            Self::Polyline(_, _, _, _, _, ref mut closed) => *closed = c,
+            Self::Transformed(shape, m) => return Self::Transformed(Box::new(shape.closed(c)), m),
+            _ => {}
+        }
+        self
+    }
+
     pub fn zdepth<T: Into<ZDepth>>(mut self, z: T) -> Self {
         let z: ZDepth = z.into();
 
         match self {
             Self::Line(_, ref mut zdepth, _, _) => *zdepth = z,
             Self::Rectangle(_, ref mut zdepth, _, _, _) => *zdepth = z,
-            Self::Circle(_, ref mut zdepth, _, _) => *zdepth = z,
+            Self::Circle(_, ref mut zdepth, _, _, _) => *zdepth = z,
+            Self::Polygon(_, ref mut zdepth, _, _, _) => *zdepth = z,
+            Self::RoundedRectangle(_, _, ref mut zdepth, _, _, _) => *zdepth = z,
+            Self::Ellipse(_, ref mut zdepth, _, _, _) => *zdepth = z,
+            Self::Arc(_, ref mut zdepth, _, _, _) => *zdepth = z,
+            Self::Pie(_, ref mut zdepth, _, _, _) => *zdepth = z,
+            Self::Path(_, ref mut zdepth, _, _, _) => *zdepth = z,
+            Self::Polyline(_, ref mut zdepth, _, _, _, _) => *zdepth = z,
+            Self::Transformed(shape, m) => return Self::Transformed(Box::new(shape.zdepth(z)), m),
         }
         self
     }
@@ -179,7 +674,17 @@ impl Shape {
         match self {
             Self::Line(_, _, ref mut rotation, _) => *rotation = r,
             Self::Rectangle(_, _, ref mut rotation, _, _) => *rotation = r,
-            _ => {}
+            Self::Circle(_, _, ref mut rotation, _, _) => *rotation = r,
+            Self::Polygon(_, _, ref mut rotation, _, _) => *rotation = r,
+            Self::RoundedRectangle(_, _, _, ref mut rotation, _, _) => *rotation = r,
+            Self::Ellipse(_, _, ref mut rotation, _, _) => *rotation = r,
+            Self::Arc(_, _, ref mut rotation, _, _) => *rotation = r,
+            Self::Pie(_, _, ref mut rotation, _, _) => *rotation = r,
+            Self::Path(_, _, ref mut rotation, _, _) => *rotation = r,
+            Self::Polyline(_, _, ref mut rotation, _, _, _) => *rotation = r,
+            Self::Transformed(shape, m) => {
+                return Self::Transformed(Box::new(shape.rotation(angle, center)), m)
+            }
         }
         self
     }
@@ -187,165 +692,384 @@ impl Shape {
     pub fn fill(mut self, f: Fill) -> Self {
         match self {
             Self::Rectangle(_, _, _, _, ref mut fill) => *fill = f,
-            Self::Circle(_, _, _, ref mut fill) => *fill = f,
+            Self::Circle(_, _, _, _, ref mut fill) => *fill = f,
+            Self::Polygon(_, _, _, _, ref mut fill) => *fill = f,
+            Self::RoundedRectangle(_, _, _, _, _, ref mut fill) => *fill = f,
+            Self::Ellipse(_, _, _, _, ref mut fill) => *fill = f,
+            Self::Arc(_, _, _, _, ref mut fill) => *fill = f,
+            Self::Pie(_, _, _, _, ref mut fill) => *fill = f,
+            Self::Path(_, _, _, _, ref mut fill) => *fill = f,
+            Self::Transformed(shape, m) => return Self::Transformed(Box::new(shape.fill(f)), m),
             _ => {}
         }
         self
     }
 
+    /// Set the stroke width and color, keeping the rest of the stroke style.
     pub fn stroke<T: Into<Rgba>>(mut self, width: f32, color: T) -> Self {
-        let s = Stroke::new(width, color.into());
+        let color = color.into();
 
-        match self {
-            Self::Line(_, _, _, ref mut stroke) => *stroke = s,
-            Self::Rectangle(_, _, _, ref mut stroke, _) => *stroke = s,
-            Self::Circle(_, _, ref mut stroke, _) => *stroke = s,
+        if let Some(stroke) = self.stroke_mut() {
+            stroke.width = width;
+            stroke.color = color;
+        }
+        self
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        if let Some(stroke) = self.stroke_mut() {
+            stroke.cap = cap;
+        }
+        self
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        if let Some(stroke) = self.stroke_mut() {
+            stroke.join = join;
+        }
+        self
+    }
+
+    /// Set where the stroke of a closed shape is drawn. See [`StrokeAlignment`].
+    /// Lines and arcs are always stroked centered.
+    pub fn alignment(mut self, alignment: StrokeAlignment) -> Self {
+        if let Some(stroke) = self.stroke_mut() {
+            stroke.alignment = alignment;
         }
         self
     }
 
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        if let Some(stroke) = self.stroke_mut() {
+            stroke.miter_limit = limit;
+        }
+        self
+    }
+
+    pub fn dash(mut self, pattern: &[f32], offset: f32) -> Self {
+        if let Some(stroke) = self.stroke_mut() {
+            *stroke = stroke.dash(pattern, offset);
+        }
+        self
+    }
+
+    fn stroke_mut(&mut self) -> Option<&mut Stroke> {
+        match self {
+            Self::Line(_, _, _, ref mut stroke) => Some(stroke),
+            Self::Rectangle(_, _, _, ref mut stroke, _) => Some(stroke),
+            Self::Circle(_, _, _, ref mut stroke, _) => Some(stroke),
+            Self::Polygon(_, _, _, ref mut stroke, _) => Some(stroke),
+            Self::RoundedRectangle(_, _, _, _, ref mut stroke, _) => Some(stroke),
+            Self::Ellipse(_, _, _, ref mut stroke, _) => Some(stroke),
+            Self::Arc(_, _, _, ref mut stroke, _) => Some(stroke),
+            Self::Pie(_, _, _, ref mut stroke, _) => Some(stroke),
+            Self::Path(_, _, _, ref mut stroke, _) => Some(stroke),
+            Self::Polyline(_, _, _, ref mut stroke, _, _) => Some(stroke),
+            Self::Transformed(shape, _) => shape.stroke_mut(),
+        }
+    }
+
+    /// The depth of the shape.
+    pub fn depth(&self) -> ZDepth {
+        match *self {
+            Self::Line(_, z, _, _) => z,
+            Self::Rectangle(_, z, _, _, _) => z,
+            Self::Circle(_, z, _, _, _) => z,
+            Self::Polygon(_, z, _, _, _) => z,
+            Self::RoundedRectangle(_, _, z, _, _, _) => z,
+            Self::Ellipse(_, z, _, _, _) => z,
+            Self::Arc(_, z, _, _, _) => z,
+            Self::Pie(_, z, _, _, _) => z,
+            Self::Path(_, z, _, _, _) => z,
+            Self::Polyline(_, z, _, _, _, _) => z,
+            Self::Transformed(ref shape, _) => shape.depth(),
+        }
+    }
+
     pub fn triangulate(&self) -> Vec<Vertex> {
+        self.tessellate(TOLERANCE)
+    }
+
+    /// Triangulate the shape into indexed geometry: a list of vertices, and a
+    /// list of indices into it, three per triangle. Vertices shared between
+    /// triangles are stored once.
+    pub fn triangulate_indexed(&self) -> (Vec<Vertex>, Vec<u32>) {
+        self.tessellate_indexed(TOLERANCE)
+    }
+
+    /// Triangulate the shape, such that curves are no further than `tolerance`
+    /// from their tessellation. The number of segments of each curve is
+    /// derived from its size and the tolerance.
+    pub fn tessellate(&self, tolerance: f32) -> Vec<Vertex> {
+        self.mesh(tolerance).triangles()
+    }
+
+    /// Like [`Shape::tessellate`], as indexed geometry. See
+    /// [`Shape::triangulate_indexed`].
+    pub fn tessellate_indexed(&self, tolerance: f32) -> (Vec<Vertex>, Vec<u32>) {
+        let Mesh { vertices, indices } = self.mesh(tolerance);
+        (vertices, indices)
+    }
+
+    fn mesh(&self, tolerance: f32) -> Mesh<Vertex> {
+        if let Shape::Transformed(ref shape, m) = *self {
+            return shape
+                .mesh(tolerance / scale_factor(&m))
+                .map(|v| v.transform(m));
+        }
+        let (ZDepth(z), Rotation { angle, center }, stroke, fill) = self.style();
+
+        // The stroke is drawn over the fill, which it overlaps on open shapes.
+        let mesh = if fill.is_empty() || fill.is_texture() {
+            Mesh::new()
+        } else {
+            fill.mesh(self.fill_mesh(tolerance), z, angle, center, tolerance)
+        };
+        let rgba8 = stroke.color.into();
+
+        mesh.chain(
+            self.outline_mesh(tolerance)
+                .map(|p| vertex(p.x, p.y, z, angle, center, rgba8)),
+        )
+    }
+
+    /// The depth, rotation, stroke and fill of the shape. Shapes without a
+    /// fill have an empty fill.
+    fn style(&self) -> (ZDepth, Rotation, Stroke, Fill) {
         match *self {
-            Shape::Line(l, ZDepth(z), Rotation { angle, center }, Stroke { width, color }) => {
-                let v = (l.p2 - l.p1).normalize();
-
-                let wx = width / 2.0 * v.y;
-                let wy = width / 2.0 * v.x;
-                let rgba8 = color.into();
-
-                vec![
-                    vertex(l.p1.x - wx, l.p1.y + wy, z, angle, center, rgba8),
-                    vertex(l.p1.x + wx, l.p1.y - wy, z, angle, center, rgba8),
-                    vertex(l.p2.x - wx, l.p2.y + wy, z, angle, center, rgba8),
-                    vertex(l.p2.x - wx, l.p2.y + wy, z, angle, center, rgba8),
-                    vertex(l.p1.x + wx, l.p1.y - wy, z, angle, center, rgba8),
-                    vertex(l.p2.x + wx, l.p2.y - wy, z, angle, center, rgba8),
-                ]
-            }
-            Shape::Rectangle(r, ZDepth(z), Rotation { angle, center }, stroke, fill) => {
-                let width = stroke.width;
-                let inner = Rect::new(r.x1 + width, r.y1 + width, r.x2 - width, r.y2 - width);
-
-                let mut verts = if stroke != Stroke::NONE {
-                    let rgba8 = stroke.color.into();
-
-                    let outer = r;
-
-                    vec![
-                        // Bottom
-                        vertex(outer.x1, outer.y1, z, angle, center, rgba8),
-                        vertex(outer.x2, outer.y1, z, angle, center, rgba8),
-                        vertex(inner.x1, inner.y1, z, angle, center, rgba8),
-                        vertex(inner.x1, inner.y1, z, angle, center, rgba8),
-                        vertex(outer.x2, outer.y1, z, angle, center, rgba8),
-                        vertex(inner.x2, inner.y1, z, angle, center, rgba8),
-                        // Left
-                        vertex(outer.x1, outer.y1, z, angle, center, rgba8),
-                        vertex(inner.x1, inner.y1, z, angle, center, rgba8),
-                        vertex(outer.x1, outer.y2, z, angle, center, rgba8),
-                        vertex(outer.x1, outer.y2, z, angle, center, rgba8),
-                        vertex(inner.x1, inner.y1, z, angle, center, rgba8),
-                        vertex(inner.x1, inner.y2, z, angle, center, rgba8),
-                        // Right
-                        vertex(inner.x2, inner.y1, z, angle, center, rgba8),
-                        vertex(outer.x2, outer.y1, z, angle, center, rgba8),
-                        vertex(outer.x2, outer.y2, z, angle, center, rgba8),
-                        vertex(inner.x2, inner.y1, z, angle, center, rgba8),
-                        vertex(inner.x2, inner.y2, z, angle, center, rgba8),
-                        vertex(outer.x2, outer.y2, z, angle, center, rgba8),
-                        // Top
-                        vertex(outer.x1, outer.y2, z, angle, center, rgba8),
-                        vertex(outer.x2, outer.y2, z, angle, center, rgba8),
-                        vertex(inner.x1, inner.y2, z, angle, center, rgba8),
-                        vertex(inner.x1, inner.y2, z, angle, center, rgba8),
-                        vertex(outer.x2, outer.y2, z, angle, center, rgba8),
-                        vertex(inner.x2, inner.y2, z, angle, center, rgba8),
-                    ]
+            Self::Line(_, z, r, stroke) => (z, r, stroke, Fill::Empty),
+            Self::Rectangle(_, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::Circle(_, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::Polygon(_, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::RoundedRectangle(_, _, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::Ellipse(_, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::Arc(_, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::Pie(_, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::Path(_, z, r, stroke, fill) => (z, r, stroke, fill),
+            Self::Polyline(_, z, r, stroke, _, _) => (z, r, stroke, Fill::Empty),
+            Self::Transformed(ref shape, _) => shape.style(),
+        }
+    }
+
+    /// Triangulate the stroke of the shape, in its own space. Closed shapes
+    /// are stroked along their outline, inset according to the stroke
+    /// alignment.
+    fn outline_mesh(&self, tolerance: f32) -> Mesh {
+        let stroke = match *self {
+            Shape::Line(l, _, _, ref stroke) => {
+                return Self::stroke_mesh(stroke, tolerance, &[l.p1, l.p2], false);
+            }
+            Shape::Polyline(ref points, _, _, ref stroke, ref widths, closed) => {
+                let uniform;
+                let widths = if widths.len() == points.len() {
+                    widths
                 } else {
-                    Vec::with_capacity(6)
+                    uniform = vec![stroke.width; points.len()];
+                    &uniform
                 };
+                let aligned;
+                let points = if closed && stroke.alignment != StrokeAlignment::Center {
+                    // The inside is to the left of counter-clockwise points.
+                    let side = polygon::signed_area(points).signum();
 
-                match fill {
-                    Fill::Solid(color) => {
-                        let rgba8 = color.into();
-
-                        verts.extend_from_slice(&[
-                            vertex(inner.x1, inner.y1, z, angle, center, rgba8),
-                            vertex(inner.x2, inner.y1, z, angle, center, rgba8),
-                            vertex(inner.x2, inner.y2, z, angle, center, rgba8),
-                            vertex(inner.x1, inner.y1, z, angle, center, rgba8),
-                            vertex(inner.x1, inner.y2, z, angle, center, rgba8),
-                            vertex(inner.x2, inner.y2, z, angle, center, rgba8),
-                        ]);
-                    }
-                    Fill::Empty => {}
-                }
-                verts
-            }
-            Shape::Circle(circle, ZDepth(z), stroke, fill) => {
-                let Circle {
-                    position,
-                    radius,
-                    sides,
-                } = circle;
-                let inner = Self::circle_points(position, radius - stroke.width, sides);
-
-                let mut verts = if stroke != Stroke::NONE {
-                    // If there is a stroke, the outer circle is larger.
-                    let outer = Self::circle_points(position, radius, sides);
-                    let rgba8 = stroke.color.into();
-
-                    let n = inner.len() - 1;
-                    let mut vs = Vec::with_capacity(n * 6);
-                    for i in 0..n {
-                        let (i0, i1) = (inner[i], inner[i + 1]);
-                        let (o0, o1) = (outer[i], outer[i + 1]);
-
-                        vs.extend_from_slice(&[
-                            vertex(i0.x, i0.y, z, 0.0, Point2::new(0.0, 0.0), rgba8),
-                            vertex(o0.x, o0.y, z, 0.0, Point2::new(0.0, 0.0), rgba8),
-                            vertex(o1.x, o1.y, z, 0.0, Point2::new(0.0, 0.0), rgba8),
-                            vertex(i0.x, i0.y, z, 0.0, Point2::new(0.0, 0.0), rgba8),
-                            vertex(o1.x, o1.y, z, 0.0, Point2::new(0.0, 0.0), rgba8),
-                            vertex(i1.x, i1.y, z, 0.0, Point2::new(0.0, 0.0), rgba8),
-                        ]);
-                    }
-                    vs
+                    aligned = polygon::offset_by(
+                        points,
+                        |i| side * stroke.alignment.inset(widths[i]),
+                        stroke.miter_limit,
+                    );
+                    &aligned
                 } else {
-                    Vec::new()
+                    points
                 };
+                return stroke.polyline(points, widths, closed, tolerance);
+            }
+            Shape::Transformed(..) => return Mesh::new(),
+            _ => self.style().2,
+        };
+        if stroke == Stroke::NONE {
+            return Mesh::new();
+        }
+        let w = stroke.inset();
 
-                match fill {
-                    Fill::Solid(color) => {
-                        let rgba8 = color.into();
-                        let center = Vertex::new(
-                            position.x,
-                            position.y,
-                            z,
-                            0.0,
-                            Point2::new(0.0, 0.0),
-                            rgba8,
-                        );
-                        let inner_verts: Vec<Vertex> = inner
-                            .iter()
-                            .map(|p| Vertex::new(p.x, p.y, z, 0., Point2::new(0.0, 0.0), rgba8))
-                            .collect();
-                        for i in 0..sides as usize {
-                            verts.extend_from_slice(&[center, inner_verts[i], inner_verts[i + 1]]);
-                        }
-                        verts.extend_from_slice(&[
-                            center,
-                            *inner_verts.last().unwrap(),
-                            *inner_verts.first().unwrap(),
-                        ]);
-                    }
-                    Fill::Empty => {}
+        match *self {
+            Shape::Rectangle(r, ..) => {
+                let points = [
+                    Point2::new(r.x1 + w, r.y1 + w),
+                    Point2::new(r.x2 - w, r.y1 + w),
+                    Point2::new(r.x2 - w, r.y2 - w),
+                    Point2::new(r.x1 + w, r.y2 - w),
+                ];
+                Self::stroke_mesh(&stroke, tolerance, &points, true)
+            }
+            Shape::Circle(circle, ..) => {
+                let sides = Self::circle_sides(circle, tolerance);
+                let mut points = Self::circle_points(circle.position, circle.radius - w, sides);
+                points.pop();
+
+                Self::stroke_mesh(&stroke, tolerance, &points, true)
+            }
+            Shape::Arc(arc, ..) => {
+                // Arcs are open, so the stroke is centered on the arc.
+                let points = Self::arc_points(arc, tolerance);
+                Self::stroke_mesh(&stroke, tolerance, &points, false)
+            }
+            Shape::Path(ref path, ..) => {
+                // Open subpaths are stroked centered on the outline.
+                let mut mesh = Mesh::new();
+
+                for subpath in path.inset_with(w, stroke.miter_limit, tolerance) {
+                    mesh.append(Self::stroke_mesh(
+                        &stroke,
+                        tolerance,
+                        &subpath.points,
+                        subpath.closed,
+                    ));
+                }
+                mesh
+            }
+            _ => {
+                let mut mesh = Mesh::new();
+
+                for contour in self.contours(tolerance) {
+                    let points = polygon::offset(&contour, w, stroke.miter_limit);
+                    mesh.append(Self::stroke_mesh(&stroke, tolerance, &points, true));
+                }
+                mesh
+            }
+        }
+    }
+
+    /// Triangulate the fill of the shape, in its own space. The fill ends
+    /// where the stroke starts.
+    fn fill_mesh(&self, tolerance: f32) -> Mesh {
+        let stroke = self.style().2;
+        let inset = stroke.inset() + stroke.width / 2.;
+
+        match *self {
+            Shape::Line(..) | Shape::Polyline(..) | Shape::Transformed(..) => Mesh::new(),
+            Shape::Rectangle(r, ..) => {
+                let inner = Rect::new(r.x1 + inset, r.y1 + inset, r.x2 - inset, r.y2 - inset);
+
+                Mesh {
+                    vertices: vec![
+                        Point2::new(inner.x1, inner.y1),
+                        Point2::new(inner.x2, inner.y1),
+                        Point2::new(inner.x2, inner.y2),
+                        Point2::new(inner.x1, inner.y2),
+                    ],
+                    indices: vec![0, 1, 2, 0, 3, 2],
+                }
+            }
+            Shape::Circle(circle, ..) => {
+                // A fan around the center, which is the first vertex.
+                let sides = Self::circle_sides(circle, tolerance);
+                let mut rim = Self::circle_points(circle.position, circle.radius - inset, sides);
+                rim.pop();
+
+                let mut mesh = Mesh::with_capacity(rim.len() + 1, rim.len() * 3);
+                mesh.vertex(circle.position);
+                mesh.vertices.extend(rim);
+
+                for i in 0..sides {
+                    mesh.triangle(0, i + 1, (i + 1) % sides + 1);
+                }
+                mesh
+            }
+            Shape::Arc(arc, ..) => {
+                // The region between the arc and its chord.
+                let polygon = Polygon::new(Self::arc_points(arc, tolerance));
+
+                Mesh {
+                    vertices: polygon.vertices(),
+                    indices: polygon.triangulate(),
+                }
+            }
+            Shape::Path(ref path, ..) => {
+                let contours: Vec<Vec<Point2<f32>>> = path
+                    .flatten_with(tolerance)
+                    .into_iter()
+                    .map(|s| s.points)
+                    .collect();
+
+                Mesh::from_triangles(path::fill(&contours, path.fill_rule))
+            }
+            _ => {
+                let inner: Vec<Vec<Point2<f32>>> = self
+                    .contours(tolerance)
+                    .iter()
+                    .map(|c| polygon::offset(c, inset, stroke.miter_limit))
+                    .collect();
+
+                Mesh {
+                    indices: polygon::triangulate(&inner),
+                    vertices: inner.into_iter().flatten().collect(),
                 }
-                verts
             }
         }
     }
 
+    /// The closed contours of polygons, rounded rectangles, ellipses and
+    /// pies, as returned by [`Polygon::contours`].
+    fn contours(&self, tolerance: f32) -> Vec<Vec<Point2<f32>>> {
+        match *self {
+            Shape::Polygon(ref polygon, ..) => polygon.contours(),
+            Shape::RoundedRectangle(r, radii, ..) => {
+                Polygon::new(Self::rounded_rect_points(r, radii, tolerance)).contours()
+            }
+            Shape::Ellipse(ellipse, ..) => vec![Self::ellipse_points(ellipse, tolerance)],
+            Shape::Pie(arc, ..) => {
+                let mut contour = vec![arc.position];
+                contour.extend(Self::arc_points(arc, tolerance));
+
+                Polygon::new(contour).contours()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Triangulate the textured fill of the shape, if it has one. The fill is
+    /// tessellated like a solid fill, with texture coordinates given by the
+    /// fill transform. Strokes are drawn by [`Shape::tessellate`].
+    pub fn tessellate_textured(&self, tolerance: f32) -> Vec<TexturedVertex> {
+        if let Shape::Transformed(ref shape, m) = *self {
+            return shape
+                .tessellate_textured(tolerance / scale_factor(&m))
+                .into_iter()
+                .map(|v| v.transform(m))
+                .collect();
+        }
+        let (ZDepth(z), Rotation { angle, center }, _, fill) = self.style();
+        let (color, transform) = match fill {
+            Fill::Texture { color, transform } => (color, transform),
+            _ => return Vec::new(),
+        };
+        let rgba8 = color.into();
+
+        self.fill_mesh(tolerance)
+            .triangles()
+            .into_iter()
+            .map(|p| {
+                let v = vertex(p.x, p.y, z, angle, center, rgba8);
+                TexturedVertex::new(v, transform * p, rgba8)
+            })
+            .collect()
+    }
+
+    /// Stroke a polyline of uniform width, centered on its points.
+    fn stroke_mesh(stroke: &Stroke, tolerance: f32, points: &[Point2<f32>], closed: bool) -> Mesh {
+        stroke.polyline(points, &vec![stroke.width; points.len()], closed, tolerance)
+    }
+
+    /// The number of sides of a circle, derived from the tolerance for
+    /// [`Circle::AUTO`].
+    fn circle_sides(circle: Circle, tolerance: f32) -> u32 {
+        if circle.sides == Circle::AUTO {
+            path::arc_segments(circle.radius, 2. * f32::consts::PI, tolerance).max(3)
+        } else {
+            circle.sides
+        }
+    }
+
     fn circle_points(position: Point2<f32>, radius: f32, sides: u32) -> Vec<Point2<f32>> {
         let mut verts = Vec::with_capacity(sides as usize + 1);
 
@@ -358,6 +1082,302 @@ impl Shape {
         }
         verts
     }
+
+    /// Outline of a rounded rectangle, counter-clockwise from the `(x1, y1)`
+    /// corner. Radii are clamped to fit the rectangle.
+    fn rounded_rect_points(r: Rect<f32>, radii: [f32; 4], tolerance: f32) -> Vec<Point2<f32>> {
+        let (x1, x2) = (r.x1.min(r.x2), r.x1.max(r.x2));
+        let (y1, y2) = (r.y1.min(r.y2), r.y1.max(r.y2));
+        let max = f32::min(x2 - x1, y2 - y1) / 2.;
+        let corners = [
+            (Point2::new(x1, y1), Vector2::new(1., 1.), f32::consts::PI),
+            (
+                Point2::new(x2, y1),
+                Vector2::new(-1., 1.),
+                f32::consts::PI * 1.5,
+            ),
+            (Point2::new(x2, y2), Vector2::new(-1., -1.), 0.),
+            (
+                Point2::new(x1, y2),
+                Vector2::new(1., -1.),
+                f32::consts::PI * 0.5,
+            ),
+        ];
+        let mut points = Vec::new();
+
+        for ((corner, inward, start), radius) in corners.iter().zip(radii.iter()) {
+            let radius = radius.max(0.).min(max);
+
+            if radius == 0. {
+                points.push(*corner);
+                continue;
+            }
+            let c = *corner + *inward * radius;
+            let n = path::arc_segments(radius, f32::consts::FRAC_PI_2, tolerance);
+
+            for i in 0..=n {
+                let a = start + f32::consts::FRAC_PI_2 * i as f32 / n as f32;
+                points.push(Point2::new(c.x + radius * a.cos(), c.y + radius * a.sin()));
+            }
+        }
+        points
+    }
+
+    fn ellipse_points(ellipse: Ellipse, tolerance: f32) -> Vec<Point2<f32>> {
+        let Ellipse { position, radii } = ellipse;
+        let sweep = 2. * f32::consts::PI;
+        let n = path::arc_segments(radii.x.max(radii.y), sweep, tolerance).max(3);
+
+        (0..n)
+            .map(|i| {
+                let a = sweep * i as f32 / n as f32;
+                Point2::new(
+                    position.x + radii.x * a.cos(),
+                    position.y + radii.y * a.sin(),
+                )
+            })
+            .collect()
+    }
+
+    fn arc_points(arc: Arc, tolerance: f32) -> Vec<Point2<f32>> {
+        let Arc {
+            position,
+            radius,
+            start,
+            end,
+        } = arc;
+        let n = path::arc_segments(radius, end - start, tolerance);
+
+        (0..=n)
+            .map(|i| {
+                let a = start + (end - start) * i as f32 / n as f32;
+                Point2::new(position.x + radius * a.cos(), position.y + radius * a.sin())
+            })
+            .collect()
+    }
+}
+
+/// Shapes are transformed in place when the transform preserves their
+/// kind, for example a rotated rectangle remains a rectangle with an updated
+/// [`Rotation`]. Otherwise, the shape is wrapped in [`Shape::Transformed`],
+/// and the transform is applied to its vertices. When a shape is transformed
+/// in place, its stroke width is scaled by the transform's scale factor.
+impl Geometry for Shape {
+    fn transform(self, m: Matrix4<f32>) -> Self {
+        match self {
+            // Strokes are only scaled in place by a uniform scale factor.
+            shape @ (Shape::Line(..)
+            | Shape::Polygon(..)
+            | Shape::Path(..)
+            | Shape::Polyline(..))
+                if similarity(&m).is_none() =>
+            {
+                Shape::Transformed(Box::new(shape), m)
+            }
+            Shape::Line(line, z, rotation, mut stroke) => {
+                let line = Line::new(rotation.apply(line.p1), rotation.apply(line.p2));
+                stroke = stroke.scale(scale_factor(&m));
+
+                Shape::Line(line.transform(m), z, Rotation::ZERO, stroke)
+            }
+            Shape::Rectangle(r, z, Rotation { angle, center }, mut stroke, fill) => {
+                match similarity(&m) {
+                    Some((scale, phi)) => {
+                        // Scale the rectangle around its center of rotation, and move
+                        // it along with it.
+                        let c = m * center;
+                        let r = Rect::new(
+                            c.x + scale * (r.x1 - center.x),
+                            c.y + scale * (r.y1 - center.y),
+                            c.x + scale * (r.x2 - center.x),
+                            c.y + scale * (r.y2 - center.y),
+                        );
+                        let fill = fill.transform(local(center, c, scale));
+                        stroke = stroke.scale(scale);
+
+                        Shape::Rectangle(r, z, Rotation::new(angle - phi, c), stroke, fill)
+                    }
+                    None => Shape::Transformed(
+                        Box::new(Shape::Rectangle(
+                            r,
+                            z,
+                            Rotation { angle, center },
+                            stroke,
+                            fill,
+                        )),
+                        m,
+                    ),
+                }
+            }
+            Shape::RoundedRectangle(r, radii, z, rotation, stroke, fill) => match similarity(&m) {
+                Some((scale, phi)) => {
+                    let Rotation { angle, center } = rotation;
+                    let c = m * center;
+                    let r = Rect::new(
+                        c.x + scale * (r.x1 - center.x),
+                        c.y + scale * (r.y1 - center.y),
+                        c.x + scale * (r.x2 - center.x),
+                        c.y + scale * (r.y2 - center.y),
+                    );
+                    let radii = [
+                        radii[0] * scale,
+                        radii[1] * scale,
+                        radii[2] * scale,
+                        radii[3] * scale,
+                    ];
+
+                    Shape::RoundedRectangle(
+                        r,
+                        radii,
+                        z,
+                        Rotation::new(angle - phi, c),
+                        stroke.scale(scale),
+                        fill.transform(local(center, c, scale)),
+                    )
+                }
+                None => Shape::Transformed(
+                    Box::new(Shape::RoundedRectangle(r, radii, z, rotation, stroke, fill)),
+                    m,
+                ),
+            },
+            Shape::Ellipse(ellipse, z, rotation, stroke, fill) => match similarity(&m) {
+                Some((scale, phi)) => {
+                    let Rotation { angle, center } = rotation;
+                    let c = m * center;
+                    let ellipse = Ellipse {
+                        position: c + (ellipse.position - center) * scale,
+                        radii: ellipse.radii * scale,
+                    };
+
+                    Shape::Ellipse(
+                        ellipse,
+                        z,
+                        Rotation::new(angle - phi, c),
+                        stroke.scale(scale),
+                        fill.transform(local(center, c, scale)),
+                    )
+                }
+                None => Shape::Transformed(
+                    Box::new(Shape::Ellipse(ellipse, z, rotation, stroke, fill)),
+                    m,
+                ),
+            },
+            Shape::Arc(arc, z, rotation, stroke, fill) => match similarity(&m) {
+                Some((scale, phi)) => Shape::Arc(
+                    arc.rotate(rotation).similar(&m, scale, phi),
+                    z,
+                    Rotation::ZERO,
+                    stroke.scale(scale),
+                    fill.transform(m * rotation.matrix()),
+                ),
+                None => Shape::Transformed(Box::new(Shape::Arc(arc, z, rotation, stroke, fill)), m),
+            },
+            Shape::Pie(arc, z, rotation, stroke, fill) => match similarity(&m) {
+                Some((scale, phi)) => Shape::Pie(
+                    arc.rotate(rotation).similar(&m, scale, phi),
+                    z,
+                    Rotation::ZERO,
+                    stroke.scale(scale),
+                    fill.transform(m * rotation.matrix()),
+                ),
+                None => Shape::Transformed(Box::new(Shape::Pie(arc, z, rotation, stroke, fill)), m),
+            },
+            Shape::Circle(circle, z, rotation, stroke, fill) => match similarity(&m) {
+                Some((scale, phi)) => {
+                    // The circle keeps its rotation around its own center, so
+                    // that the corners of circles with few sides stay in place.
+                    let position = m * rotation.apply(circle.position);
+                    let fill = fill.transform(local(circle.position, position, scale));
+                    let circle = Circle {
+                        position,
+                        radius: circle.radius * scale,
+                        sides: circle.sides,
+                    };
+
+                    Shape::Circle(
+                        circle,
+                        z,
+                        Rotation::new(rotation.angle - phi, position),
+                        stroke.scale(scale),
+                        fill,
+                    )
+                }
+                None => Shape::Transformed(
+                    Box::new(Shape::Circle(circle, z, rotation, stroke, fill)),
+                    m,
+                ),
+            },
+            Shape::Polygon(polygon, z, rotation, mut stroke, fill) => {
+                let fill = fill.transform(m * rotation.matrix());
+                let polygon = Polygon {
+                    points: polygon
+                        .points
+                        .into_iter()
+                        .map(|p| rotation.apply(p))
+                        .collect(),
+                    holes: polygon
+                        .holes
+                        .into_iter()
+                        .map(|h| h.into_iter().map(|p| rotation.apply(p)).collect())
+                        .collect(),
+                };
+                stroke = stroke.scale(scale_factor(&m));
+
+                Shape::Polygon(polygon.transform(m), z, Rotation::ZERO, stroke, fill)
+            }
+            Shape::Path(path, z, rotation, mut stroke, fill) => {
+                let fill = fill.transform(m * rotation.matrix());
+                stroke = stroke.scale(scale_factor(&m));
+
+                Shape::Path(
+                    path.transform(m * rotation.matrix()),
+                    z,
+                    Rotation::ZERO,
+                    stroke,
+                    fill,
+                )
+            }
+            Shape::Polyline(points, z, rotation, stroke, widths, closed) => {
+                let scale = scale_factor(&m);
+
+                Shape::Polyline(
+                    points.into_iter().map(|p| m * rotation.apply(p)).collect(),
+                    z,
+                    Rotation::ZERO,
+                    stroke.scale(scale),
+                    widths.into_iter().map(|w| w * scale).collect(),
+                    closed,
+                )
+            }
+            Shape::Transformed(shape, t) => Shape::Transformed(shape, m * t),
+        }
+    }
+}
+
+/// The scale factor and rotation angle of the transform, if it is a
+/// similarity, ie. a combination of translation, rotation and uniform scale.
+fn similarity(m: &Matrix4<f32>) -> Option<(f32, f32)> {
+    let (a, b, c, d) = (m.x.x, m.x.y, m.y.x, m.y.y);
+    let epsilon = 1e-6 * f32::max(1., a.abs() + b.abs());
+
+    if (a - d).abs() <= epsilon && (b + c).abs() <= epsilon {
+        Some(((a * a + b * b).sqrt(), b.atan2(a)))
+    } else {
+        None
+    }
+}
+
+/// The transform which scales around `from` and moves it to `to`. This is
+/// how shapes transformed under a similarity map their local coordinates.
+fn local(from: Point2<f32>, to: Point2<f32>, scale: f32) -> Matrix4<f32> {
+    Matrix4::from_translation(Vector3::new(to.x, to.y, 0.))
+        * Matrix4::from_scale(scale)
+        * Matrix4::from_translation(Vector3::new(-from.x, -from.y, 0.))
+}
+
+/// The average scale factor of the transform in the plane.
+fn scale_factor(m: &Matrix4<f32>) -> f32 {
+    (m.x.x * m.y.y - m.x.y * m.y.x).abs().sqrt()
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -413,15 +1433,68 @@ impl std::ops::Add<Vector2<f32>> for Line {
 pub struct Circle {
     pub position: Point2<f32>,
     pub radius: f32,
+    /// Number of sides, or [`Circle::AUTO`] to derive it from the tolerance.
     pub sides: u32,
 }
 
+impl Circle {
+    /// Derive the number of sides from the radius and tessellation tolerance.
+    pub const AUTO: u32 = 0;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Ellipse {
+    pub position: Point2<f32>,
+    pub radii: Vector2<f32>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Arc {
+    pub position: Point2<f32>,
+    pub radius: f32,
+    /// Start angle, in radians, counter-clockwise from the positive x-axis.
+    pub start: f32,
+    /// End angle, in radians. The arc is drawn clockwise if smaller than `start`.
+    pub end: f32,
+}
+
+impl Arc {
+    /// Apply a rotation to the arc.
+    fn rotate(self, rotation: Rotation) -> Self {
+        Self {
+            position: rotation.apply(self.position),
+            start: self.start - rotation.angle,
+            end: self.end - rotation.angle,
+            ..self
+        }
+    }
+
+    /// Apply a similarity transform with the given scale and rotation.
+    fn similar(self, m: &Matrix4<f32>, scale: f32, phi: f32) -> Self {
+        Self {
+            position: *m * self.position,
+            radius: self.radius * scale,
+            start: self.start + phi,
+            end: self.end + phi,
+        }
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 // Batch
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Batch {
+    /// Maximum distance between a curve and its tessellation, in pixels.
+    pub tolerance: f32,
+    /// Number of pixels per unit, eg. the camera zoom. Curves are tessellated
+    /// finely enough to stay within the tolerance at this scale.
+    pub scale: f32,
+    /// Whether to anti-alias shape outlines, with a one pixel fringe fading
+    /// out to full transparency.
+    pub antialias: bool,
+
     items: Vec<Shape>,
 }
 
@@ -441,17 +1514,78 @@ impl Batch {
     }
 
     pub fn vertices(&self) -> Vec<Vertex> {
-        // TODO: This is a lower-bound estimate of how much space we need.
-        // We should get the actual numbers from the shapes.
-        let mut buf = Vec::with_capacity(6 * self.items.len());
+        let shapes: Vec<Vec<Vertex>> = self
+            .items
+            .iter()
+            .map(|shape| tessellate(shape, self.tolerance, self.scale, self.antialias))
+            .collect();
 
-        for shape in self.items.iter() {
-            let mut verts: Vec<Vertex> = shape.triangulate();
+        let mut buf = Vec::with_capacity(shapes.iter().map(Vec::len).sum());
+        for mut verts in shapes {
             buf.append(&mut verts);
         }
         buf
     }
 
+    /// The textured fills of the batch, to be drawn with a texture pipeline
+    /// after the [`Batch::vertices`]. See [`Fill::Texture`].
+    pub fn textured_vertices(&self) -> Vec<TexturedVertex> {
+        self.items
+            .iter()
+            .flat_map(|shape| shape.tessellate_textured(self.tolerance / self.scale))
+            .collect()
+    }
+
+    /// The batch as indexed geometry. See [`Shape::triangulate_indexed`].
+    /// Antialiased shapes are feathered as triangle lists, so their vertices
+    /// are not shared.
+    pub fn indexed(&self) -> (Vec<Vertex>, Vec<u32>) {
+        let mut mesh = Mesh::new();
+
+        for shape in self.items.iter() {
+            if self.antialias {
+                mesh.append(Mesh::from_triangles(tessellate(
+                    shape,
+                    self.tolerance,
+                    self.scale,
+                    true,
+                )));
+            } else {
+                mesh.append(shape.mesh(self.tolerance / self.scale));
+            }
+        }
+        (mesh.vertices, mesh.indices)
+    }
+
+    /// The index of the topmost shape containing the point, if any. Shapes
+    /// with a greater [`ZDepth`] are on top, and among shapes at the same
+    /// depth, the last one added is on top.
+    pub fn pick(&self, point: Point2<f32>) -> Option<usize> {
+        let mut top: Option<(usize, f32)> = None;
+
+        for (i, shape) in self.items.iter().enumerate() {
+            let ZDepth(z) = shape.depth();
+
+            let above = match top {
+                Some((_, t)) => z >= t,
+                None => true,
+            };
+            if above && shape.contains_within(point, self.tolerance / self.scale) {
+                top = Some((i, z));
+            }
+        }
+        top.map(|(i, _)| i)
+    }
+
+    /// The shapes in the batch, in the order they were added.
+    pub fn shapes(&self) -> &[Shape] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
@@ -460,3 +1594,657 @@ impl Batch {
         self.items.clear();
     }
 }
+
+/// Tessellate a shape as part of a batch drawn at the given scale.
+fn tessellate(shape: &Shape, tolerance: f32, scale: f32, antialias: bool) -> Vec<Vertex> {
+    let mut verts = shape.tessellate(tolerance / scale);
+
+    if antialias {
+        feather::feather(&mut verts, 1. / scale);
+    }
+    verts
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self {
+            tolerance: TOLERANCE,
+            scale: 1.,
+            antialias: false,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl Geometry for Batch {
+    fn transform(self, m: Matrix4<f32>) -> Self {
+        Self {
+            items: self.items.into_iter().map(|s| s.transform(m)).collect(),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The positions of the vertices of a shape, as a triangle list.
+    pub(super) fn positions(shape: &Shape) -> Vec<Point2<f32>> {
+        shape
+            .triangulate()
+            .into_iter()
+            .map(|v| v.transform(Matrix4::identity()))
+            .map(|v| Point2::new(v.position.x, v.position.y))
+            .collect()
+    }
+
+    /// The area covered by a triangle list, whatever the winding of its
+    /// triangles.
+    pub(super) fn area(triangles: &[Point2<f32>]) -> f32 {
+        triangles
+            .chunks(3)
+            .map(|t| polygon::cross(t[0], t[1], t[2]).abs() / 2.)
+            .sum()
+    }
+
+    fn assert_same(a: &Shape, b: &Shape) {
+        let (a, b) = (positions(a), positions(b));
+
+        assert_eq!(a.len(), b.len());
+        for (p, q) in a.iter().zip(b.iter()) {
+            assert!(
+                (p.x - q.x).abs() < 1e-3 && (p.y - q.y).abs() < 1e-3,
+                "{:?} != {:?}",
+                p,
+                q
+            );
+        }
+    }
+
+    #[test]
+    fn test_transform_in_place() {
+        let m = Matrix4::from_translation(Vector3::new(7., -3., 0.))
+            * Matrix4::from_angle_z(0.7)
+            * Matrix4::from_scale(2.);
+
+        let shapes = [
+            Shape::rect([1., 2.], [5., 4.])
+                .rotation(0.3, [3., 3.])
+                .stroke(1., Rgba::WHITE)
+                .fill(Fill::solid(Rgba::RED)),
+            Shape::line([0., 0.], [4., 1.]).rotation(0.5, [1., 1.]),
+            Shape::polygon(Polygon::new(vec![[0., 0.], [4., 0.], [2., 3.]]))
+                .rotation(0.2, [1., 1.])
+                .fill(Fill::solid(Rgba::RED)),
+        ];
+
+        for shape in shapes.iter() {
+            let transformed = shape.clone().transform(m);
+            let expected = Shape::Transformed(Box::new(shape.clone()), m);
+
+            assert!(!matches!(transformed, Shape::Transformed(..)));
+            assert_same(
+                &transformed.stroke(0., Rgba::WHITE),
+                &expected.stroke(0., Rgba::WHITE),
+            );
+        }
+
+        match Shape::circle([2., 2.], 3., 16).transform(m) {
+            Shape::Circle(circle, ..) => {
+                assert_eq!(circle.position, m * Point2::new(2., 2.));
+                assert!((circle.radius - 6.).abs() < 1e-4);
+            }
+            other => panic!("unexpected shape {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_transform_general() {
+        let shear = Matrix4::new(
+            1., 0., 0., 0., 0.5, 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+        );
+        let rect = Shape::rect([0., 0.], [2., 2.]).fill(Fill::solid(Rgba::RED));
+        let sheared = rect.transform(shear);
+
+        assert!(matches!(sheared, Shape::Transformed(..)));
+
+        let ps = positions(&sheared);
+        assert!(ps.contains(&Point2::new(1., 2.)));
+        assert!(ps.contains(&Point2::new(3., 2.)));
+    }
+
+    #[test]
+    fn test_transform_stroke() {
+        // Stretching a vertical line horizontally widens its stroke.
+        let m = Matrix4::from_nonuniform_scale(2., 1., 1.);
+        let width = |shape: &Shape| {
+            let xs = positions(shape).into_iter().map(|p| p.x);
+            xs.clone().fold(f32::MIN, f32::max) - xs.fold(f32::MAX, f32::min)
+        };
+        let square = vec![[0., 0.], [0., 4.], [4., 4.], [4., 0.]];
+        let shapes = [
+            Shape::line([0., 0.], [0., 4.]).stroke(2., Rgba::WHITE),
+            Shape::polyline(vec![[0., 0.], [0., 4.]]).stroke(2., Rgba::WHITE),
+            Shape::path(Path::new().move_to([0., 0.]).line_to([0., 4.])).stroke(2., Rgba::WHITE),
+            Shape::polygon(Polygon::new(square)).stroke(2., Rgba::WHITE),
+        ];
+
+        for shape in shapes.iter() {
+            let transformed = shape.clone().transform(m);
+
+            assert!(matches!(transformed, Shape::Transformed(..)));
+            assert!((width(&transformed) - 2. * width(shape)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_curved_shapes() {
+        use std::f32::consts::PI;
+
+        let filled = |shape: Shape| area(&positions(&shape.fill(Fill::solid(Rgba::RED))));
+        let close = |a: f32, b: f32| (a - b).abs() / b < 0.05;
+
+        let rounded = Shape::rounded_rect([0., 0.], [10., 6.], [2., 2., 2., 2.]);
+        assert!(close(
+            filled(rounded.clone().stroke(0., Rgba::WHITE)),
+            60. - (4. - PI) * 4.
+        ));
+        assert!(close(
+            filled(rounded.stroke(1., Rgba::WHITE)),
+            60. - (4. - PI) * 4.
+        ));
+
+        // Radii are clamped to half the smallest side.
+        let pill =
+            Shape::rounded_rect([0., 0.], [10., 4.], [0., 0., 9., 9.]).stroke(0., Rgba::WHITE);
+        assert!(close(filled(pill), 40. - (4. - PI) * 4. / 2.));
+
+        let ellipse = Shape::ellipse([0., 0.], 10., 5.).stroke(0., Rgba::WHITE);
+        assert!(close(filled(ellipse.clone()), PI * 50.));
+
+        // Ellipses can be rotated.
+        let rotated = positions(
+            &ellipse
+                .rotation(PI / 2., [0., 0.])
+                .fill(Fill::solid(Rgba::RED)),
+        );
+        let width = rotated.iter().map(|p| p.x.abs()).fold(0., f32::max);
+        assert!(width > 4.5 && width <= 5. + 1e-3);
+
+        let pie = Shape::pie([0., 0.], 10., 0., PI / 2.).stroke(0., Rgba::WHITE);
+        assert!(close(filled(pie), PI * 25.));
+
+        let arc = Shape::arc([0., 0.], 10., 0., PI).stroke(2., Rgba::WHITE);
+        assert!(close(area(&positions(&arc)), PI * 10. * 2.));
+
+        // Larger curves get more segments.
+        let small = Shape::ellipse_points(
+            Ellipse {
+                position: Point2::new(0., 0.),
+                radii: Vector2::new(1., 1.),
+            },
+            TOLERANCE,
+        );
+        let large = Shape::ellipse_points(
+            Ellipse {
+                position: Point2::new(0., 0.),
+                radii: Vector2::new(100., 100.),
+            },
+            TOLERANCE,
+        );
+        assert!(large.len() > small.len() * 4);
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let shapes = || {
+            vec![
+                Shape::circle([0., 0.], 10., Circle::AUTO).fill(Fill::solid(Rgba::RED)),
+                Shape::ellipse([0., 0.], 10., 5.),
+                Shape::pie([0., 0.], 10., 0., 1.),
+                Shape::line([0., 0.], [10., 0.])
+                    .stroke(4., Rgba::WHITE)
+                    .cap(LineCap::Round),
+                Shape::path(Path::new().move_to([0., 0.]).quad_to([5., 10.], [10., 0.])),
+            ]
+        };
+        let count = |scale: f32| {
+            shapes()
+                .into_iter()
+                .map(|shape| {
+                    let mut batch = Batch::singleton(shape);
+                    batch.scale = scale;
+                    batch.vertices().len()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Zooming in yields finer tessellation for every curved shape.
+        let (near, far) = (count(8.), count(1.));
+        for (n, f) in near.iter().zip(far.iter()) {
+            assert!(n > f, "{:?} <= {:?}", near, far);
+        }
+
+        // An explicit path tolerance takes precedence.
+        let path = || Path::new().move_to([0., 0.]).quad_to([5., 10.], [10., 0.]);
+        let mut batch = Batch::singleton(Shape::path(path().tolerance(1.)));
+        batch.scale = 8.;
+        let mut coarse = Batch::singleton(Shape::path(path().tolerance(1.)));
+        coarse.scale = 1.;
+
+        assert_eq!(batch.vertices().len(), coarse.vertices().len());
+
+        // Fixed sides are left as-is.
+        let mut fixed =
+            Batch::singleton(Shape::circle([0., 0.], 10., 8).fill(Fill::solid(Rgba::RED)));
+        let n = fixed.vertices().len();
+        fixed.scale = 8.;
+        assert_eq!(fixed.vertices().len(), n);
+    }
+
+    #[test]
+    fn test_antialias() {
+        let rect = Shape::rect([0., 0.], [4., 2.])
+            .stroke(0., Rgba::WHITE)
+            .fill(Fill::solid(Rgba::RED));
+        let mut batch = Batch::singleton(rect);
+        let solid = batch.vertices();
+
+        batch.antialias = true;
+        let feathered = batch.vertices();
+
+        // The shape itself is untouched.
+        for (a, b) in solid.iter().zip(feathered.iter()) {
+            assert_eq!(a.position, b.position);
+        }
+        assert!(solid.iter().all(|v| v.color.a == 255));
+
+        // Each of the four outer edges gets a quad, but not the inner diagonal.
+        let fringe = &feathered[solid.len()..];
+        assert_eq!(fringe.len(), 4 * 6);
+
+        for v in fringe {
+            let (x, y) = (v.position.x, v.position.y);
+            let outside = !(0. ..=4.).contains(&x) || !(0. ..=2.).contains(&y);
+
+            if outside {
+                // Outer fringe vertices are fully transparent, one pixel out.
+                assert_eq!(v.color.a, 0);
+                assert!((x + 1.).abs() < 1e-4 || (x - 5.).abs() < 1e-4);
+                assert!((y + 1.).abs() < 1e-4 || (y - 3.).abs() < 1e-4);
+            } else {
+                assert_eq!(v.color.a, 255);
+            }
+        }
+        assert_eq!(fringe.iter().filter(|v| v.color.a == 0).count(), 4 * 3);
+
+        // At a higher zoom, the fringe stays one pixel wide.
+        batch.scale = 4.;
+        let max = batch
+            .vertices()
+            .iter()
+            .map(|v| v.position.x)
+            .fold(f32::MIN, f32::max);
+        assert!((max - 4.25).abs() < 1e-4);
+
+        // A filled circle only gets a fringe around its circumference.
+        let mut batch = Batch::singleton(
+            Shape::circle([0., 0.], 8., 16)
+                .fill(Fill::solid(Rgba::RED))
+                .stroke(0., Rgba::WHITE),
+        );
+        let n = batch.vertices().len();
+        batch.antialias = true;
+        assert_eq!(batch.vertices().len() - n, 16 * 6);
+    }
+
+    #[test]
+    fn test_texture() {
+        let rect = Rect::new(0., 0., 4., 2.);
+        let shape = Shape::rect([0., 0.], [4., 2.])
+            .stroke(1., Rgba::RED)
+            .alignment(StrokeAlignment::Outside)
+            .fill(Fill::texture(rect));
+
+        // The stroke is drawn as usual, and the fill separately.
+        assert!(shape
+            .triangulate()
+            .iter()
+            .all(|v| v.color == Rgba::RED.into()));
+
+        let uv = |shape: &Shape, p: Point2<f32>| {
+            shape
+                .tessellate_textured(TOLERANCE)
+                .into_iter()
+                .map(|v| v.transform(Matrix4::identity()))
+                .find(|v| (v.position.x - p.x).abs() < 1e-4 && (v.position.y - p.y).abs() < 1e-4)
+                .map(|v| (v.uv.x, v.uv.y))
+        };
+        assert_eq!(shape.tessellate_textured(TOLERANCE).len(), 6);
+        assert_eq!(uv(&shape, Point2::new(0., 0.)), Some((0., 1.)));
+        assert_eq!(uv(&shape, Point2::new(4., 2.)), Some((1., 0.)));
+
+        // Texture coordinates move along with the shape, whether it is
+        // transformed in place or not.
+        let moved = shape
+            .clone()
+            .transform(Matrix4::from_translation(Vector3::new(8., 0., 0.)));
+        assert_eq!(uv(&moved, Point2::new(8., 0.)), Some((0., 1.)));
+
+        let sheared = shape.clone().transform(Matrix4::new(
+            1., 0., 0., 0., 1., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+        ));
+        assert!(matches!(sheared, Shape::Transformed(..)));
+        assert_eq!(uv(&sheared, Point2::new(6., 2.)), Some((1., 0.)));
+
+        let batch = Batch::singleton(shape);
+        assert_eq!(batch.textured_vertices().len(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero width and height")]
+    fn test_texture_empty() {
+        Fill::texture(Rect::new(1., 0., 1., 2.));
+    }
+
+    #[test]
+    fn test_indexed() {
+        let rect = Shape::rect([0., 0.], [4., 2.])
+            .stroke(0., Rgba::TRANSPARENT)
+            .fill(Fill::solid(Rgba::RED));
+        let (verts, indices) = rect.triangulate_indexed();
+
+        assert_eq!(verts.len(), 4);
+        assert_eq!(indices.len(), 6);
+        assert_eq!(verts.capacity(), verts.len());
+        assert_eq!(indices.capacity(), indices.len());
+
+        // A fan around the center, and a stroke whose segments share corners.
+        let circle = Shape::circle([8., 8.], 4., 32)
+            .stroke(1., Rgba::WHITE)
+            .join(LineJoin::Bevel)
+            .fill(Fill::solid(Rgba::RED));
+        let (verts, indices) = circle.triangulate_indexed();
+
+        assert_eq!(verts.len(), 32 * 4 + 33);
+        assert_eq!(indices.len(), 32 * 9 + 32 * 3);
+        assert_eq!(verts.capacity(), verts.len());
+        assert_eq!(indices.capacity(), indices.len());
+
+        // Indexing preserves the triangles, and shares vertices between them.
+        let mut batch = Batch::new();
+        batch.add(rect);
+        batch.add(Shape::circle([8., 8.], 4., 32).stroke(1., Rgba::WHITE));
+        batch.add(
+            Shape::polyline(vec![[0., 0.], [4., 4.], [8., 0.]])
+                .stroke(2., Rgba::BLUE)
+                .join(LineJoin::Round),
+        );
+        let triangles = batch.vertices();
+        let (verts, indices) = batch.indexed();
+
+        assert_eq!(triangles.capacity(), triangles.len());
+        assert_eq!(indices.len(), triangles.len());
+        assert!(verts.len() < triangles.len() / 2);
+
+        for (v, i) in triangles.iter().zip(indices.iter()) {
+            let w = verts[*i as usize];
+            assert_eq!(v.position, w.position);
+            assert_eq!(v.color, w.color);
+        }
+    }
+
+    #[test]
+    fn test_pick() {
+        let mut batch = Batch::new();
+        batch.add(
+            Shape::rect([0., 0.], [10., 10.])
+                .fill(Fill::solid(Rgba::RED))
+                .zdepth(0.5),
+        );
+        batch.add(Shape::rect([5., 5.], [15., 15.]).fill(Fill::solid(Rgba::GREEN)));
+        batch.add(Shape::rect([8., 8.], [12., 12.]).fill(Fill::solid(Rgba::BLUE)));
+
+        assert_eq!(batch.pick(Point2::new(2., 2.)), Some(0));
+        // The first rectangle is in front, despite being added first.
+        assert_eq!(batch.pick(Point2::new(9., 9.)), Some(0));
+        // At the same depth, the last shape added is on top.
+        assert_eq!(batch.pick(Point2::new(11., 11.)), Some(2));
+        assert_eq!(batch.pick(Point2::new(14., 14.)), Some(1));
+        assert_eq!(batch.pick(Point2::new(20., 20.)), None);
+
+        // Curves are flattened with the batch tolerance.
+        let mut batch = Batch::singleton(
+            Shape::path(Path::new().move_to([0., 0.]).quad_to([5., 10.], [10., 0.]))
+                .fill(Fill::solid(Rgba::RED)),
+        );
+        assert_eq!(batch.pick(Point2::new(5., 4.9)), Some(0));
+        batch.tolerance = 10.;
+        assert_eq!(batch.pick(Point2::new(5., 4.9)), None);
+    }
+
+    #[test]
+    fn test_polygons() {
+        let area = |shape: &Shape| match shape {
+            Shape::Polygon(p, ..) => p.area(),
+            other => panic!("unexpected shape {:?}", other),
+        };
+
+        let triangle = Shape::triangle([0., 0.], [4., 0.], [0., 2.]);
+        assert!((area(&triangle) - 4.).abs() < 1e-4);
+
+        // A hexagon of radius 2 has six equilateral triangles with sides of 2.
+        let hexagon = Shape::regular_polygon([1., 1.], 2., 6);
+        assert!((area(&hexagon) - 6. * 3f32.sqrt()).abs() < 1e-4);
+        assert_eq!(hexagon.bounds().x2, 3.);
+        assert!(hexagon.contains(Point2::new(2.9, 1.)));
+
+        // A star is made of triangles between the center and adjacent corners.
+        let star = Shape::star([0., 0.], 5, 4., 2.);
+        let expected = 10. * 0.5 * 4. * 2. * (f32::consts::PI / 5.).sin();
+        assert!((area(&star) - expected).abs() < 1e-3);
+        assert!(star
+            .fill(Fill::solid(Rgba::RED))
+            .contains(Point2::new(3.9, 0.)));
+
+        let arrow = Shape::arrow([0., 0.], [10., 0.], 2., 6., 4.).fill(Fill::solid(Rgba::RED));
+        assert!((area(&arrow) - (6. * 2. + 4. * 6. / 2.)).abs() < 1e-4);
+        assert_eq!(arrow.bounds(), Rect::new(0., -3., 10., 3.));
+        assert!(arrow.contains(Point2::new(9.9, 0.)));
+        assert!(arrow.contains(Point2::new(7., 2.)));
+        assert!(!arrow.contains(Point2::new(5., 1.5)));
+
+        // Short arrows are all head.
+        let short = Shape::arrow([0., 0.], [0., 2.], 2., 6., 4.);
+        assert!((area(&short) - 6.).abs() < 1e-4);
+        assert!(!Shape::arrow([1., 1.], [1., 1.], 2., 6., 4.)
+            .triangulate()
+            .iter()
+            .any(|v| v.position.x.is_nan()));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let a = f32::consts::FRAC_PI_2;
+        let center = Point2::new(0., 0.);
+
+        // Rotating a circle around another point moves it.
+        let circle = Shape::circle([4., 0.], 1., 8)
+            .rotation(a, center)
+            .fill(Fill::solid(Rgba::RED));
+        let bounds = circle.bounds();
+        assert!((bounds.x1 + 1.).abs() < 1e-4 && (bounds.y1 + 5.).abs() < 1e-4);
+        assert!(circle.contains(Point2::new(0., -4.)));
+        assert!(!circle.contains(Point2::new(4., 0.)));
+
+        // Rotating an arc or pie is the same as shifting its angles.
+        for (rotated, shifted) in [
+            (
+                Shape::arc([2., 0.], 4., 0., 1.).rotation(a, center),
+                Shape::arc([0., -2.], 4., -a, 1. - a),
+            ),
+            (
+                Shape::pie([2., 0.], 4., 0., 1.).rotation(a, center),
+                Shape::pie([0., -2.], 4., -a, 1. - a),
+            ),
+        ] {
+            let rotated = rotated.stroke(1., Rgba::WHITE);
+            let shifted = shifted.stroke(1., Rgba::WHITE);
+            let (b1, b2) = (rotated.bounds(), shifted.bounds());
+
+            assert!((b1.x1 - b2.x1).abs() < 1e-3 && (b1.y1 - b2.y1).abs() < 1e-3);
+            assert!((b1.x2 - b2.x2).abs() < 1e-3 && (b1.y2 - b2.y2).abs() < 1e-3);
+        }
+
+        // Rotations are kept by similarity transforms.
+        let m = Matrix4::from_translation(Vector3::new(1., 2., 0.)) * Matrix4::from_scale(2.);
+        match circle.clone().transform(m) {
+            Shape::Circle(c, _, rotation, ..) => {
+                assert_eq!(rotation.center, c.position);
+                assert!((c.position - m * Point2::new(0., -4.)).magnitude() < 1e-4);
+            }
+            other => panic!("unexpected shape {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stroke_alignment() {
+        let shapes = [
+            Shape::rect([0., 0.], [10., 10.]),
+            Shape::rounded_rect([0., 0.], [10., 10.], [3.; 4]),
+            Shape::circle([5., 5.], 5., 64),
+            Shape::ellipse([5., 5.], 5., 5.),
+            Shape::polygon(Polygon::new(vec![
+                [0., 0.],
+                [10., 0.],
+                [10., 10.],
+                [0., 10.],
+            ])),
+        ];
+        let alignments = [
+            (StrokeAlignment::Inside, 0.),
+            (StrokeAlignment::Center, 1.),
+            (StrokeAlignment::Outside, 2.),
+        ];
+        let extent = |verts: &[Vertex], color: Rgba| {
+            let color: Rgba8 = color.into();
+            let xs = verts
+                .iter()
+                .filter(|v| v.color == color)
+                .map(|v| v.position.x);
+            let ys = verts
+                .iter()
+                .filter(|v| v.color == color)
+                .map(|v| v.position.y);
+
+            Rect::new(
+                xs.clone().fold(f32::MAX, f32::min),
+                ys.clone().fold(f32::MAX, f32::min),
+                xs.fold(f32::MIN, f32::max),
+                ys.fold(f32::MIN, f32::max),
+            )
+        };
+        let assert_near = |a: Rect<f32>, b: Rect<f32>, shape: &Shape| {
+            for (x, y) in [(a.x1, b.x1), (a.y1, b.y1), (a.x2, b.x2), (a.y2, b.y2)] {
+                assert!((x - y).abs() < 0.05, "{:?} != {:?} for {:?}", a, b, shape);
+            }
+        };
+
+        for shape in shapes.iter() {
+            for (alignment, outside) in alignments.iter() {
+                let shape = shape
+                    .clone()
+                    .stroke(2., Rgba::RED)
+                    .alignment(*alignment)
+                    .fill(Fill::solid(Rgba::BLUE));
+                let verts = shape.tessellate(0.01);
+                let (e, i) = (*outside, 2. - outside);
+
+                // The stroke extends `e` outside the outline, and the fill ends
+                // where the stroke starts, `i` inside the outline.
+                let outer = Rect::new(-e, -e, 10. + e, 10. + e);
+                let inner = Rect::new(i, i, 10. - i, 10. - i);
+
+                assert_near(extent(&verts, Rgba::RED), outer, &shape);
+                assert_near(extent(&verts, Rgba::BLUE), inner, &shape);
+                assert_near(shape.bounds(), outer, &shape);
+
+                assert!(shape.contains(Point2::new(5., 10. + e - 0.1)));
+                assert!(!shape.contains(Point2::new(5., 10. + e + 0.1)));
+            }
+        }
+
+        // The stroke of a quarter pie ends `e` outside of its square corner.
+        for (alignment, e) in alignments.iter() {
+            let pie = Shape::pie([0., 0.], 10., 0., f32::consts::FRAC_PI_2)
+                .stroke(2., Rgba::RED)
+                .alignment(*alignment);
+            let outer = extent(&pie.triangulate(), Rgba::RED);
+
+            assert!((outer.x1 + e).abs() < 0.05 && (outer.y1 + e).abs() < 0.05);
+            assert_near(pie.bounds(), outer, &pie);
+        }
+
+        // The outline of an aligned stroke follows its miter limit.
+        let sharp = Shape::polygon(Polygon::new(vec![[0., 0.], [10., 0.], [0., 2.]]))
+            .stroke(0.5, Rgba::RED)
+            .join(LineJoin::Bevel)
+            .alignment(StrokeAlignment::Outside);
+        let reach = |shape: Shape| extent(&shape.triangulate(), Rgba::RED).x2;
+
+        assert!(reach(sharp.clone().miter_limit(1.)) < reach(sharp.miter_limit(100.)) - 1.);
+    }
+
+    #[test]
+    fn test_stroke_alignment_closed_path() {
+        let square = |path: Path, x: f32, size: f32| {
+            path.move_to([x, x])
+                .line_to([x, x + size])
+                .line_to([x + size, x + size])
+                .line_to([x + size, x])
+                .close()
+        };
+        let points = vec![[0., 0.], [10., 0.], [10., 10.], [0., 10.]];
+        let reversed: Vec<_> = points.iter().rev().cloned().collect();
+
+        for (alignment, e) in [
+            (StrokeAlignment::Inside, 0.),
+            (StrokeAlignment::Center, 1.),
+            (StrokeAlignment::Outside, 2.),
+        ]
+        .iter()
+        {
+            let shapes = [
+                Shape::path(square(Path::new(), 0., 10.)),
+                Shape::polyline(points.clone()).closed(true),
+                Shape::polyline(reversed.clone()).closed(true),
+            ];
+            for shape in shapes.iter() {
+                let shape = shape.clone().stroke(2., Rgba::RED).alignment(*alignment);
+                let bounds = shape.bounds();
+
+                for (a, b) in [(bounds.x1, -e), (bounds.y1, -e), (bounds.x2, 10. + e)].iter() {
+                    assert!((a - b).abs() < 1e-3, "{:?} for {:?}", bounds, shape);
+                }
+            }
+
+            // The inside of a hole is outside of its contour, so an inner
+            // stroke runs around the hole.
+            let hole = Shape::path(
+                square(square(Path::new(), 0., 10.), 3., 4.).fill_rule(FillRule::EvenOdd),
+            )
+            .stroke(2., Rgba::RED)
+            .alignment(*alignment);
+            assert_eq!(hole.contains(Point2::new(5., 2.5)), *e < 2.);
+            assert_eq!(hole.contains(Point2::new(5., 3.5)), *e > 0.);
+        }
+
+        // Open polylines, lines and arcs stay centered.
+        let open = Shape::polyline(points).stroke(2., Rgba::RED);
+        assert_same(&open.clone().alignment(StrokeAlignment::Outside), &open);
+
+        let line = Shape::line([0., 0.], [10., 0.]).stroke(2., Rgba::RED);
+        assert_same(&line.clone().alignment(StrokeAlignment::Inside), &line);
+    }
+}
@@ -0,0 +1,789 @@
+use crate::kit::Geometry;
+use crate::math::*;
+
+use super::polygon;
+
+use std::f32;
+
+///////////////////////////////////////////////////////////////////////////
+// Path
+///////////////////////////////////////////////////////////////////////////
+
+/// A path command. Coordinates are absolute.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    MoveTo(Point2<f32>),
+    LineTo(Point2<f32>),
+    /// Quadratic bézier curve, with a control point and an end point.
+    QuadTo(Point2<f32>, Point2<f32>),
+    /// Cubic bézier curve, with two control points and an end point.
+    CubicTo(Point2<f32>, Point2<f32>, Point2<f32>),
+    /// Elliptical arc, as specified by SVG.
+    ArcTo {
+        radii: Vector2<f32>,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: Point2<f32>,
+    },
+    Close,
+}
+
+/// How the inside of a path is determined when it overlaps itself.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// A point is inside if the path winds around it a non-zero number of times.
+    #[default]
+    NonZero,
+    /// A point is inside if a ray from it crosses the path an odd number of times.
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Whether a point around which the path winds the given number of times
+    /// is inside.
+    pub(crate) fn contains(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// A flattened sub-path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Subpath {
+    pub points: Vec<Point2<f32>>,
+    pub closed: bool,
+}
+
+/// A vector path made of lines and curves, built with a chain of commands.
+///
+/// ```
+/// use rgx::kit::shape2d::{FillRule, Path};
+///
+/// let path = Path::new()
+///     .move_to([0., 0.])
+///     .line_to([8., 0.])
+///     .quad_to([8., 8.], [0., 8.])
+///     .close()
+///     .fill_rule(FillRule::EvenOdd);
+///
+/// assert_eq!(path.flatten().len(), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Path {
+    /// Fill rule used when the path is filled.
+    pub fill_rule: FillRule,
+    /// Maximum distance between a curve and its flattened approximation. If
+    /// unset, the tolerance of the batch the path is drawn with is used.
+    pub tolerance: Option<f32>,
+
+    commands: Vec<Command>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to<P: Into<Point2<f32>>>(mut self, p: P) -> Self {
+        self.commands.push(Command::MoveTo(p.into()));
+        self
+    }
+
+    pub fn line_to<P: Into<Point2<f32>>>(mut self, p: P) -> Self {
+        self.commands.push(Command::LineTo(p.into()));
+        self
+    }
+
+    pub fn quad_to<P: Into<Point2<f32>>>(mut self, ctrl: P, to: P) -> Self {
+        self.commands.push(Command::QuadTo(ctrl.into(), to.into()));
+        self
+    }
+
+    pub fn cubic_to<P: Into<Point2<f32>>>(mut self, ctrl1: P, ctrl2: P, to: P) -> Self {
+        self.commands
+            .push(Command::CubicTo(ctrl1.into(), ctrl2.into(), to.into()));
+        self
+    }
+
+    /// Elliptical arc to the given point, with the same parameters as the
+    /// SVG `A` command. The rotation is in radians.
+    pub fn arc_to<P: Into<Point2<f32>>, V: Into<Vector2<f32>>>(
+        mut self,
+        radii: V,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: P,
+    ) -> Self {
+        self.commands.push(Command::ArcTo {
+            radii: radii.into(),
+            rotation,
+            large_arc,
+            sweep,
+            to: to.into(),
+        });
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.commands.push(Command::Close);
+        self
+    }
+
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill_rule = rule;
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Flatten the path into sub-paths made of straight lines, such that no
+    /// point on a curve is further than the path tolerance from its
+    /// approximation.
+    pub fn flatten(&self) -> Vec<Subpath> {
+        self.flatten_with(super::TOLERANCE)
+    }
+
+    /// Flatten the path, using the given tolerance if the path doesn't
+    /// specify one.
+    pub(crate) fn flatten_with(&self, tolerance: f32) -> Vec<Subpath> {
+        let tolerance = self.tolerance.unwrap_or(tolerance);
+        let mut subpaths = Vec::new();
+        let mut current = Subpath {
+            points: Vec::new(),
+            closed: false,
+        };
+        let mut start = Point2::new(0., 0.);
+        let mut pen = start;
+
+        for cmd in self.commands.iter() {
+            if current.closed || (current.points.is_empty() && !matches!(cmd, Command::MoveTo(_))) {
+                // Drawing after a close starts a new sub-path at the start point.
+                if current.points.len() > 1 {
+                    subpaths.push(current);
+                }
+                current = Subpath {
+                    points: vec![start],
+                    closed: false,
+                };
+                pen = start;
+            }
+
+            match *cmd {
+                Command::MoveTo(p) => {
+                    if current.points.len() > 1 {
+                        subpaths.push(current);
+                    }
+                    current = Subpath {
+                        points: vec![p],
+                        closed: false,
+                    };
+                    start = p;
+                    pen = p;
+                }
+                Command::LineTo(p) => {
+                    current.points.push(p);
+                    pen = p;
+                }
+                Command::QuadTo(c, p) => {
+                    quad(pen, c, p, tolerance, &mut current.points);
+                    pen = p;
+                }
+                Command::CubicTo(c1, c2, p) => {
+                    cubic(pen, c1, c2, p, tolerance, &mut current.points);
+                    pen = p;
+                }
+                Command::ArcTo {
+                    radii,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    arc(
+                        pen,
+                        radii,
+                        rotation,
+                        large_arc,
+                        sweep,
+                        to,
+                        tolerance,
+                        &mut current.points,
+                    );
+                    pen = to;
+                }
+                Command::Close => {
+                    if current.points.len() > 1 && current.points.last() == Some(&start) {
+                        current.points.pop();
+                    }
+                    current.closed = true;
+                    pen = start;
+                }
+            }
+        }
+        if current.points.len() > 1 {
+            subpaths.push(current);
+        }
+        subpaths
+    }
+
+    /// Flatten the path, and offset its closed subpaths by `distance` towards
+    /// the inside of the path, as given by its fill rule, or away from it if
+    /// the distance is negative. Miters are limited to `limit` times the
+    /// distance.
+    pub(crate) fn inset_with(&self, distance: f32, limit: f32, tolerance: f32) -> Vec<Subpath> {
+        let mut subpaths = self.flatten_with(tolerance);
+
+        if distance == 0. {
+            return subpaths;
+        }
+        let contours: Vec<Vec<Point2<f32>>> = subpaths.iter().map(|s| s.points.clone()).collect();
+
+        for subpath in subpaths.iter_mut().filter(|s| s.closed) {
+            let d = if inside_left(&contours, self.fill_rule, &subpath.points) {
+                distance
+            } else {
+                -distance
+            };
+            subpath.points = polygon::offset(&subpath.points, d, limit);
+        }
+        subpaths
+    }
+}
+
+/// Whether the inside of a set of contours, as given by the fill rule, lies to
+/// the left of the edges of one of them. The side is probed next to the middle
+/// of its longest edge.
+fn inside_left(contours: &[Vec<Point2<f32>>], rule: FillRule, points: &[Point2<f32>]) -> bool {
+    let n = points.len();
+    let longest = (0..n)
+        .map(|i| (points[i], points[(i + 1) % n]))
+        .max_by(|&(a, b), &(c, d)| {
+            (b - a)
+                .magnitude()
+                .partial_cmp(&(d - c).magnitude())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+    match longest {
+        Some((a, b)) if a != b => {
+            let mid = a + (b - a) * 0.5;
+            let probe = mid + polygon::normal(b - a) * ((b - a).magnitude() * 1e-3);
+
+            rule.contains(polygon::winding(contours, probe))
+        }
+        _ => polygon::signed_area(points) > 0.,
+    }
+}
+
+/// Points and control points are transformed directly. Arcs are kept as
+/// arcs under similarity transforms, and flattened otherwise.
+impl Geometry for Path {
+    fn transform(self, m: Matrix4<f32>) -> Self {
+        let similarity = super::similarity(&m);
+        let mut commands = Vec::with_capacity(self.commands.len());
+        let mut start = Point2::new(0., 0.);
+        let mut pen = start;
+
+        for cmd in self.commands.iter() {
+            commands.push(match *cmd {
+                Command::MoveTo(p) => {
+                    start = p;
+                    pen = p;
+                    Command::MoveTo(m * p)
+                }
+                Command::LineTo(p) => {
+                    pen = p;
+                    Command::LineTo(m * p)
+                }
+                Command::QuadTo(c, p) => {
+                    pen = p;
+                    Command::QuadTo(m * c, m * p)
+                }
+                Command::CubicTo(c1, c2, p) => {
+                    pen = p;
+                    Command::CubicTo(m * c1, m * c2, m * p)
+                }
+                Command::ArcTo {
+                    radii,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    let from = pen;
+                    pen = to;
+
+                    match similarity {
+                        Some((scale, angle)) => Command::ArcTo {
+                            radii: radii * scale,
+                            rotation: rotation + angle,
+                            large_arc,
+                            sweep,
+                            to: m * to,
+                        },
+                        None => {
+                            let mut points = Vec::new();
+                            arc(
+                                from,
+                                radii,
+                                rotation,
+                                large_arc,
+                                sweep,
+                                to,
+                                self.tolerance.unwrap_or(super::TOLERANCE),
+                                &mut points,
+                            );
+                            commands.extend(points.into_iter().map(|p| Command::LineTo(m * p)));
+                            continue;
+                        }
+                    }
+                }
+                Command::Close => {
+                    pen = start;
+                    Command::Close
+                }
+            });
+        }
+
+        Self {
+            fill_rule: self.fill_rule,
+            tolerance: self.tolerance,
+            commands,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Flattening
+///////////////////////////////////////////////////////////////////////////
+
+/// Number of line segments needed to approximate a circular arc of the given
+/// radius and angle, such that the error is no larger than the tolerance, up
+/// to 1024 segments.
+pub(crate) fn arc_segments(radius: f32, angle: f32, tolerance: f32) -> u32 {
+    if radius <= tolerance || tolerance <= 0. {
+        return if tolerance <= 0. { 64 } else { 1 };
+    }
+    let step = 2. * (1. - tolerance / radius).acos();
+
+    // The tolerance is too small to be told apart from the radius.
+    if step <= 0. {
+        return 1024;
+    }
+    ((angle.abs() / step).ceil() as u32).clamp(1, 1024)
+}
+
+/// Number of segments needed to flatten a bézier curve of the given degree,
+/// using Wang's formula, where `dd` is the largest second difference of its
+/// control points.
+fn curve_segments(degree: f32, dd: f32, tolerance: f32) -> u32 {
+    if tolerance <= 0. {
+        return 64;
+    }
+    ((degree * (degree - 1.) / 8. * dd / tolerance).sqrt().ceil() as u32).clamp(1, 1024)
+}
+
+fn quad(
+    p0: Point2<f32>,
+    p1: Point2<f32>,
+    p2: Point2<f32>,
+    tolerance: f32,
+    out: &mut Vec<Point2<f32>>,
+) {
+    let dd = (p0 - p1 - (p1 - p2)).magnitude();
+    let n = curve_segments(2., dd, tolerance);
+
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let u = 1. - t;
+
+        out.push(Point2::new(
+            u * u * p0.x + 2. * u * t * p1.x + t * t * p2.x,
+            u * u * p0.y + 2. * u * t * p1.y + t * t * p2.y,
+        ));
+    }
+}
+
+fn cubic(
+    p0: Point2<f32>,
+    p1: Point2<f32>,
+    p2: Point2<f32>,
+    p3: Point2<f32>,
+    tolerance: f32,
+    out: &mut Vec<Point2<f32>>,
+) {
+    let dd = f32::max(
+        (p0 - p1 - (p1 - p2)).magnitude(),
+        (p1 - p2 - (p2 - p3)).magnitude(),
+    );
+    let n = curve_segments(3., dd, tolerance);
+
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let u = 1. - t;
+        let (a, b, c, d) = (u * u * u, 3. * u * u * t, 3. * u * t * t, t * t * t);
+
+        out.push(Point2::new(
+            a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+            a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+        ));
+    }
+}
+
+/// Flatten an SVG elliptical arc, by converting it to its center
+/// parameterization. See the SVG specification, appendix F.6.
+#[allow(clippy::too_many_arguments)]
+fn arc(
+    from: Point2<f32>,
+    radii: Vector2<f32>,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Point2<f32>,
+    tolerance: f32,
+    out: &mut Vec<Point2<f32>>,
+) {
+    if from == to {
+        return;
+    }
+    let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+    if rx == 0. || ry == 0. {
+        out.push(to);
+        return;
+    }
+    let (sin, cos) = rotation.sin_cos();
+    let (hx, hy) = ((from.x - to.x) / 2., (from.y - to.y) / 2.);
+    let x1 = cos * hx + sin * hy;
+    let y1 = -sin * hx + cos * hy;
+
+    // Scale up the radii if they are too small to reach the end point.
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large_arc == sweep { -1. } else { 1. };
+    let coef = sign * (num / den).max(0.).sqrt();
+
+    let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+    let cx = cos * cx1 - sin * cy1 + (from.x + to.x) / 2.;
+    let cy = sin * cx1 + cos * cy1 + (from.y + to.y) / 2.;
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let (ux, uy) = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let (vx, vy) = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+
+    let theta = angle(1., 0., ux, uy);
+    let mut delta = angle(ux, uy, vx, vy);
+
+    if !sweep && delta > 0. {
+        delta -= 2. * f32::consts::PI;
+    } else if sweep && delta < 0. {
+        delta += 2. * f32::consts::PI;
+    }
+
+    let n = arc_segments(rx.max(ry), delta, tolerance);
+    for i in 1..n {
+        let t = theta + delta * i as f32 / n as f32;
+        let (s, c) = t.sin_cos();
+
+        out.push(Point2::new(
+            cx + rx * c * cos - ry * s * sin,
+            cy + rx * c * sin + ry * s * cos,
+        ));
+    }
+    out.push(to);
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Filling
+///////////////////////////////////////////////////////////////////////////
+
+struct Edge {
+    top: Point2<f32>,
+    bottom: Point2<f32>,
+    winding: i32,
+}
+
+impl Edge {
+    fn x(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + t * (self.bottom.x - self.top.x)
+    }
+}
+
+/// Tessellate closed contours into triangles, using the given fill rule.
+///
+/// The plane is cut into horizontal bands at every vertex and edge
+/// intersection, such that no two edges cross within a band. The spans
+/// inside each band are then emitted as trapezoids.
+pub(crate) fn fill(contours: &[Vec<Point2<f32>>], rule: FillRule) -> Vec<Point2<f32>> {
+    let mut edges = Vec::new();
+
+    for contour in contours {
+        let n = contour.len();
+        for i in 0..n {
+            let (a, b) = (contour[i], contour[(i + 1) % n]);
+
+            if a.y < b.y {
+                edges.push(Edge {
+                    top: a,
+                    bottom: b,
+                    winding: 1,
+                });
+            } else if a.y > b.y {
+                edges.push(Edge {
+                    top: b,
+                    bottom: a,
+                    winding: -1,
+                });
+            }
+        }
+    }
+
+    let mut ys: Vec<f32> = edges
+        .iter()
+        .flat_map(|e| vec![e.top.y, e.bottom.y])
+        .collect();
+    for (i, a) in edges.iter().enumerate() {
+        for b in edges[i + 1..].iter() {
+            if let Some(y) = intersection(a, b) {
+                ys.push(y);
+            }
+        }
+    }
+    ys.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    ys.dedup();
+
+    let mut triangles = Vec::new();
+    let mut active: Vec<(f32, f32, f32, i32)> = Vec::new();
+
+    for band in ys.windows(2) {
+        let (y0, y1) = (band[0], band[1]);
+        let ym = (y0 + y1) / 2.;
+
+        active.clear();
+        active.extend(
+            edges
+                .iter()
+                .filter(|e| e.top.y <= y0 && e.bottom.y >= y1)
+                .map(|e| (e.x(ym), e.x(y0), e.x(y1), e.winding)),
+        );
+        active.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut winding = 0;
+        for pair in active.windows(2) {
+            let (l, r) = (pair[0], pair[1]);
+            winding += l.3;
+
+            if !rule.contains(winding) {
+                continue;
+            }
+            let (l0, l1) = (Point2::new(l.1, y0), Point2::new(l.2, y1));
+            let (r0, r1) = (Point2::new(r.1, y0), Point2::new(r.2, y1));
+
+            if r0.x > l0.x {
+                triangles.extend_from_slice(&[l0, r0, r1]);
+            }
+            if r1.x > l1.x {
+                triangles.extend_from_slice(&[l0, r1, l1]);
+            }
+        }
+    }
+    triangles
+}
+
+/// The y-coordinate at which two edges cross, if they do so strictly
+/// between their end points.
+fn intersection(a: &Edge, b: &Edge) -> Option<f32> {
+    let (p, r) = (a.top, a.bottom - a.top);
+    let (q, s) = (b.top, b.bottom - b.top);
+    let denom = r.x * s.y - r.y * s.x;
+
+    if denom == 0. {
+        return None;
+    }
+    let qp = q - p;
+    let t = (qp.x * s.y - qp.y * s.x) / denom;
+    let u = (qp.x * r.y - qp.y * r.x) / denom;
+
+    if t > 0. && t < 1. && u > 0. && u < 1. {
+        Some(p.y + t * r.y)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{area, positions};
+    use super::super::{Fill, Shape};
+    use super::*;
+    use crate::color::Rgba;
+
+    fn fill_area(path: &Path) -> f32 {
+        let contours: Vec<_> = path.flatten().into_iter().map(|s| s.points).collect();
+        let triangles = fill(&contours, path.fill_rule);
+
+        assert!(
+            triangles
+                .chunks(3)
+                .all(|t| super::super::polygon::cross(t[0], t[1], t[2]) >= 0.),
+            "triangles must be counter-clockwise"
+        );
+        area(&triangles)
+    }
+
+    fn square(path: Path, x: f32, y: f32, size: f32, ccw: bool) -> Path {
+        let path = path.move_to([x, y]);
+        let path = if ccw {
+            path.line_to([x + size, y])
+                .line_to([x + size, y + size])
+                .line_to([x, y + size])
+        } else {
+            path.line_to([x, y + size])
+                .line_to([x + size, y + size])
+                .line_to([x + size, y])
+        };
+        path.close()
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        // Two overlapping squares wound the same way.
+        let overlap = square(square(Path::new(), 0., 0., 4., true), 2., 2., 4., true);
+
+        assert!((fill_area(&overlap) - 28.).abs() < 1e-3);
+        assert!((fill_area(&overlap.clone().fill_rule(FillRule::EvenOdd)) - 24.).abs() < 1e-3);
+
+        // A square with a hole wound the opposite way.
+        let hole = square(square(Path::new(), 0., 0., 6., true), 2., 2., 2., false);
+
+        assert!((fill_area(&hole) - 32.).abs() < 1e-3);
+        assert!((fill_area(&hole.fill_rule(FillRule::EvenOdd)) - 32.).abs() < 1e-3);
+
+        // A self-intersecting bow-tie.
+        let bowtie = Path::new()
+            .move_to([0., 0.])
+            .line_to([4., 4.])
+            .line_to([4., 0.])
+            .line_to([0., 4.])
+            .close();
+        assert!((fill_area(&bowtie) - 8.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_arc_segments() {
+        let pi = f32::consts::PI;
+
+        assert_eq!(arc_segments(1e30, pi, 0.1), 1024);
+        assert_eq!(arc_segments(2e4, 2. * pi, 1e-4), 1024);
+        assert_eq!(arc_segments(1e3, 0., 0.1), 1);
+
+        // Huge curves tessellate into a bounded number of vertices.
+        let ellipse = Shape::ellipse([0., 0.], 2e4, 2e4)
+            .stroke(0., Rgba::TRANSPARENT)
+            .fill(Fill::solid(Rgba::RED));
+        assert!(ellipse.tessellate(1e-4).len() <= 1024 * 3);
+    }
+
+    #[test]
+    fn test_curves() {
+        let r = 10.;
+        let circle = |tolerance| {
+            Path::new()
+                .move_to([r, 0.])
+                .arc_to([r, r], 0., false, true, [-r, 0.])
+                .arc_to([r, r], 0., false, true, [r, 0.])
+                .close()
+                .tolerance(tolerance)
+        };
+        let coarse = circle(1.);
+        let fine = circle(0.01);
+
+        let expected = f32::consts::PI * r * r;
+        assert!(fine.flatten()[0].points.len() > coarse.flatten()[0].points.len());
+        assert!((fill_area(&fine) - expected).abs() / expected < 0.01);
+        assert!(fill_area(&coarse) < fill_area(&fine));
+
+        // All flattened points lie on the circle.
+        for p in fine.flatten()[0].points.iter() {
+            assert!(((p.x * p.x + p.y * p.y).sqrt() - r).abs() < 1e-3);
+        }
+
+        let curve = Path::new()
+            .move_to([0., 0.])
+            .quad_to([5., 10.], [10., 0.])
+            .cubic_to([10., -5.], [0., -5.], [0., 0.]);
+        let subpaths = curve.flatten();
+
+        assert_eq!(subpaths.len(), 1);
+        assert!(!subpaths[0].closed);
+        assert_eq!(subpaths[0].points.first(), Some(&Point2::new(0., 0.)));
+        assert_eq!(subpaths[0].points.last(), Some(&Point2::new(0., 0.)));
+    }
+
+    #[test]
+    fn test_subpaths() {
+        let path = Path::new()
+            .move_to([0., 0.])
+            .line_to([1., 0.])
+            .line_to([1., 1.])
+            .close()
+            .line_to([0., 1.])
+            .move_to([5., 5.])
+            .line_to([6., 5.]);
+        let subpaths = path.flatten();
+
+        assert_eq!(subpaths.len(), 3);
+        assert!(subpaths[0].closed);
+        assert_eq!(subpaths[0].points.len(), 3);
+        assert_eq!(
+            subpaths[1].points,
+            vec![Point2::new(0., 0.), Point2::new(0., 1.)]
+        );
+        assert!(!subpaths[2].closed);
+    }
+
+    #[test]
+    fn test_path_shape() {
+        let m = Matrix4::from_translation(Vector3::new(3., 1., 0.))
+            * Matrix4::from_nonuniform_scale(2., 3., 1.);
+        let path = Shape::path(
+            Path::new()
+                .move_to([0., 0.])
+                .line_to([4., 0.])
+                .arc_to([2., 2.], 0., false, true, [0., 0.])
+                .close()
+                .tolerance(0.01),
+        )
+        .rotation(0.4, [2., 2.])
+        .stroke(0., Rgba::WHITE)
+        .fill(Fill::solid(Rgba::RED));
+        let expected = f32::consts::PI * 2. * 2. / 2.;
+
+        assert!((area(&positions(&path)) - expected).abs() / expected < 0.01);
+
+        // Arcs are flattened under non-uniform scaling.
+        let transformed = path.transform(m);
+        assert!((area(&positions(&transformed)) - expected * 6.).abs() / expected < 0.1);
+    }
+}
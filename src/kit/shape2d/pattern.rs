@@ -0,0 +1,228 @@
+use crate::color::Rgba;
+use crate::math::*;
+
+use super::gradient::clip;
+use super::polygon::cross;
+
+/// A repeating two-color pattern, made of unit cells in pattern space. See
+/// [`super::Fill::Pattern`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// Cells alternating between the two colors in both directions.
+    Checkerboard,
+    /// Stripes one cell wide, alternating between the two colors along the
+    /// x-axis.
+    Stripes,
+    /// Lines of the first color on a background of the second color, one per
+    /// cell along the x-axis, with the given width as a fraction of a cell.
+    Hatch(f32),
+}
+
+impl Pattern {
+    /// The index of the color at the given point in pattern space.
+    fn color(self, p: Point2<f32>) -> usize {
+        match self {
+            Self::Checkerboard => (p.x.floor() + p.y.floor()).rem_euclid(2.) as usize,
+            Self::Stripes => p.x.floor().rem_euclid(2.) as usize,
+            Self::Hatch(width) => (p.x.rem_euclid(1.) >= width) as usize,
+        }
+    }
+
+    /// The boundaries between colors along the x-axis of pattern space,
+    /// strictly between `lo` and `hi`.
+    fn bounds(self, lo: f32, hi: f32) -> Vec<f32> {
+        let cells = (lo.floor() as i64)..=(hi.ceil() as i64);
+
+        match self {
+            Self::Checkerboard | Self::Stripes => cells
+                .map(|k| k as f32)
+                .filter(|t| *t > lo && *t < hi)
+                .collect(),
+            Self::Hatch(width) => cells
+                .flat_map(|k| vec![k as f32, k as f32 + width])
+                .filter(|t| *t > lo && *t < hi)
+                .collect(),
+        }
+    }
+}
+
+/// Color a list of triangles with a pattern, given the transform from shape
+/// coordinates to pattern space.
+///
+/// Triangles are split along the boundaries between cells, such that each
+/// piece has a single color. This yields a number of pieces proportional to
+/// the number of cells covered, so cells should not be much smaller than a
+/// pixel.
+pub(super) fn shade(
+    triangles: &[Point2<f32>],
+    pattern: Pattern,
+    colors: [Rgba; 2],
+    transform: Matrix4<f32>,
+) -> Vec<(Point2<f32>, Rgba)> {
+    let mut out = Vec::with_capacity(triangles.len());
+
+    for tri in triangles.chunks(3).filter(|t| t.len() == 3) {
+        let uvs: Vec<Point2<f32>> = tri.iter().map(|p| transform * *p).collect();
+
+        for piece in split(tri, &uvs, |uv| uv.x, |lo, hi| pattern.bounds(lo, hi)) {
+            let pieces = if pattern == Pattern::Checkerboard {
+                let uvs: Vec<Point2<f32>> = piece.iter().map(|p| transform * *p).collect();
+                split(&piece, &uvs, |uv| uv.y, |lo, hi| pattern.bounds(lo, hi))
+            } else {
+                vec![piece]
+            };
+
+            for piece in pieces {
+                if piece.len() < 3 {
+                    continue;
+                }
+                let n = piece.len() as f32;
+                let centroid = piece.iter().fold(Point2::new(0., 0.), |c, p| {
+                    Point2::new(c.x + p.x / n, c.y + p.y / n)
+                });
+                let color = colors[pattern.color(transform * centroid)];
+
+                for i in 1..piece.len() - 1 {
+                    // Clipping at cell corners leaves degenerate triangles.
+                    if cross(piece[0], piece[i], piece[i + 1]).abs() <= f32::EPSILON {
+                        continue;
+                    }
+                    for p in [piece[0], piece[i], piece[i + 1]].iter() {
+                        out.push((*p, color));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Split a convex polygon into bands between the given boundaries, along one
+/// axis of pattern space.
+fn split(
+    poly: &[Point2<f32>],
+    uvs: &[Point2<f32>],
+    axis: impl Fn(Point2<f32>) -> f32,
+    bounds: impl Fn(f32, f32) -> Vec<f32>,
+) -> Vec<Vec<Point2<f32>>> {
+    let poly: Vec<(Point2<f32>, f32)> = poly
+        .iter()
+        .zip(uvs)
+        .map(|(p, uv)| (*p, axis(*uv)))
+        .collect();
+    let (lo, hi) = poly
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v.1), hi.max(v.1))
+        });
+
+    let mut edges = vec![f32::NEG_INFINITY];
+    edges.extend(bounds(lo, hi));
+    edges.push(f32::INFINITY);
+
+    edges
+        .windows(2)
+        .map(|band| {
+            clip(&clip(&poly, band[0], 1.), band[1], -1.)
+                .into_iter()
+                .map(|(p, _)| p)
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::area;
+    use super::super::{Fill, Shape, Vertex};
+    use super::*;
+    use crate::kit::Geometry;
+
+    use std::f32;
+
+    /// The area covered by the triangles of a given color.
+    fn colored_area(verts: &[Vertex], color: Rgba) -> f32 {
+        let triangles: Vec<_> = verts
+            .chunks(3)
+            .filter(|t| t[0].color == color.into())
+            .flatten()
+            .map(|v| Point2::new(v.position.x, v.position.y))
+            .collect();
+
+        area(&triangles)
+    }
+
+    #[test]
+    fn test_patterns() {
+        let rect = Shape::rect([0., 0.], [8., 8.]).stroke(0., Rgba::TRANSPARENT);
+
+        // Each piece of a checkerboard has the color of its cell.
+        let verts = rect
+            .clone()
+            .fill(Fill::checkerboard(2., Rgba::RED, Rgba::BLUE))
+            .triangulate();
+
+        assert!((colored_area(&verts, Rgba::RED) - 32.).abs() < 1e-3);
+        assert!((colored_area(&verts, Rgba::BLUE) - 32.).abs() < 1e-3);
+        for t in verts.chunks(3) {
+            let c = t.iter().fold(Vector2::new(0., 0.), |c, v| {
+                c + Vector2::new(v.position.x, v.position.y) * (1. / 3.)
+            });
+            let cell = (c.x / 2.).floor() + (c.y / 2.).floor();
+            let expected = if cell % 2. == 0. {
+                Rgba::RED
+            } else {
+                Rgba::BLUE
+            };
+
+            assert_eq!(t[0].color, expected.into());
+            assert!(t.iter().all(|v| v.color == t[0].color));
+        }
+
+        // Horizontal stripes start with the first color at the origin.
+        let verts = rect
+            .clone()
+            .fill(Fill::stripes(1., 0., Rgba::RED, Rgba::BLUE))
+            .triangulate();
+
+        for t in verts.chunks(3) {
+            let y = t.iter().map(|v| v.position.y).sum::<f32>() / 3.;
+            let expected = if y.floor() % 2. == 0. {
+                Rgba::RED
+            } else {
+                Rgba::BLUE
+            };
+
+            assert_eq!(t[0].color, expected.into());
+        }
+
+        // Hatching covers a fraction of the shape given by the line width.
+        let verts = rect
+            .clone()
+            .fill(Fill::hatch(
+                4.,
+                1.,
+                f32::consts::FRAC_PI_2,
+                Rgba::RED,
+                Rgba::BLUE,
+            ))
+            .triangulate();
+
+        assert!((colored_area(&verts, Rgba::RED) - 16.).abs() < 1e-2);
+        assert!((colored_area(&verts, Rgba::BLUE) - 48.).abs() < 1e-2);
+
+        // Patterns follow the shape under any transform.
+        let m = Matrix4::from_translation(Vector3::new(3., 1., 0.))
+            * Matrix4::from_nonuniform_scale(2., 1., 1.);
+        let stretched = rect
+            .clone()
+            .fill(Fill::checkerboard(2., Rgba::RED, Rgba::BLUE))
+            .transform(m)
+            .triangulate();
+
+        assert!((colored_area(&stretched, Rgba::RED) - 64.).abs() < 1e-2);
+        for v in stretched.iter().filter(|v| v.color == Rgba::RED.into()) {
+            assert!(v.position.x >= 3. - 1e-3 && v.position.x <= 19. + 1e-3);
+        }
+    }
+}
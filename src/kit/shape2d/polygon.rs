@@ -0,0 +1,545 @@
+use crate::kit::Geometry;
+use crate::math::*;
+
+use super::{path, LineJoin, TOLERANCE};
+
+use std::f32;
+
+///////////////////////////////////////////////////////////////////////////
+// Polygon
+///////////////////////////////////////////////////////////////////////////
+
+/// A simple polygon, convex or concave, with optional holes.
+///
+/// Contours may be given in any winding order, and are not closed: the last
+/// point connects back to the first.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Polygon {
+    pub points: Vec<Point2<f32>>,
+    pub holes: Vec<Vec<Point2<f32>>>,
+}
+
+impl Polygon {
+    pub fn new<P: Into<Point2<f32>>, I: IntoIterator<Item = P>>(points: I) -> Self {
+        Self {
+            points: points.into_iter().map(|p| p.into()).collect(),
+            holes: Vec::new(),
+        }
+    }
+
+    /// Add a hole to the polygon. The hole must lie inside the outer contour
+    /// and must not overlap other holes.
+    pub fn hole<P: Into<Point2<f32>>, I: IntoIterator<Item = P>>(mut self, points: I) -> Self {
+        self.holes
+            .push(points.into_iter().map(|p| p.into()).collect());
+        self
+    }
+
+    /// Area of the polygon, excluding its holes.
+    pub fn area(&self) -> f32 {
+        signed_area(&self.points).abs()
+            - self.holes.iter().map(|h| signed_area(h).abs()).sum::<f32>()
+    }
+
+    /// All contours, with the outer contour wound counter-clockwise and holes
+    /// wound clockwise, such that the filled region is always to the left.
+    pub fn contours(&self) -> Vec<Vec<Point2<f32>>> {
+        let mut contours = Vec::with_capacity(self.holes.len() + 1);
+
+        contours.push(wind(&self.points, true));
+        for hole in self.holes.iter() {
+            contours.push(wind(hole, false));
+        }
+        contours
+    }
+
+    /// All points of the polygon, outer contour first, followed by the holes,
+    /// in the order used by the indices returned from [`Polygon::triangulate`].
+    pub fn vertices(&self) -> Vec<Point2<f32>> {
+        self.contours().into_iter().flatten().collect()
+    }
+
+    /// Triangulate the polygon using ear clipping. Returns triangle indices
+    /// into [`Polygon::vertices`], in counter-clockwise order.
+    pub fn triangulate(&self) -> Vec<u32> {
+        triangulate(&self.contours())
+    }
+}
+
+impl Geometry for Polygon {
+    fn transform(self, m: Matrix4<f32>) -> Self {
+        Self {
+            points: self.points.into_iter().map(|p| m * p).collect(),
+            holes: self
+                .holes
+                .into_iter()
+                .map(|h| h.into_iter().map(|p| m * p).collect())
+                .collect(),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Helpers
+///////////////////////////////////////////////////////////////////////////
+
+/// The signed area of a contour, positive if it is wound counter-clockwise.
+pub fn signed_area(points: &[Point2<f32>]) -> f32 {
+    let n = points.len();
+    let mut area = 0.;
+
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        area += a.x * b.y - b.x * a.y;
+    }
+    area / 2.
+}
+
+/// Return the contour wound counter-clockwise if `ccw` is true, or clockwise
+/// otherwise.
+fn wind(points: &[Point2<f32>], ccw: bool) -> Vec<Point2<f32>> {
+    let mut points = points.to_vec();
+
+    if (signed_area(&points) > 0.) != ccw {
+        points.reverse();
+    }
+    points
+}
+
+/// Twice the signed area of the triangle `abc`, positive if it is wound
+/// counter-clockwise.
+#[inline]
+pub(crate) fn cross(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Whether `p` lies inside or on the edges of the counter-clockwise triangle `abc`.
+fn in_triangle(p: Point2<f32>, a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> bool {
+    cross(a, b, p) >= 0. && cross(b, c, p) >= 0. && cross(c, a, p) >= 0.
+}
+
+/// Offset a closed contour to the left of its edges by `distance`, using
+/// mitered corners. Miters are limited to `limit` times the distance.
+pub(crate) fn offset(points: &[Point2<f32>], distance: f32, limit: f32) -> Vec<Point2<f32>> {
+    offset_by(points, |_| distance, limit)
+}
+
+/// Like [`offset`], with the distance at each point given by its index.
+pub(crate) fn offset_by<F: Fn(usize) -> f32>(
+    points: &[Point2<f32>],
+    distance: F,
+    limit: f32,
+) -> Vec<Point2<f32>> {
+    let n = points.len();
+    let mut result = Vec::with_capacity(n);
+
+    for i in 0..n {
+        let prev = points[(i + n - 1) % n];
+        let curr = points[i];
+        let next = points[(i + 1) % n];
+
+        result.push(curr + miter(prev, curr, next, distance(i), limit));
+    }
+    result
+}
+
+/// Offset a closed contour to the left of its edges by `distance`, adding a
+/// join of the given kind at corners which open a gap between the offset
+/// edges. Miters longer than `limit` times the distance are beveled. Unlike
+/// [`offset`], other corners are left to overlap, so the result loops back on
+/// itself at concave corners.
+pub(crate) fn offset_joined(
+    points: &[Point2<f32>],
+    distance: f32,
+    join: LineJoin,
+    limit: f32,
+) -> Vec<Point2<f32>> {
+    let n = points.len();
+    let mut out = Vec::with_capacity(n * 2);
+
+    for i in 0..n {
+        let (prev, curr, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        let (n0, n1) = (
+            normal(curr - prev) * distance,
+            normal(next - curr) * distance,
+        );
+
+        if cross(prev, curr, next) * distance >= 0. {
+            out.push(curr + n0);
+            out.push(curr);
+            out.push(curr + n1);
+            continue;
+        }
+        match join {
+            LineJoin::Miter => {
+                let m = miter(prev, curr, next, distance, f32::INFINITY);
+
+                if m.magnitude() <= distance.abs() * limit {
+                    out.push(curr + m);
+                } else {
+                    // Past the miter limit, the corner is beveled.
+                    out.push(curr + n0);
+                    out.push(curr + n1);
+                }
+            }
+            LineJoin::Bevel => {
+                out.push(curr + n0);
+                out.push(curr + n1);
+            }
+            LineJoin::Round => {
+                let r = distance.abs();
+                let a0 = n0.y.atan2(n0.x);
+                let mut sweep = n1.y.atan2(n1.x) - a0;
+
+                while sweep > f32::consts::PI {
+                    sweep -= 2. * f32::consts::PI;
+                }
+                while sweep < -f32::consts::PI {
+                    sweep += 2. * f32::consts::PI;
+                }
+                let segments = path::arc_segments(r, sweep, TOLERANCE);
+
+                for s in 0..=segments {
+                    let a = a0 + sweep * s as f32 / segments as f32;
+                    out.push(curr + Vector2::new(a.cos(), a.sin()) * r);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The offset of the corner at `curr` to the left of the edges `prev -> curr`
+/// and `curr -> next`.
+pub(crate) fn miter(
+    prev: Point2<f32>,
+    curr: Point2<f32>,
+    next: Point2<f32>,
+    distance: f32,
+    limit: f32,
+) -> Vector2<f32> {
+    let n0 = normal(curr - prev);
+    let n1 = normal(next - curr);
+    let sum = n0 + n1;
+
+    if sum.magnitude() < 1e-6 {
+        // The contour doubles back on itself.
+        return n1 * distance;
+    }
+    let m = sum.normalize();
+    let scale = (1. / Vector2::dot(m, n1)).min(limit);
+
+    m * (distance * scale)
+}
+
+/// The winding number of a set of contours around a point. Contours are
+/// implicitly closed.
+pub(crate) fn winding(contours: &[Vec<Point2<f32>>], p: Point2<f32>) -> i32 {
+    let mut w = 0;
+
+    for c in contours {
+        for i in 0..c.len() {
+            let (a, b) = (c[i], c[(i + 1) % c.len()]);
+
+            if a.y <= p.y {
+                if b.y > p.y && cross(a, b, p) > 0. {
+                    w += 1;
+                }
+            } else if b.y <= p.y && cross(a, b, p) < 0. {
+                w -= 1;
+            }
+        }
+    }
+    w
+}
+
+/// The distance between a point and the segment `ab`.
+pub(crate) fn distance_to_segment(p: Point2<f32>, a: Point2<f32>, b: Point2<f32>) -> f32 {
+    let ab = b - a;
+    let len = Vector2::dot(ab, ab);
+    let t = if len == 0. {
+        0.
+    } else {
+        (Vector2::dot(p - a, ab) / len).clamp(0., 1.)
+    };
+    (p - (a + ab * t)).magnitude()
+}
+
+/// The left-hand unit normal of a vector, or zero for a zero vector.
+#[inline]
+pub(crate) fn normal(v: Vector2<f32>) -> Vector2<f32> {
+    let len = v.magnitude();
+
+    if len == 0. {
+        Vector2::new(0., 0.)
+    } else {
+        Vector2::new(-v.y / len, v.x / len)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Ear clipping
+///////////////////////////////////////////////////////////////////////////
+
+/// Triangulate a polygon given its contours, the first being the outer
+/// contour wound counter-clockwise, and the rest being holes wound
+/// clockwise. Returns triangle indices into the concatenated contours.
+pub(crate) fn triangulate(contours: &[Vec<Point2<f32>>]) -> Vec<u32> {
+    let points: Vec<Point2<f32>> = contours.iter().flatten().cloned().collect();
+
+    let mut offsets = Vec::with_capacity(contours.len());
+    let mut start = 0;
+    for c in contours {
+        offsets.push(start);
+        start += c.len();
+    }
+
+    let mut ring: Vec<usize> = match contours.first() {
+        Some(outer) if outer.len() >= 3 => (0..outer.len()).collect(),
+        _ => return Vec::new(),
+    };
+
+    // Merge holes into the outer ring, starting with the right-most hole.
+    let mut holes: Vec<usize> = (1..contours.len())
+        .filter(|&i| contours[i].len() >= 3)
+        .collect();
+    holes.sort_by(|&a, &b| {
+        let max = |i: usize| {
+            contours[i]
+                .iter()
+                .map(|p| p.x)
+                .fold(f32::NEG_INFINITY, f32::max)
+        };
+        max(b)
+            .partial_cmp(&max(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for h in holes {
+        bridge(&mut ring, &points, offsets[h], contours[h].len());
+    }
+
+    clip(ring, &points)
+}
+
+/// Connect a hole to the ring through a pair of coincident edges.
+fn bridge(ring: &mut Vec<usize>, points: &[Point2<f32>], offset: usize, len: usize) {
+    // The right-most vertex of the hole.
+    let m = (offset..offset + len)
+        .max_by(|&a, &b| {
+            points[a]
+                .x
+                .partial_cmp(&points[b].x)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(offset);
+    let mp = points[m];
+
+    // Cast a ray from `m` to the right, and find the closest ring edge it hits.
+    let mut closest = f32::INFINITY;
+    let mut candidate = None;
+
+    for i in 0..ring.len() {
+        let (a, b) = (points[ring[i]], points[ring[(i + 1) % ring.len()]]);
+
+        if (a.y > mp.y) == (b.y > mp.y) && a.y != mp.y && b.y != mp.y {
+            continue;
+        }
+        if a.y == b.y {
+            // Horizontal edge on the ray: take its nearest endpoint.
+            for (j, p) in [(i, a), ((i + 1) % ring.len(), b)].iter() {
+                if p.y == mp.y && p.x >= mp.x && p.x - mp.x < closest {
+                    closest = p.x - mp.x;
+                    candidate = Some(*j);
+                }
+            }
+            continue;
+        }
+        let x = a.x + (mp.y - a.y) * (b.x - a.x) / (b.y - a.y);
+        if x >= mp.x && x - mp.x < closest {
+            closest = x - mp.x;
+            // Prefer the endpoint furthest along the ray.
+            candidate = Some(if a.x > b.x { i } else { (i + 1) % ring.len() });
+        }
+    }
+    let mut p = match candidate {
+        Some(p) => p,
+        None => return,
+    };
+
+    // If any ring vertex lies inside the triangle formed by `m`, the ray hit
+    // and `p`, it may block the bridge. Pick the one closest in angle.
+    let hit = Point2::new(mp.x + closest, mp.y);
+    let pp = points[ring[p]];
+    let (a, b, c) = if pp.y < mp.y {
+        (mp, pp, hit)
+    } else {
+        (mp, hit, pp)
+    };
+    let mut best = f32::INFINITY;
+
+    for (i, &r) in ring.iter().enumerate() {
+        let rp = points[r];
+
+        if rp == pp || rp.x < mp.x || !in_triangle(rp, a, b, c) {
+            continue;
+        }
+        let slope = (rp.y - mp.y).abs() / (rp.x - mp.x).max(f32::EPSILON);
+        if slope < best {
+            best = slope;
+            p = i;
+        }
+    }
+
+    // Splice the hole into the ring: ... p, m, hole..., m, p, ...
+    let mut splice = Vec::with_capacity(len + 2);
+    for k in 0..=len {
+        splice.push(offset + (m - offset + k) % len);
+    }
+    splice.push(ring[p]);
+
+    ring.splice(p + 1..p + 1, splice);
+}
+
+/// Clip ears off the ring until a single triangle remains.
+fn clip(mut ring: Vec<usize>, points: &[Point2<f32>]) -> Vec<u32> {
+    let mut indices = Vec::with_capacity(ring.len().saturating_sub(2) * 3);
+    let mut i = 0;
+    let mut stalled = 0;
+
+    while ring.len() > 3 {
+        let n = ring.len();
+        let (ia, ib, ic) = (ring[(i + n - 1) % n], ring[i % n], ring[(i + 1) % n]);
+        let (a, b, c) = (points[ia], points[ib], points[ic]);
+        let area = cross(a, b, c);
+
+        let is_ear = area > 0.
+            && !ring.iter().any(|&r| {
+                let p = points[r];
+                p != a && p != b && p != c && in_triangle(p, a, b, c)
+            });
+
+        // Degenerate corners are removed without emitting a triangle. If no
+        // ear can be found, the polygon is not simple; clip anyway so that we
+        // always terminate.
+        if is_ear || area == 0. || stalled > n {
+            if area > 0. {
+                indices.extend_from_slice(&[ia as u32, ib as u32, ic as u32]);
+            }
+            ring.remove(i % n);
+            stalled = 0;
+        } else {
+            i += 1;
+            stalled += 1;
+        }
+        i %= ring.len();
+    }
+    if ring.len() == 3 && cross(points[ring[0]], points[ring[1]], points[ring[2]]) > 0. {
+        indices.extend(ring.iter().map(|&r| r as u32));
+    }
+    indices
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{area, positions};
+    use super::super::{Fill, Shape};
+    use super::*;
+    use crate::color::Rgba;
+
+    fn triangles_area(polygon: &Polygon) -> f32 {
+        let vertices = polygon.vertices();
+
+        polygon
+            .triangulate()
+            .chunks(3)
+            .map(|t| {
+                let (a, b, c) = (
+                    vertices[t[0] as usize],
+                    vertices[t[1] as usize],
+                    vertices[t[2] as usize],
+                );
+                assert!(cross(a, b, c) > 0., "triangles must be counter-clockwise");
+                cross(a, b, c) / 2.
+            })
+            .sum()
+    }
+
+    fn assert_area(polygon: &Polygon, expected: f32) {
+        assert!((polygon.area() - expected).abs() < 1e-3);
+        assert!(
+            (triangles_area(polygon) - expected).abs() < 1e-3,
+            "triangle area {} != polygon area {}",
+            triangles_area(polygon),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_convex() {
+        let square = Polygon::new(vec![[0., 0.], [4., 0.], [4., 4.], [0., 4.]]);
+        assert_area(&square, 16.);
+
+        // Clockwise winding works too.
+        let mut points = square.points.clone();
+        points.reverse();
+        assert_area(&Polygon::new(points), 16.);
+    }
+
+    #[test]
+    fn test_concave() {
+        let star = Polygon::new(
+            (0..10)
+                .map(|i| {
+                    let r = if i % 2 == 0 { 10. } else { 4. };
+                    let a = i as f32 * std::f32::consts::PI / 5.;
+                    [r * a.cos(), r * a.sin()]
+                })
+                .collect::<Vec<_>>(),
+        );
+        assert_area(&star, star.area());
+        assert!((triangles_area(&star) - signed_area(&star.points).abs()).abs() < 1e-3);
+
+        let l = Polygon::new(vec![
+            [0., 0.],
+            [3., 0.],
+            [3., 1.],
+            [1., 1.],
+            [1., 3.],
+            [0., 3.],
+        ]);
+        assert_area(&l, 5.);
+    }
+
+    #[test]
+    fn test_holes() {
+        let frame = Polygon::new(vec![[0., 0.], [10., 0.], [10., 10.], [0., 10.]])
+            .hole(vec![[2., 2.], [4., 2.], [4., 4.], [2., 4.]])
+            .hole(vec![[6., 6.], [6., 8.], [8., 8.], [8., 6.]])
+            .hole(vec![[6., 2.], [8., 2.], [7., 4.]]);
+
+        assert_area(&frame, 100. - 4. - 4. - 2.);
+    }
+
+    #[test]
+    fn test_collinear() {
+        let poly = Polygon::new(vec![[0., 0.], [1., 0.], [2., 0.], [2., 2.], [0., 2.]]);
+        assert_area(&poly, 4.);
+    }
+
+    #[test]
+    fn test_polygon_shape() {
+        let polygon = Polygon::new(vec![[0., 0.], [6., 0.], [6., 6.], [3., 3.], [0., 6.]])
+            .hole(vec![[1., 1.], [2., 1.], [2., 2.], [1., 2.]]);
+
+        let filled = Shape::polygon(polygon.clone())
+            .stroke(0., Rgba::WHITE)
+            .fill(Fill::solid(Rgba::RED));
+        assert!((area(&positions(&filled)) - polygon.area()).abs() < 1e-3);
+
+        // Stroke and fill together cover the polygon.
+        let stroked = Shape::polygon(polygon.clone())
+            .stroke(0.25, Rgba::WHITE)
+            .fill(Fill::solid(Rgba::RED));
+        assert!((area(&positions(&stroked)) - polygon.area()).abs() < 1e-3);
+    }
+}
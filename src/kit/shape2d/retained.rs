@@ -0,0 +1,438 @@
+use crate::kit::Rgba8;
+use crate::math::*;
+
+use super::{tessellate, Shape, Vertex, TOLERANCE};
+
+use std::ops::Range;
+
+/// A vertex which doesn't produce any fragments. Unused parts of the vertex
+/// buffer are filled with it, so that they form degenerate triangles.
+const DEGENERATE: Vertex = Vertex::new(0., 0., 0., 0., Point2::new(0., 0.), Rgba8::TRANSPARENT);
+
+/// A stable reference to a shape in a [`Retained`] batch. Handles of removed
+/// shapes are never reused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct Slot {
+    shape: Option<Shape>,
+    generation: u32,
+    range: Range<usize>,
+}
+
+/// A batch of shapes which persists across frames.
+///
+/// Each shape keeps its own range of the vertex buffer, and is only
+/// re-triangulated when it changes. The ranges of the buffer which changed
+/// since the last call to [`Retained::clean`] are available from
+/// [`Retained::dirty`], so that the GPU buffer can be patched in place.
+///
+/// Shapes which shrink keep their range, and shapes which grow are moved to
+/// a free range. Unused ranges are filled with degenerate triangles until
+/// they are reused, or the batch is compacted with [`Retained::compact`].
+/// Adjacent unused ranges are merged, and unused space at the end of the
+/// buffer is dropped.
+#[derive(Debug)]
+pub struct Retained {
+    tolerance: f32,
+    scale: f32,
+    antialias: bool,
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    vertices: Vec<Vertex>,
+    holes: Vec<Range<usize>>,
+    dirty: Vec<Range<usize>>,
+}
+
+impl Retained {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a shape, returning its handle.
+    pub fn add(&mut self, shape: Shape) -> Handle {
+        let range = self.place(&self.tessellate(&shape));
+
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.shape = Some(shape);
+                slot.range = range;
+
+                Handle {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    shape: Some(shape),
+                    generation: 0,
+                    range,
+                });
+                Handle {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Replace the shape behind a handle, returning the previous shape. Returns
+    /// `None` if the handle is no longer valid.
+    pub fn update(&mut self, handle: Handle, shape: Shape) -> Option<Shape> {
+        let range = self
+            .get(handle)
+            .map(|_| self.slots[handle.index as usize].range.clone())?;
+        let verts = self.tessellate(&shape);
+
+        let range = if verts.len() <= range.len() {
+            let (start, end) = (range.start, range.start + verts.len());
+
+            self.write(start..end, &verts);
+            self.release(end..range.end);
+
+            start..end
+        } else {
+            self.release(range);
+            self.place(&verts)
+        };
+
+        let slot = &mut self.slots[handle.index as usize];
+        slot.range = range;
+        slot.shape.replace(shape)
+    }
+
+    /// Remove the shape behind a handle, returning it. Returns `None` if the
+    /// handle is no longer valid.
+    pub fn remove(&mut self, handle: Handle) -> Option<Shape> {
+        self.get(handle)?;
+
+        let slot = &mut self.slots[handle.index as usize];
+        let range = std::mem::replace(&mut slot.range, 0..0);
+        let shape = slot.shape.take();
+
+        slot.generation += 1;
+        self.free.push(handle.index);
+        self.release(range);
+
+        shape
+    }
+
+    /// The shape behind a handle, if the handle is still valid.
+    pub fn get(&self, handle: Handle) -> Option<&Shape> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|s| s.generation == handle.generation)
+            .and_then(|s| s.shape.as_ref())
+    }
+
+    /// The range of the vertex buffer holding a shape.
+    pub fn range(&self, handle: Handle) -> Option<Range<usize>> {
+        self.get(handle)?;
+        Some(self.slots[handle.index as usize].range.clone())
+    }
+
+    /// The vertex buffer of the whole batch.
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// The ranges of the vertex buffer which changed since the last call to
+    /// [`Retained::clean`], sorted and merged.
+    ///
+    /// When the length of the vertex buffer changes, the GPU buffer has to be
+    /// recreated instead.
+    pub fn dirty(&self) -> Vec<Range<usize>> {
+        let mut ranges = self.dirty.clone();
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+
+        ranges.sort_by_key(|r| r.start);
+
+        for r in ranges {
+            match merged.last_mut() {
+                Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+        merged
+    }
+
+    /// Mark the vertex buffer as up to date.
+    pub fn clean(&mut self) {
+        self.dirty.clear();
+    }
+
+    /// Pack the shapes together, removing unused ranges from the vertex
+    /// buffer. This moves shapes, so the whole buffer becomes dirty.
+    pub fn compact(&mut self) {
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+
+        for slot in self.slots.iter_mut().filter(|s| s.shape.is_some()) {
+            let start = vertices.len();
+
+            vertices.extend_from_slice(&self.vertices[slot.range.clone()]);
+            slot.range = start..vertices.len();
+        }
+        self.vertices = vertices;
+        self.holes.clear();
+        self.dirty.clear();
+        self.dirty.push(0..self.vertices.len());
+    }
+
+    /// Set the scale at which the batch is drawn, for example the camera
+    /// zoom. See [`super::Batch::scale`]. This re-triangulates all shapes.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        self.rebuild();
+    }
+
+    /// Set the tolerance of curves, in pixels. This re-triangulates all shapes.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+        self.rebuild();
+    }
+
+    /// Enable or disable anti-aliasing. This re-triangulates all shapes.
+    pub fn set_antialias(&mut self, antialias: bool) {
+        self.antialias = antialias;
+        self.rebuild();
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all shapes. Existing handles are invalidated.
+    pub fn clear(&mut self) {
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            if slot.shape.take().is_some() {
+                slot.generation += 1;
+                slot.range = 0..0;
+                self.free.push(i as u32);
+            }
+        }
+        self.vertices.clear();
+        self.holes.clear();
+        self.dirty.clear();
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+
+    fn tessellate(&self, shape: &Shape) -> Vec<Vertex> {
+        tessellate(shape, self.tolerance, self.scale, self.antialias)
+    }
+
+    /// Re-triangulate all shapes into a new vertex buffer.
+    fn rebuild(&mut self) {
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+
+        for slot in self.slots.iter_mut() {
+            if let Some(ref shape) = slot.shape {
+                let start = vertices.len();
+
+                vertices.extend(tessellate(
+                    shape,
+                    self.tolerance,
+                    self.scale,
+                    self.antialias,
+                ));
+                slot.range = start..vertices.len();
+            }
+        }
+        self.vertices = vertices;
+        self.holes.clear();
+        self.dirty.clear();
+        self.dirty.push(0..self.vertices.len());
+    }
+
+    /// Store vertices in the first free range large enough to hold them, or
+    /// at the end of the buffer.
+    fn place(&mut self, verts: &[Vertex]) -> Range<usize> {
+        let n = verts.len();
+
+        if n == 0 {
+            return 0..0;
+        }
+        if let Some(i) = self.holes.iter().position(|h| h.len() >= n) {
+            let hole = self.holes[i].clone();
+            let range = hole.start..hole.start + n;
+
+            if range.end < hole.end {
+                self.holes[i] = range.end..hole.end;
+            } else {
+                self.holes.remove(i);
+            }
+            self.write(range.clone(), verts);
+
+            return range;
+        }
+        let start = self.vertices.len();
+
+        self.vertices.extend_from_slice(verts);
+        self.dirty.push(start..self.vertices.len());
+
+        start..self.vertices.len()
+    }
+
+    fn write(&mut self, range: Range<usize>, verts: &[Vertex]) {
+        self.vertices[range.clone()].copy_from_slice(verts);
+        self.dirty.push(range);
+    }
+
+    /// Free a range of the buffer, clearing its vertices. Holes are kept
+    /// sorted, and merged with their neighbours. Free space at the end of the
+    /// buffer is truncated.
+    fn release(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let mut i = self
+            .holes
+            .iter()
+            .position(|h| h.start > range.start)
+            .unwrap_or(self.holes.len());
+        let mut hole = range.clone();
+
+        if i < self.holes.len() && self.holes[i].start == hole.end {
+            hole.end = self.holes.remove(i).end;
+        }
+        if i > 0 && self.holes[i - 1].end == hole.start {
+            i -= 1;
+            hole.start = self.holes.remove(i).start;
+        }
+
+        if hole.end == self.vertices.len() {
+            self.vertices.truncate(hole.start);
+
+            for r in self.dirty.iter_mut() {
+                r.end = r.end.min(hole.start);
+            }
+            self.dirty.retain(|r| !r.is_empty());
+        } else {
+            for v in &mut self.vertices[range.clone()] {
+                *v = DEGENERATE;
+            }
+            self.dirty.push(range);
+            self.holes.insert(i, hole);
+        }
+    }
+}
+
+impl Default for Retained {
+    fn default() -> Self {
+        Self {
+            tolerance: TOLERANCE,
+            scale: 1.,
+            antialias: false,
+            slots: Vec::new(),
+            free: Vec::new(),
+            vertices: Vec::new(),
+            holes: Vec::new(),
+            dirty: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Rgba;
+    use crate::kit::shape2d::Fill;
+
+    fn square(x: f32) -> Shape {
+        Shape::rect([x, 0.], [x + 1., 1.])
+            .stroke(0., Rgba::TRANSPARENT)
+            .fill(Fill::solid(Rgba::RED))
+    }
+
+    #[test]
+    fn test_retained() {
+        let mut batch = Retained::new();
+        let a = batch.add(square(0.));
+        let b = batch.add(square(2.));
+        let c = batch.add(square(4.));
+
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch.vertices().len(), 18);
+        assert_eq!(batch.dirty(), vec![0..18]);
+        batch.clean();
+
+        // Updating a shape in place only dirties its own range.
+        batch.update(b, square(8.));
+        assert_eq!(batch.dirty(), vec![6..12]);
+        assert_eq!(batch.vertices()[6].position.x, 8.);
+        batch.clean();
+
+        // A shape which grows is moved to the end of the buffer.
+        let outlined = square(2.).stroke(0.25, Rgba::WHITE);
+        let n = outlined.triangulate().len();
+        batch.update(b, outlined);
+
+        assert_eq!(batch.range(b), Some(18..18 + n));
+        assert_eq!(batch.dirty(), vec![6..12, 18..18 + n]);
+        assert!(batch.vertices()[6..12].iter().all(|v| v.color.a == 0));
+        batch.clean();
+
+        // Removed shapes leave a hole, merged with the one next to it. Free
+        // ranges are reused first-fit.
+        assert!(batch.remove(a).is_some());
+        assert!(batch.remove(a).is_none());
+        assert!(batch.get(a).is_none());
+        assert_eq!(batch.dirty(), vec![0..6]);
+        assert_eq!(batch.holes, vec![0..12]);
+
+        let d = batch.add(square(6.));
+        assert_ne!(a, d);
+        assert!(batch.get(a).is_none());
+        assert_eq!(batch.range(d), Some(0..6));
+        assert_eq!(batch.holes, vec![6..12]);
+        assert_eq!(batch.vertices().len(), 18 + n);
+
+        // Compacting removes the remaining hole.
+        batch.compact();
+        assert_eq!(batch.vertices().len(), 12 + n);
+        assert_eq!(batch.range(d), Some(0..6));
+        assert_eq!(batch.range(c), Some(6 + n..12 + n));
+        assert_eq!(batch.get(c).map(|s| s.bounds().x1), Some(4.));
+
+        batch.clear();
+        assert!(batch.is_empty());
+        assert!(batch.get(c).is_none());
+    }
+
+    #[test]
+    fn test_release() {
+        let mut batch = Retained::new();
+        let handles: Vec<Handle> = (0..4).map(|i| batch.add(square(i as f32))).collect();
+        batch.clean();
+
+        // Holes on either side of a range are merged with it.
+        batch.remove(handles[0]);
+        batch.remove(handles[2]);
+        assert_eq!(batch.holes, vec![0..6, 12..18]);
+        batch.remove(handles[1]);
+        assert_eq!(batch.holes, vec![0..18]);
+
+        // Freeing the last range truncates the buffer, along with the hole
+        // before it, and dirty ranges past the end.
+        batch.remove(handles[3]);
+        assert!(batch.holes.is_empty());
+        assert!(batch.vertices().is_empty());
+        assert!(batch.dirty().is_empty());
+
+        // A shape which shrinks at the end of the buffer gives back its tail.
+        let h = batch.add(square(0.).stroke(0.25, Rgba::WHITE));
+        batch.update(h, square(0.));
+        assert_eq!(batch.vertices().len(), 6);
+        assert_eq!(batch.dirty(), vec![0..6]);
+    }
+}
@@ -0,0 +1,698 @@
+use crate::color::Rgba;
+use crate::math::*;
+
+use super::index::Mesh;
+use super::path;
+use super::polygon::normal;
+
+///////////////////////////////////////////////////////////////////////////
+// Stroke
+///////////////////////////////////////////////////////////////////////////
+
+/// Shape drawn at the open ends of a stroke.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// The stroke ends exactly at the end point.
+    #[default]
+    Butt,
+    /// The stroke ends with a half-circle.
+    Round,
+    /// The stroke is extended by half its width.
+    Square,
+}
+
+/// Shape drawn where two segments of a stroke meet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// The outer edges are extended until they meet, up to the miter limit.
+    #[default]
+    Miter,
+    /// The corner is rounded off.
+    Round,
+    /// The corner is cut off.
+    Bevel,
+}
+
+/// Where the stroke of a closed shape lies relative to its outline.
+///
+/// The closed subpaths of a path are aligned to the inside of the path, as
+/// given by its fill rule, and closed polylines to the side their points wind
+/// around. Paths and polylines are stroked centered by default, like in SVG.
+/// Lines, arcs, open subpaths and open polylines have no inside, and are
+/// always stroked centered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum StrokeAlignment {
+    /// The stroke is drawn inside the outline, so the shape keeps its size.
+    #[default]
+    Inside,
+    /// The stroke is centered on the outline.
+    Center,
+    /// The stroke is drawn outside the outline, leaving the fill uncovered.
+    Outside,
+}
+
+impl StrokeAlignment {
+    /// How far the center of a stroke of the given width is inset from the
+    /// outline. Negative if the stroke is centered outside of the outline.
+    pub(crate) fn inset(self, width: f32) -> f32 {
+        match self {
+            Self::Inside => width / 2.,
+            Self::Center => 0.,
+            Self::Outside => -width / 2.,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Stroke {
+    pub(crate) width: f32,
+    pub(crate) color: Rgba,
+    pub(crate) cap: LineCap,
+    pub(crate) join: LineJoin,
+    pub(crate) alignment: StrokeAlignment,
+    pub(crate) miter_limit: f32,
+    pub(crate) dashes: [f32; Stroke::MAX_DASHES],
+    pub(crate) dash_count: usize,
+    pub(crate) dash_offset: f32,
+}
+
+impl Stroke {
+    pub const NONE: Self = Self {
+        width: 0.,
+        color: Rgba::TRANSPARENT,
+        cap: LineCap::Butt,
+        join: LineJoin::Miter,
+        alignment: StrokeAlignment::Inside,
+        miter_limit: Self::MITER_LIMIT,
+        dashes: [0.; Self::MAX_DASHES],
+        dash_count: 0,
+        dash_offset: 0.,
+    };
+
+    /// Default miter limit, as a ratio of the miter length to the stroke width.
+    pub const MITER_LIMIT: f32 = 4.;
+    /// Maximum number of lengths in a dash pattern. Patterns are stored
+    /// inline, so that strokes can be copied.
+    pub const MAX_DASHES: usize = 8;
+
+    pub fn new(width: f32, color: Rgba) -> Self {
+        Self {
+            width,
+            color,
+            ..Self::NONE
+        }
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn alignment(mut self, alignment: StrokeAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Miter joins longer than this ratio of the stroke width are beveled.
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+
+    /// Dash the stroke with alternating lengths of dashes and gaps, starting
+    /// `offset` into the pattern. An odd number of lengths is repeated to
+    /// yield an even number. An empty pattern draws a solid stroke. The
+    /// pattern can have at most [`Stroke::MAX_DASHES`] lengths.
+    pub fn dash(mut self, pattern: &[f32], offset: f32) -> Self {
+        assert!(
+            pattern.len() <= Self::MAX_DASHES,
+            "Stroke::dash: too many dash lengths"
+        );
+        self.dashes[..pattern.len()].copy_from_slice(pattern);
+        self.dash_count = pattern.len();
+        self.dash_offset = offset;
+        self
+    }
+
+    /// The dash pattern, or an empty slice for a solid stroke.
+    pub fn dashes(&self) -> &[f32] {
+        &self.dashes[..self.dash_count]
+    }
+
+    pub fn dash_offset(&self) -> f32 {
+        self.dash_offset
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn color(&self) -> Rgba {
+        self.color
+    }
+
+    /// How far the center of the stroke of a closed shape is inset from its
+    /// outline. Negative if the stroke is centered outside of the outline.
+    pub(crate) fn inset(&self) -> f32 {
+        self.alignment.inset(self.width)
+    }
+
+    /// Scale the stroke width and dash pattern.
+    pub(super) fn scale(mut self, factor: f32) -> Self {
+        self.width *= factor;
+        self.dash_offset *= factor;
+        for d in self.dashes[..self.dash_count].iter_mut() {
+            *d *= factor;
+        }
+        self
+    }
+
+    /// Stroke a polyline, centered on its points, with a width of `widths[i]`
+    /// at point `i`. Round joins and caps are tessellated with the given
+    /// tolerance. The corners shared by segments and joins are stored once.
+    pub(super) fn polyline(
+        &self,
+        points: &[Point2<f32>],
+        widths: &[f32],
+        closed: bool,
+        tolerance: f32,
+    ) -> Mesh {
+        debug_assert_eq!(points.len(), widths.len());
+
+        let mut ps = Vec::with_capacity(points.len());
+        let mut ws = Vec::with_capacity(widths.len());
+
+        for (p, w) in points.iter().zip(widths.iter()) {
+            if ps.last() != Some(p) {
+                ps.push(*p);
+                ws.push(*w);
+            }
+        }
+        if closed && ps.len() > 1 && ps.first() == ps.last() {
+            ps.pop();
+            ws.pop();
+        }
+
+        let mut out = Mesh::new();
+
+        if ps.len() < 2 {
+            return out;
+        }
+        if self.is_dashed() {
+            for (ps, ws) in self.split_dashes(&ps, &ws, closed) {
+                self.solid(&ps, &ws, false, tolerance, &mut out);
+            }
+        } else {
+            self.solid(&ps, &ws, closed && ps.len() > 2, tolerance, &mut out);
+        }
+        out
+    }
+
+    fn is_dashed(&self) -> bool {
+        self.dashes().iter().sum::<f32>() > 0. && self.dashes().iter().all(|d| *d >= 0.)
+    }
+
+    /// Split a polyline into dashes.
+    fn split_dashes(
+        &self,
+        points: &[Point2<f32>],
+        widths: &[f32],
+        closed: bool,
+    ) -> Vec<(Vec<Point2<f32>>, Vec<f32>)> {
+        let mut pattern = self.dashes().to_vec();
+        if pattern.len() % 2 == 1 {
+            pattern.extend_from_slice(self.dashes());
+        }
+        let total: f32 = pattern.iter().sum();
+
+        // Find where in the pattern we start.
+        let mut i = 0;
+        let mut phase = self.dash_offset.rem_euclid(total);
+        while phase >= pattern[i] {
+            phase -= pattern[i];
+            i = (i + 1) % pattern.len();
+        }
+        let mut remaining = pattern[i] - phase;
+
+        let mut pieces = Vec::new();
+        let mut piece: (Vec<Point2<f32>>, Vec<f32>) = (Vec::new(), Vec::new());
+
+        if i % 2 == 0 {
+            piece.0.push(points[0]);
+            piece.1.push(widths[0]);
+        }
+
+        let n = if closed {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+        for s in 0..n {
+            let (a, b) = (points[s], points[(s + 1) % points.len()]);
+            let (wa, wb) = (widths[s], widths[(s + 1) % widths.len()]);
+            let len = (b - a).magnitude();
+            let mut t = 0.;
+
+            while len - t > remaining {
+                t += remaining;
+
+                let f = t / len;
+                let p = a + (b - a) * f;
+                let w = wa + (wb - wa) * f;
+
+                if i % 2 == 0 {
+                    piece.0.push(p);
+                    piece.1.push(w);
+                    pieces.push(std::mem::take(&mut piece));
+                } else {
+                    piece = (vec![p], vec![w]);
+                }
+                i = (i + 1) % pattern.len();
+                remaining = pattern[i];
+            }
+            remaining -= len - t;
+
+            if i % 2 == 0 {
+                piece.0.push(b);
+                piece.1.push(wb);
+            }
+        }
+        if i % 2 == 0 {
+            pieces.push(piece);
+        }
+        pieces.retain(|(ps, _)| ps.len() > 1);
+        pieces
+    }
+
+    /// Stroke a polyline without dashes. Points must be distinct.
+    fn solid(
+        &self,
+        points: &[Point2<f32>],
+        widths: &[f32],
+        closed: bool,
+        tolerance: f32,
+        out: &mut Mesh,
+    ) {
+        let n = points.len();
+        let segments = if closed { n } else { n - 1 };
+
+        // The left and right corners at the start and end of each segment.
+        let mut starts = Vec::with_capacity(segments);
+        let mut ends = Vec::with_capacity(segments);
+
+        for s in 0..segments {
+            let (a, b) = (points[s], points[(s + 1) % n]);
+            let nrm = normal(b - a);
+            let (ha, hb) = (widths[s] / 2., widths[(s + 1) % n] / 2.);
+
+            starts.push((a + nrm * ha, a - nrm * ha));
+            ends.push((b + nrm * hb, b - nrm * hb));
+        }
+
+        // Joins, along with the number of points and indices of the stroke,
+        // so that the mesh is only grown once.
+        let mut joins = Vec::with_capacity(n);
+        let (mut len, mut indices) = (4 * segments, 6 * segments);
+
+        for v in if closed { 0..n } else { 1..n - 1 } {
+            let (sa, sb) = ((v + segments - 1) % segments, v);
+            let p = points[v];
+            let hw = widths[v] / 2.;
+
+            let (prev, next) = (points[(v + n - 1) % n], points[(v + 1) % n]);
+            let (d0, d1) = ((p - prev).normalize(), (next - p).normalize());
+            let (n0, n1) = (normal(d0), normal(d1));
+            let cross = d0.x * d1.y - d0.y * d1.x;
+            let dot = Vector2::dot(d0, d1);
+
+            if cross.abs() < 1e-6 && dot > 0. {
+                continue;
+            }
+            // The outer side of the corner is to the right when turning left.
+            let side = if cross > 0. { -1. } else { 1. };
+            let theta = cross.abs().atan2(dot);
+            let ratio = 1. / (theta / 2.).cos();
+            let bisector = n0 + n1;
+            let mut pivot = None;
+
+            // Join the inner edges where they intersect, if that point lies
+            // within both segments. The intersection is then the pivot of the
+            // join, shared with both segments.
+            let reach = hw * (theta / 2.).tan();
+            if bisector.magnitude() > 1e-6
+                && reach <= (p - prev).magnitude()
+                && reach <= (next - p).magnitude()
+            {
+                let inner = p + bisector.normalize() * (-side * hw * ratio);
+
+                if side > 0. {
+                    ends[sa].1 = inner;
+                    starts[sb].1 = inner;
+                } else {
+                    ends[sa].0 = inner;
+                    starts[sb].0 = inner;
+                }
+            } else {
+                pivot = Some(p);
+                len += 1;
+            }
+
+            let kind = match self.join {
+                LineJoin::Miter if ratio <= self.miter_limit && bisector.magnitude() > 1e-6 => {
+                    len += 1;
+                    indices += 6;
+                    Join::Miter(p + bisector.normalize() * (side * hw * ratio))
+                }
+                LineJoin::Round => {
+                    let sweep = if cross > 0. { theta } else { -theta };
+                    let k = path::arc_segments(hw, sweep, tolerance) as usize;
+
+                    len += k - 1;
+                    indices += 3 * k;
+                    Join::Round(sweep)
+                }
+                _ => {
+                    indices += 3;
+                    Join::Bevel
+                }
+            };
+            joins.push(Corner {
+                segments: (sa, sb),
+                point: p,
+                width: hw,
+                outer: side > 0.,
+                pivot,
+                kind,
+            });
+        }
+
+        // Caps.
+        if !closed {
+            for hw in [widths[0] / 2., widths[n - 1] / 2.].iter() {
+                let (l, i) = self.cap_size(*hw, tolerance);
+
+                len += l;
+                indices += i;
+            }
+        }
+        out.reserve(len, indices);
+
+        // Segments, with their corners in the order: start left, start right,
+        // end right, end left.
+        let base = out.vertices.len() as u32;
+        let corner = |s: usize, end: bool, left: bool| {
+            base + 4 * s as u32
+                + match (end, left) {
+                    (false, true) => 0,
+                    (false, false) => 1,
+                    (true, false) => 2,
+                    (true, true) => 3,
+                }
+        };
+        for s in 0..segments {
+            let ((l0, r0), (l1, r1)) = (starts[s], ends[s]);
+            let (l0, r0, r1, l1) = (
+                out.vertex(l0),
+                out.vertex(r0),
+                out.vertex(r1),
+                out.vertex(l1),
+            );
+
+            out.triangle(l0, r0, r1);
+            out.triangle(l0, r1, l1);
+        }
+
+        for j in joins {
+            let (sa, sb) = j.segments;
+            let outer = (corner(sa, true, j.outer), corner(sb, false, j.outer));
+            let pivot = match j.pivot {
+                Some(p) => out.vertex(p),
+                None => corner(sa, true, !j.outer),
+            };
+
+            match j.kind {
+                Join::Miter(tip) => {
+                    let tip = out.vertex(tip);
+
+                    out.triangle(pivot, outer.0, tip);
+                    out.triangle(pivot, tip, outer.1);
+                }
+                Join::Round(sweep) => {
+                    let start = out.vertices[outer.0 as usize] - j.point;
+
+                    fan(
+                        out,
+                        (pivot, outer.0, Some(outer.1)),
+                        j.point,
+                        start,
+                        sweep,
+                        j.width,
+                        tolerance,
+                    );
+                }
+                Join::Bevel => {
+                    out.triangle(pivot, outer.0, outer.1);
+                }
+            }
+        }
+
+        // Caps.
+        if !closed {
+            let (first, last) = (points[0], points[n - 1]);
+            let d0 = (points[1] - first).normalize();
+            let d1 = (last - points[n - 2]).normalize();
+
+            self.end_cap(first, d0 * -1., widths[0] / 2., tolerance, out);
+            self.end_cap(last, d1, widths[n - 1] / 2., tolerance, out);
+        }
+    }
+
+    /// The number of points and indices of an end cap.
+    fn cap_size(&self, hw: f32, tolerance: f32) -> (usize, usize) {
+        match self.cap {
+            LineCap::Butt => (0, 0),
+            LineCap::Square => (4, 6),
+            LineCap::Round => {
+                let k = path::arc_segments(hw, std::f32::consts::PI, tolerance) as usize;
+                (k + 2, 3 * k)
+            }
+        }
+    }
+
+    /// Cap the stroke at point `p`, facing outwards in direction `d`.
+    fn end_cap(&self, p: Point2<f32>, d: Vector2<f32>, hw: f32, tolerance: f32, out: &mut Mesh) {
+        let n = normal(d);
+
+        match self.cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                let (a, b) = (p - n * hw, p + n * hw);
+                let (c, e) = (b + d * hw, a + d * hw);
+                let (a, b, c, e) = (out.vertex(a), out.vertex(b), out.vertex(c), out.vertex(e));
+
+                out.triangle(a, b, c);
+                out.triangle(a, c, e);
+            }
+            LineCap::Round => {
+                let (pivot, first) = (out.vertex(p), out.vertex(p - n * hw));
+
+                fan(
+                    out,
+                    (pivot, first, None),
+                    p,
+                    n * -hw,
+                    std::f32::consts::PI,
+                    hw,
+                    tolerance,
+                );
+            }
+        }
+    }
+}
+
+/// A join between two segments of a stroke.
+struct Corner {
+    /// The segments before and after the join.
+    segments: (usize, usize),
+    point: Point2<f32>,
+    /// Half the stroke width at the join.
+    width: f32,
+    /// Whether the outer side of the join is on the left of the segments.
+    outer: bool,
+    /// The point the join is fanned around, unless it is the inner corner
+    /// shared by both segments.
+    pivot: Option<Point2<f32>>,
+    kind: Join,
+}
+
+enum Join {
+    /// A miter, with its tip.
+    Miter(Point2<f32>),
+    /// A round join, sweeping by the given angle.
+    Round(f32),
+    Bevel,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self {
+            width: 1.,
+            color: Rgba::default(),
+            ..Self::NONE
+        }
+    }
+}
+
+/// Triangulate a circular wedge around `center`, starting at `center + start`
+/// and sweeping by `sweep` radians, as a fan around `pivot`. The fan is given
+/// as the indices of the pivot, the first point of the rim, and optionally
+/// its last point, which is otherwise added.
+fn fan(
+    out: &mut Mesh,
+    (pivot, first, last): (u32, u32, Option<u32>),
+    center: Point2<f32>,
+    start: Vector2<f32>,
+    sweep: f32,
+    radius: f32,
+    tolerance: f32,
+) {
+    let n = path::arc_segments(radius, sweep, tolerance);
+    let a0 = start.y.atan2(start.x);
+    let mut prev = first;
+
+    for i in 1..=n {
+        let next = match last {
+            Some(last) if i == n => last,
+            _ => {
+                let a = a0 + sweep * i as f32 / n as f32;
+                out.vertex(Point2::new(
+                    center.x + radius * a.cos(),
+                    center.y + radius * a.sin(),
+                ))
+            }
+        };
+        out.triangle(pivot, prev, next);
+        prev = next;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{area, positions};
+    use super::super::{Shape, TOLERANCE};
+    use super::*;
+    use crate::kit::Geometry;
+
+    fn stroke(s: &Stroke, points: &[[f32; 2]], closed: bool) -> Vec<Point2<f32>> {
+        let points: Vec<Point2<f32>> = points.iter().map(|p| Point2::from(*p)).collect();
+        s.polyline(&points, &vec![s.width; points.len()], closed, TOLERANCE)
+            .triangles()
+    }
+
+    #[test]
+    fn test_caps() {
+        let s = Stroke::new(2., Rgba::WHITE);
+        let line = [[0., 0.], [10., 0.]];
+
+        assert!((area(&stroke(&s, &line, false)) - 20.).abs() < 1e-3);
+        assert!((area(&stroke(&s.cap(LineCap::Square), &line, false)) - 24.).abs() < 1e-3);
+
+        let round = area(&stroke(&s.cap(LineCap::Round), &line, false));
+        assert!(round > 22. && round < 20. + std::f32::consts::PI);
+    }
+
+    #[test]
+    fn test_joins() {
+        // A closed square stroke with miter joins covers exactly the ring.
+        let square = [[0., 0.], [10., 0.], [10., 10.], [0., 10.]];
+        let s = Stroke::new(2., Rgba::WHITE);
+
+        assert!((area(&stroke(&s, &square, true)) - (144. - 64.)).abs() < 1e-3);
+
+        // Bevel cuts off each corner by half a unit square.
+        let bevel = area(&stroke(&s.join(LineJoin::Bevel), &square, true));
+        assert!((bevel - (144. - 64. - 4. * 0.5)).abs() < 1e-3);
+
+        // Round is in between.
+        let round = area(&stroke(&s.join(LineJoin::Round), &square, true));
+        assert!(round > bevel && round < 80.);
+
+        // A sharp corner exceeds the miter limit, and falls back to a bevel.
+        let sharp = [[0., 0.], [10., 0.], [0., 1.]];
+        let miter = stroke(&s.miter_limit(100.), &sharp, false);
+        let limited = stroke(&s, &sharp, false);
+        let beveled = stroke(&s.join(LineJoin::Bevel), &sharp, false);
+
+        assert!(area(&miter) > area(&limited));
+        assert!((area(&limited) - area(&beveled)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_dashes() {
+        let s = Stroke::new(2., Rgba::WHITE).dash(&[3., 1.], 0.);
+        let line = [[0., 0.], [10., 0.]];
+
+        // Dash patterns are stored inline, so strokes stay `Copy`.
+        let copy = s;
+        assert_eq!(copy.dashes(), s.dashes());
+
+        // Dashes at [0, 3], [4, 7] and [8, 10].
+        assert_eq!(
+            s.split_dashes(
+                &[Point2::new(0., 0.), Point2::new(10., 0.)],
+                &[2., 2.],
+                false
+            )
+            .len(),
+            3
+        );
+        assert!((area(&stroke(&s, &line, false)) - 16.).abs() < 1e-3);
+
+        // With an offset: [0, 2], [3, 6], [7, 10].
+        let s = s.dash(&[3., 1.], 1.);
+        assert!((area(&stroke(&s, &line, false)) - 16.).abs() < 1e-3);
+
+        // Dashes follow corners.
+        let corner = [[0., 0.], [4., 0.], [4., 4.]];
+        let s = Stroke::new(1., Rgba::WHITE).dash(&[6.], 0.);
+        let pieces = s.split_dashes(
+            &corner.iter().map(|p| Point2::from(*p)).collect::<Vec<_>>(),
+            &[1., 1., 1.],
+            false,
+        );
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].0.len(), 3);
+        assert_eq!(pieces[0].0[2], Point2::new(4., 2.));
+    }
+
+    #[test]
+    fn test_polyline() {
+        let points = vec![[0., 0.], [10., 0.], [10., 10.]];
+
+        // A single continuous stroke, without gaps or overlaps at the corner.
+        let polyline = Shape::polyline(points.clone()).stroke(2., Rgba::WHITE);
+        assert!((area(&positions(&polyline)) - 40.).abs() < 1e-3);
+
+        // Closed into a triangle, and stroked with a round join.
+        let closed = Shape::polyline(points.clone())
+            .stroke(2., Rgba::WHITE)
+            .join(LineJoin::Round)
+            .closed(true);
+        assert!(area(&positions(&closed)) > area(&positions(&polyline)));
+
+        // Tapered from zero to full width.
+        let tapered = Shape::polyline(vec![[0., 0.], [10., 0.]])
+            .stroke(2., Rgba::WHITE)
+            .widths(vec![0., 2.]);
+        assert!((area(&positions(&tapered)) - 10.).abs() < 1e-3);
+
+        // Widths are scaled along with the points.
+        let scaled = tapered.transform(Matrix4::from_scale(2.));
+        assert!((area(&positions(&scaled)) - 40.).abs() < 1e-3);
+    }
+}
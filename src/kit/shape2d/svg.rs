@@ -0,0 +1,1227 @@
+//! Import of SVG documents into shape batches.
+//!
+//! A subset of SVG is supported, suitable for icons and simple drawings:
+//!
+//! * The `svg`, `g`, `rect`, `circle`, `ellipse`, `line`, `polyline`,
+//!   `polygon` and `path` elements, with all path commands.
+//! * The `transform`, `viewBox` and `preserveAspectRatio` attributes.
+//! * Solid fill and stroke colors, opacity, and the stroke style properties,
+//!   either as attributes or in a `style` attribute.
+//!
+//! Descriptive elements such as `title` and `defs`, and elements in other
+//! namespaces, are skipped. Anything else which would affect rendering, such
+//! as text, gradients or clipping, is reported as an error.
+//!
+//! ```
+//! use rgx::kit::shape2d::svg;
+//!
+//! let batch = svg::parse(r#"
+//!     <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+//!         <circle cx="8" cy="8" r="6" fill="red" stroke="black"/>
+//!     </svg>
+//! "#).unwrap();
+//!
+//! assert_eq!(batch.len(), 1);
+//! assert!(svg::parse("<svg><text>Hi</text></svg>").is_err());
+//! ```
+use crate::color::Rgba;
+use crate::kit::{Geometry, ZDepth};
+use crate::math::*;
+use crate::rect::Rect;
+
+use super::{
+    Batch, Circle, Ellipse, Fill, FillRule, Line, LineCap, LineJoin, Path, Rotation, Shape, Stroke,
+    StrokeAlignment,
+};
+
+use std::error;
+use std::f32;
+use std::fmt;
+
+///////////////////////////////////////////////////////////////////////////
+// Errors
+///////////////////////////////////////////////////////////////////////////
+
+/// An error encountered while parsing an SVG document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The document is not well-formed, at the given byte offset.
+    Syntax(usize, &'static str),
+    /// The element isn't supported.
+    UnsupportedElement(String),
+    /// The attribute, or its value, isn't supported.
+    UnsupportedAttribute(String, String),
+    /// The value of the attribute couldn't be parsed.
+    InvalidAttribute(String, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(pos, msg) => write!(f, "syntax error at offset {}: {}", pos, msg),
+            Self::UnsupportedElement(name) => write!(f, "unsupported element `{}`", name),
+            Self::UnsupportedAttribute(name, value) => {
+                write!(f, "unsupported attribute `{}=\"{}\"`", name, value)
+            }
+            Self::InvalidAttribute(name, value) => {
+                write!(f, "invalid value for attribute `{}`: \"{}\"", name, value)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+fn invalid(name: &str, value: &str) -> Error {
+    Error::InvalidAttribute(name.to_owned(), value.to_owned())
+}
+
+fn unsupported(name: &str, value: &str) -> Error {
+    Error::UnsupportedAttribute(name.to_owned(), value.to_owned())
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Parsing
+///////////////////////////////////////////////////////////////////////////
+
+/// Parse an SVG document into a batch of shapes, in document order.
+///
+/// Strokes are centered on the outline of shapes, as in SVG. Group opacity
+/// is applied to the fill and stroke of each shape, so overlapping shapes in
+/// a translucent group are blended individually.
+pub fn parse(svg: &str) -> Result<Batch, Error> {
+    let mut tokens = Tokenizer { src: svg, pos: 0 };
+    let mut batch = Batch::new();
+    let mut stack: Vec<(&str, Style)> = Vec::new();
+    let mut skip: Vec<&str> = Vec::new();
+    let mut root = false;
+
+    while let Some(token) = tokens.next()? {
+        match token {
+            Token::Start(name, attrs, empty) => {
+                if !skip.is_empty() || is_skipped(name, &attrs) {
+                    if !empty {
+                        skip.push(name);
+                    }
+                    continue;
+                }
+                let parent = match stack.last() {
+                    Some((_, style)) => style.clone(),
+                    None if name == "svg" && !root => Style::default(),
+                    None => return Err(Error::Syntax(tokens.pos, "expected a root `svg` element")),
+                };
+                let mut style = parent.apply(&attrs)?;
+
+                match name {
+                    "svg" => {
+                        style.transform = style.transform * viewport(&attrs)?;
+                        root = true;
+                    }
+                    "g" => {}
+                    "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon" | "path" => {
+                        if let Some(shape) = shape(name, &attrs, &style)? {
+                            batch.add(shape.transform(style.transform));
+                        }
+                    }
+                    _ => return Err(Error::UnsupportedElement(name.to_owned())),
+                }
+                if !empty {
+                    stack.push((name, style));
+                }
+            }
+            Token::End(name) => {
+                let open = if skip.is_empty() {
+                    stack.pop().map(|(n, _)| n)
+                } else {
+                    skip.pop()
+                };
+                if open != Some(name) {
+                    return Err(Error::Syntax(tokens.pos, "mismatched closing tag"));
+                }
+            }
+        }
+    }
+    if !root {
+        return Err(Error::Syntax(tokens.pos, "expected a root `svg` element"));
+    }
+    if !stack.is_empty() || !skip.is_empty() {
+        return Err(Error::Syntax(tokens.pos, "unexpected end of document"));
+    }
+    Ok(batch)
+}
+
+/// Whether an element and its children should be skipped, either because
+/// it isn't rendered, or because it belongs to another namespace, such as
+/// editor metadata.
+fn is_skipped(name: &str, attrs: &[(&str, String)]) -> bool {
+    let hidden = attrs
+        .iter()
+        .any(|(k, v)| *k == "display" && v.trim() == "none")
+        || attrs
+            .iter()
+            .filter(|(k, _)| *k == "style")
+            .flat_map(|(_, v)| declarations(v))
+            .any(|(k, v)| k == "display" && v == "none");
+
+    hidden || name.contains(':') || ["title", "desc", "metadata", "defs"].contains(&name)
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Style
+///////////////////////////////////////////////////////////////////////////
+
+/// Inherited presentation state.
+#[derive(Clone, Debug)]
+struct Style {
+    fill: Option<Rgba>,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke: Option<Rgba>,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    cap: LineCap,
+    join: LineJoin,
+    miter_limit: f32,
+    dashes: Vec<f32>,
+    dash_offset: f32,
+    opacity: f32,
+    transform: Matrix4<f32>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Some(Rgba::BLACK),
+            fill_opacity: 1.,
+            fill_rule: FillRule::NonZero,
+            stroke: None,
+            stroke_opacity: 1.,
+            stroke_width: 1.,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: Stroke::MITER_LIMIT,
+            dashes: Vec::new(),
+            dash_offset: 0.,
+            opacity: 1.,
+            transform: Matrix4::identity(),
+        }
+    }
+}
+
+impl Style {
+    /// The style of an element with the given attributes. Declarations in the
+    /// `style` attribute take precedence over presentation attributes.
+    fn apply(mut self, attrs: &[(&str, String)]) -> Result<Self, Error> {
+        let mut opacity = 1.;
+
+        for (name, value) in attrs {
+            match *name {
+                "transform" => self.transform = self.transform * transform(value)?,
+                "opacity" => opacity = number(name, value)?,
+                "style" => {}
+                _ => self.property(name, value)?,
+            }
+        }
+        for (_, style) in attrs.iter().filter(|(k, _)| *k == "style") {
+            for (name, value) in declarations(style) {
+                match name {
+                    "transform" => return Err(unsupported(name, value)),
+                    "opacity" => opacity = number(name, value)?,
+                    _ => self.property(name, value)?,
+                }
+            }
+        }
+        self.opacity *= opacity.clamp(0., 1.);
+
+        Ok(self)
+    }
+
+    fn property(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        let value = value.trim();
+
+        if value == "inherit" {
+            return Ok(());
+        }
+        match name {
+            "fill" => self.fill = paint(name, value)?,
+            "fill-opacity" => self.fill_opacity = number(name, value)?.clamp(0., 1.),
+            "fill-rule" => {
+                self.fill_rule = match value {
+                    "nonzero" => FillRule::NonZero,
+                    "evenodd" => FillRule::EvenOdd,
+                    _ => return Err(invalid(name, value)),
+                }
+            }
+            "stroke" => self.stroke = paint(name, value)?,
+            "stroke-opacity" => self.stroke_opacity = number(name, value)?.clamp(0., 1.),
+            "stroke-width" => self.stroke_width = length(name, value)?,
+            "stroke-linecap" => {
+                self.cap = match value {
+                    "butt" => LineCap::Butt,
+                    "round" => LineCap::Round,
+                    "square" => LineCap::Square,
+                    _ => return Err(invalid(name, value)),
+                }
+            }
+            "stroke-linejoin" => {
+                self.join = match value {
+                    "miter" => LineJoin::Miter,
+                    "round" => LineJoin::Round,
+                    "bevel" => LineJoin::Bevel,
+                    _ => return Err(unsupported(name, value)),
+                }
+            }
+            "stroke-miterlimit" => self.miter_limit = number(name, value)?,
+            "stroke-dasharray" => {
+                self.dashes = if value == "none" {
+                    Vec::new()
+                } else {
+                    numbers(value)
+                        .map(|n| n.ok_or_else(|| invalid(name, value)))
+                        .collect::<Result<_, _>>()?
+                };
+                if self.dashes.len() > Stroke::MAX_DASHES {
+                    return Err(unsupported(name, value));
+                }
+            }
+            "stroke-dashoffset" => self.dash_offset = length(name, value)?,
+            "clip-path" | "mask" | "filter" | "marker-start" | "marker-mid" | "marker-end"
+                if value != "none" =>
+            {
+                return Err(unsupported(name, value))
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn fill(&self) -> Fill {
+        match self.fill {
+            Some(color) => Fill::Solid(Rgba {
+                a: color.a * self.fill_opacity * self.opacity,
+                ..color
+            }),
+            None => Fill::Empty,
+        }
+    }
+
+    fn stroke(&self) -> Stroke {
+        match self.stroke {
+            Some(color) if self.stroke_width > 0. => {
+                let color = Rgba {
+                    a: color.a * self.stroke_opacity * self.opacity,
+                    ..color
+                };
+                // SVG strokes are centered on the outline of closed shapes.
+                Stroke::new(self.stroke_width, color)
+                    .alignment(StrokeAlignment::Center)
+                    .cap(self.cap)
+                    .join(self.join)
+                    .miter_limit(self.miter_limit)
+                    .dash(&self.dashes, self.dash_offset)
+            }
+            _ => Stroke::NONE,
+        }
+    }
+}
+
+/// The declarations of a `style` attribute.
+fn declarations(style: &str) -> impl Iterator<Item = (&str, &str)> {
+    style.split(';').filter_map(|d| {
+        let mut parts = d.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(k), Some(v)) => Some((k.trim(), v.trim())),
+            _ => None,
+        }
+    })
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Shapes
+///////////////////////////////////////////////////////////////////////////
+
+/// Build the shape of an element, if it has any geometry.
+fn shape(name: &str, attrs: &[(&str, String)], style: &Style) -> Result<Option<Shape>, Error> {
+    let attr = |key: &str| {
+        attrs
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    let len =
+        |key: &str| -> Result<Option<f32>, Error> { attr(key).map(|v| length(key, v)).transpose() };
+    let (stroke, fill) = (style.stroke(), style.fill());
+    let z = ZDepth::default();
+
+    let shape = match name {
+        "rect" => {
+            let (x, y) = (len("x")?.unwrap_or(0.), len("y")?.unwrap_or(0.));
+            let (width, height) = (len("width")?.unwrap_or(0.), len("height")?.unwrap_or(0.));
+
+            if width <= 0. || height <= 0. {
+                return Ok(None);
+            }
+            let (rx, ry) = match (len("rx")?, len("ry")?) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(r), None) | (None, Some(r)) => (r, r),
+                (None, None) => (0., 0.),
+            };
+            let (rx, ry) = (rx.max(0.).min(width / 2.), ry.max(0.).min(height / 2.));
+            let r = Rect::new(x, y, x + width, y + height);
+
+            if rx == 0. || ry == 0. {
+                Shape::Rectangle(r, z, Rotation::ZERO, stroke, fill)
+            } else if (rx - ry).abs() < f32::EPSILON {
+                Shape::RoundedRectangle(r, [rx; 4], z, Rotation::ZERO, stroke, fill)
+            } else {
+                let path = Path::new()
+                    .move_to([x + rx, y])
+                    .line_to([x + width - rx, y])
+                    .arc_to([rx, ry], 0., false, true, [x + width, y + ry])
+                    .line_to([x + width, y + height - ry])
+                    .arc_to([rx, ry], 0., false, true, [x + width - rx, y + height])
+                    .line_to([x + rx, y + height])
+                    .arc_to([rx, ry], 0., false, true, [x, y + height - ry])
+                    .line_to([x, y + ry])
+                    .arc_to([rx, ry], 0., false, true, [x + rx, y])
+                    .close();
+
+                Shape::Path(path, z, Rotation::ZERO, stroke, fill)
+            }
+        }
+        "circle" => {
+            let r = len("r")?.unwrap_or(0.);
+
+            if r <= 0. {
+                return Ok(None);
+            }
+            let position = Point2::new(len("cx")?.unwrap_or(0.), len("cy")?.unwrap_or(0.));
+            let circle = Circle {
+                position,
+                radius: r,
+                sides: Circle::AUTO,
+            };
+            Shape::Circle(circle, z, Rotation::ZERO, stroke, fill)
+        }
+        "ellipse" => {
+            let (rx, ry) = (len("rx")?.unwrap_or(0.), len("ry")?.unwrap_or(0.));
+
+            if rx <= 0. || ry <= 0. {
+                return Ok(None);
+            }
+            let ellipse = Ellipse {
+                position: Point2::new(len("cx")?.unwrap_or(0.), len("cy")?.unwrap_or(0.)),
+                radii: Vector2::new(rx, ry),
+            };
+            Shape::Ellipse(ellipse, z, Rotation::ZERO, stroke, fill)
+        }
+        "line" => {
+            let p1 = Point2::new(len("x1")?.unwrap_or(0.), len("y1")?.unwrap_or(0.));
+            let p2 = Point2::new(len("x2")?.unwrap_or(0.), len("y2")?.unwrap_or(0.));
+
+            Shape::Line(Line::new(p1, p2), z, Rotation::ZERO, stroke)
+        }
+        "polyline" | "polygon" => {
+            let value = attr("points").unwrap_or("");
+            let coords = numbers(value)
+                .map(|n| n.ok_or_else(|| invalid("points", value)))
+                .collect::<Result<Vec<f32>, _>>()?;
+            let mut points = coords.chunks_exact(2).map(|c| Point2::new(c[0], c[1]));
+
+            let mut path = match points.next() {
+                Some(p) => Path::new().move_to(p),
+                None => return Ok(None),
+            };
+            for p in points {
+                path = path.line_to(p);
+            }
+            if name == "polygon" {
+                path = path.close();
+            }
+            Shape::Path(
+                path.fill_rule(style.fill_rule),
+                z,
+                Rotation::ZERO,
+                stroke,
+                fill,
+            )
+        }
+        "path" => {
+            let path = match attr("d") {
+                Some(d) => path(d).map_err(|_| invalid("d", d))?,
+                None => return Ok(None),
+            };
+            if path.is_empty() {
+                return Ok(None);
+            }
+            Shape::Path(
+                path.fill_rule(style.fill_rule),
+                z,
+                Rotation::ZERO,
+                stroke,
+                fill,
+            )
+        }
+        _ => return Err(Error::UnsupportedElement(name.to_owned())),
+    };
+    Ok(Some(shape))
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Values
+///////////////////////////////////////////////////////////////////////////
+
+fn number(name: &str, value: &str) -> Result<f32, Error> {
+    finite(value.trim()).ok_or_else(|| invalid(name, value))
+}
+
+/// A length in user units. Only unitless and pixel lengths are supported.
+fn length(name: &str, value: &str) -> Result<f32, Error> {
+    let value = value.trim();
+    let n = value.strip_suffix("px").unwrap_or(value);
+    let unit = |c: char| c == '%' || c.is_ascii_alphabetic();
+
+    match finite(n) {
+        Some(n) => Ok(n),
+        None if finite(n.trim_end_matches(unit)).is_some() => Err(unsupported(name, value)),
+        None => Err(invalid(name, value)),
+    }
+}
+
+/// A list of numbers separated by whitespace or commas. Yields `None` for
+/// values which aren't numbers.
+fn numbers(value: &str) -> impl Iterator<Item = Option<f32>> + '_ {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| finite(s.strip_suffix("px").unwrap_or(s)))
+}
+
+/// A finite number. Rust also parses `NaN`, `inf` and `infinity`, which
+/// aren't SVG numbers.
+fn finite(s: &str) -> Option<f32> {
+    s.parse().ok().filter(|n: &f32| n.is_finite())
+}
+
+/// A fill or stroke paint. Only solid colors are supported.
+fn paint(name: &str, value: &str) -> Result<Option<Rgba>, Error> {
+    match value {
+        "none" => Ok(None),
+        _ if value.starts_with("url(") || value == "currentColor" => Err(unsupported(name, value)),
+        _ => color(value).map(Some).ok_or_else(|| invalid(name, value)),
+    }
+}
+
+fn color(value: &str) -> Option<Rgba> {
+    let rgb = |r: u8, g: u8, b: u8| {
+        Some(Rgba::new(
+            r as f32 / 255.,
+            g as f32 / 255.,
+            b as f32 / 255.,
+            1.,
+        ))
+    };
+
+    if let Some(hex) = value.strip_prefix('#') {
+        let digit = |i: usize| u8::from_str_radix(hex.get(i..=i)?, 16).ok();
+
+        return match hex.len() {
+            3 => rgb(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17),
+            6 => rgb(
+                digit(0)? * 16 + digit(1)?,
+                digit(2)? * 16 + digit(3)?,
+                digit(4)? * 16 + digit(5)?,
+            ),
+            _ => None,
+        };
+    }
+    if let Some(args) = value.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+        let channels: Vec<f32> = args
+            .split(',')
+            .map(|c| {
+                let c = c.trim();
+                match c.strip_suffix('%') {
+                    Some(p) => p.trim().parse::<f32>().ok().map(|p| p / 100.),
+                    None => c.parse::<f32>().ok().map(|c| c / 255.),
+                }
+            })
+            .collect::<Option<_>>()?;
+
+        return match channels.as_slice() {
+            [r, g, b] => Some(Rgba::new(
+                r.clamp(0., 1.),
+                g.clamp(0., 1.),
+                b.clamp(0., 1.),
+                1.,
+            )),
+            _ => None,
+        };
+    }
+    match value.to_ascii_lowercase().as_str() {
+        "transparent" => Some(Rgba::TRANSPARENT),
+        "black" => rgb(0, 0, 0),
+        "silver" => rgb(192, 192, 192),
+        "gray" | "grey" => rgb(128, 128, 128),
+        "white" => rgb(255, 255, 255),
+        "maroon" => rgb(128, 0, 0),
+        "red" => rgb(255, 0, 0),
+        "purple" => rgb(128, 0, 128),
+        "fuchsia" | "magenta" => rgb(255, 0, 255),
+        "green" => rgb(0, 128, 0),
+        "lime" => rgb(0, 255, 0),
+        "olive" => rgb(128, 128, 0),
+        "yellow" => rgb(255, 255, 0),
+        "navy" => rgb(0, 0, 128),
+        "blue" => rgb(0, 0, 255),
+        "teal" => rgb(0, 128, 128),
+        "aqua" | "cyan" => rgb(0, 255, 255),
+        "orange" => rgb(255, 165, 0),
+        _ => None,
+    }
+}
+
+/// The affine transform `[a c e; b d f]`, as in SVG.
+fn affine(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Matrix4<f32> {
+    #[rustfmt::skip]
+    let m = Matrix4::new(
+        a,  b,  0., 0.,
+        c,  d,  0., 0.,
+        0., 0., 1., 0.,
+        e,  f,  0., 1.,
+    );
+    m
+}
+
+/// Parse a transform list.
+fn transform(value: &str) -> Result<Matrix4<f32>, Error> {
+    let mut m = Matrix4::identity();
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let open = rest.find('(').ok_or_else(|| invalid("transform", value))?;
+        let close = rest.find(')').ok_or_else(|| invalid("transform", value))?;
+        let name = rest[..open].trim();
+        let args = numbers(&rest[open + 1..close])
+            .collect::<Option<Vec<f32>>>()
+            .ok_or_else(|| invalid("transform", value))?;
+
+        let t = match (name, args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => affine(a, b, c, d, e, f),
+            ("translate", &[x]) => affine(1., 0., 0., 1., x, 0.),
+            ("translate", &[x, y]) => affine(1., 0., 0., 1., x, y),
+            ("scale", &[s]) => affine(s, 0., 0., s, 0., 0.),
+            ("scale", &[x, y]) => affine(x, 0., 0., y, 0., 0.),
+            ("rotate", &[a]) => rotate(a),
+            ("rotate", &[a, x, y]) => {
+                affine(1., 0., 0., 1., x, y) * rotate(a) * affine(1., 0., 0., 1., -x, -y)
+            }
+            ("skewX", &[a]) => affine(1., 0., a.to_radians().tan(), 1., 0., 0.),
+            ("skewY", &[a]) => affine(1., a.to_radians().tan(), 0., 1., 0., 0.),
+            _ => return Err(invalid("transform", value)),
+        };
+        m = m * t;
+        rest = rest[close + 1..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+    Ok(m)
+}
+
+/// A rotation by the given angle in degrees, clockwise on screen.
+fn rotate(degrees: f32) -> Matrix4<f32> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    affine(cos, sin, -sin, cos, 0., 0.)
+}
+
+/// The transform from the view box to the viewport of the root element.
+fn viewport(attrs: &[(&str, String)]) -> Result<Matrix4<f32>, Error> {
+    let attr = |key: &str| {
+        attrs
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    let view = match attr("viewBox") {
+        Some(v) => match numbers(v).collect::<Option<Vec<f32>>>().as_deref() {
+            Some(&[x, y, w, h]) if w > 0. && h > 0. => (x, y, w, h),
+            _ => return Err(invalid("viewBox", v)),
+        },
+        None => return Ok(Matrix4::identity()),
+    };
+    let (x, y, w, h) = view;
+    let width = attr("width")
+        .map(|v| length("width", v))
+        .transpose()?
+        .unwrap_or(w);
+    let height = attr("height")
+        .map(|v| length("height", v))
+        .transpose()?
+        .unwrap_or(h);
+    let (sx, sy) = (width / w, height / h);
+
+    match attr("preserveAspectRatio").map(str::trim) {
+        Some("none") => Ok(affine(sx, 0., 0., sy, -x * sx, -y * sy)),
+        None | Some("xMidYMid") | Some("xMidYMid meet") => {
+            let s = sx.min(sy);
+            let (tx, ty) = ((width - w * s) / 2., (height - h * s) / 2.);
+
+            Ok(affine(s, 0., 0., s, tx - x * s, ty - y * s))
+        }
+        Some(v) => Err(unsupported("preserveAspectRatio", v)),
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Path data
+///////////////////////////////////////////////////////////////////////////
+
+/// Parse path data. On error, returns the offset at which parsing failed.
+fn path(d: &str) -> Result<Path, usize> {
+    let mut s = Scanner {
+        src: d.as_bytes(),
+        pos: 0,
+    };
+    let mut path = Path::new();
+    let mut pen = Point2::new(0., 0.);
+    let mut start = pen;
+    // The last control point, for smooth curves.
+    let mut ctrl: Option<(u8, Point2<f32>)> = None;
+    let mut cmd: Option<u8> = None;
+
+    loop {
+        s.space();
+        if s.done() {
+            break;
+        }
+        let c = match s.peek() {
+            // Paths start with a move.
+            Some(c) if cmd.is_none() && c != b'M' && c != b'm' => return Err(s.pos),
+            Some(c) if c.is_ascii_alphabetic() => {
+                s.pos += 1;
+                c
+            }
+            // Repeated commands may omit the letter. A move is followed by
+            // implicit lines.
+            Some(_) => match cmd {
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(b'Z') | Some(b'z') | None => return Err(s.pos),
+                Some(c) => c,
+            },
+            None => break,
+        };
+        let rel = c.is_ascii_lowercase();
+        let at = |p: Point2<f32>| {
+            if rel {
+                Point2::new(pen.x + p.x, pen.y + p.y)
+            } else {
+                p
+            }
+        };
+        let reflect = |kind: u8| match ctrl {
+            Some((k, c)) if k == kind => Point2::new(2. * pen.x - c.x, 2. * pen.y - c.y),
+            _ => pen,
+        };
+        let mut next = None;
+
+        match c.to_ascii_uppercase() {
+            b'M' => {
+                let p = at(s.point()?);
+                path = path.move_to(p);
+                pen = p;
+                start = p;
+            }
+            b'L' => {
+                let p = at(s.point()?);
+                path = path.line_to(p);
+                pen = p;
+            }
+            b'H' => {
+                let x = s.number()?;
+                pen = Point2::new(if rel { pen.x + x } else { x }, pen.y);
+                path = path.line_to(pen);
+            }
+            b'V' => {
+                let y = s.number()?;
+                pen = Point2::new(pen.x, if rel { pen.y + y } else { y });
+                path = path.line_to(pen);
+            }
+            b'C' => {
+                let (c1, c2, p) = (at(s.point()?), at(s.point()?), at(s.point()?));
+                path = path.cubic_to(c1, c2, p);
+                next = Some((b'C', c2));
+                pen = p;
+            }
+            b'S' => {
+                let c1 = reflect(b'C');
+                let (c2, p) = (at(s.point()?), at(s.point()?));
+                path = path.cubic_to(c1, c2, p);
+                next = Some((b'C', c2));
+                pen = p;
+            }
+            b'Q' => {
+                let (c1, p) = (at(s.point()?), at(s.point()?));
+                path = path.quad_to(c1, p);
+                next = Some((b'Q', c1));
+                pen = p;
+            }
+            b'T' => {
+                let c1 = reflect(b'Q');
+                let p = at(s.point()?);
+                path = path.quad_to(c1, p);
+                next = Some((b'Q', c1));
+                pen = p;
+            }
+            b'A' => {
+                let (rx, ry, rotation) = (s.number()?, s.number()?, s.number()?);
+                let (large_arc, sweep) = (s.flag()?, s.flag()?);
+                let p = at(s.point()?);
+
+                path = path.arc_to(
+                    [rx.abs(), ry.abs()],
+                    rotation.to_radians(),
+                    large_arc,
+                    sweep,
+                    p,
+                );
+                pen = p;
+            }
+            b'Z' => {
+                path = path.close();
+                pen = start;
+            }
+            _ => return Err(s.pos - 1),
+        }
+        ctrl = next;
+        cmd = Some(c);
+    }
+    Ok(path)
+}
+
+/// A scanner over path data.
+struct Scanner<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).cloned()
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.src.len()
+    }
+
+    /// Skip whitespace and at most one comma.
+    fn space(&mut self) {
+        let mut comma = false;
+
+        while let Some(c) = self.peek() {
+            if c.is_ascii_whitespace() || (c == b',' && !comma) {
+                comma |= c == b',';
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, usize> {
+        self.space();
+
+        let start = self.pos;
+        let mut dot = false;
+        let mut exp = false;
+
+        if let Some(b'+') | Some(b'-') = self.peek() {
+            self.pos += 1;
+        }
+        while let Some(c) = self.peek() {
+            match c {
+                b'0'..=b'9' => {}
+                b'.' if !dot && !exp => dot = true,
+                b'e' | b'E' if !exp => {
+                    exp = true;
+                    if let Some(b'+') | Some(b'-') = self.src.get(self.pos + 1) {
+                        self.pos += 1;
+                    }
+                }
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.src[start..self.pos])
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .ok_or(start)
+    }
+
+    fn point(&mut self) -> Result<Point2<f32>, usize> {
+        Ok(Point2::new(self.number()?, self.number()?))
+    }
+
+    /// An arc flag, which may not be followed by a separator.
+    fn flag(&mut self) -> Result<bool, usize> {
+        self.space();
+
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.pos),
+        };
+        self.pos += 1;
+
+        Ok(flag)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// XML
+///////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+enum Token<'a> {
+    /// An opening tag, with its attributes, and whether it is self-closing.
+    Start(&'a str, Vec<(&'a str, String)>, bool),
+    /// A closing tag.
+    End(&'a str),
+}
+
+/// A tokenizer for the subset of XML used by SVG documents. Text content,
+/// comments, processing instructions and declarations are skipped.
+struct Tokenizer<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    /// Skip past the given delimiter.
+    fn skip_past(&mut self, delim: &str) -> Result<(), Error> {
+        match self.rest().find(delim) {
+            Some(i) => {
+                self.pos += i + delim.len();
+                Ok(())
+            }
+            None => Err(Error::Syntax(self.pos, "unterminated markup")),
+        }
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> Result<&'a str, Error> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=')
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(Error::Syntax(self.pos, "expected a name"));
+        }
+        self.pos += len;
+
+        Ok(&rest[..len])
+    }
+
+    fn next(&mut self) -> Result<Option<Token<'a>>, Error> {
+        loop {
+            match self.rest().find('<') {
+                Some(i) => self.pos += i,
+                None => return Ok(None),
+            }
+            let rest = self.rest();
+
+            if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_past("]]>")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!") {
+                self.skip_past(">")?;
+            } else if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                self.skip_space();
+                if !self.rest().starts_with('>') {
+                    return Err(Error::Syntax(self.pos, "expected `>`"));
+                }
+                self.pos += 1;
+
+                return Ok(Some(Token::End(name)));
+            } else {
+                self.pos += 1;
+                return self.start().map(Some);
+            }
+        }
+    }
+
+    fn start(&mut self) -> Result<Token<'a>, Error> {
+        let name = self.name()?;
+        let mut attrs = Vec::new();
+
+        loop {
+            self.skip_space();
+
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok(Token::Start(name, attrs, true));
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                return Ok(Token::Start(name, attrs, false));
+            } else if rest.is_empty() {
+                return Err(Error::Syntax(self.pos, "unterminated tag"));
+            }
+            let key = self.name()?;
+            self.skip_space();
+
+            if !self.rest().starts_with('=') {
+                return Err(Error::Syntax(self.pos, "expected `=`"));
+            }
+            self.pos += 1;
+            self.skip_space();
+
+            let quote = match self.rest().chars().next() {
+                Some(q) if q == '"' || q == '\'' => q,
+                _ => return Err(Error::Syntax(self.pos, "expected a quoted value")),
+            };
+            self.pos += 1;
+
+            let len = self
+                .rest()
+                .find(quote)
+                .ok_or(Error::Syntax(self.pos, "unterminated value"))?;
+            let value = unescape(&self.rest()[..len]);
+            self.pos += len + 1;
+
+            attrs.push((key, value));
+        }
+    }
+}
+
+/// Replace the predefined entities and character references.
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|h| u32::from_str_radix(h, 16).ok())
+                .unwrap_or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                .and_then(std::char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The bounds of the triangles covered by a shape.
+    fn coverage(shape: &Shape) -> Rect<f32> {
+        let points: Vec<Point2<f32>> = shape
+            .triangulate()
+            .into_iter()
+            .map(|v| v.transform(Matrix4::identity()))
+            .map(|v| Point2::new(v.position.x, v.position.y))
+            .collect();
+
+        points
+            .iter()
+            .fold(Rect::new(f32::MAX, f32::MAX, f32::MIN, f32::MIN), |r, p| {
+                Rect::new(r.x1.min(p.x), r.y1.min(p.y), r.x2.max(p.x), r.y2.max(p.y))
+            })
+    }
+
+    fn assert_coverage(batch: &Batch, expected: &[(f32, f32, f32, f32)]) {
+        assert_eq!(batch.len(), expected.len());
+
+        for (i, (shape, &(x1, y1, x2, y2))) in batch.shapes().iter().zip(expected).enumerate() {
+            let r = coverage(shape);
+
+            for (a, b) in &[(r.x1, x1), (r.y1, y1), (r.x2, x2), (r.y2, y2)] {
+                assert!(
+                    (a - b).abs() < 0.5,
+                    "shape #{}: {:?} != {:?}",
+                    i,
+                    r,
+                    (x1, y1, x2, y2)
+                );
+            }
+        }
+    }
+
+    fn fill(shape: &Shape) -> Option<Rgba> {
+        match shape {
+            Shape::Rectangle(.., Fill::Solid(c))
+            | Shape::Circle(.., Fill::Solid(c))
+            | Shape::Path(.., Fill::Solid(c)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_shapes() {
+        let batch = parse(include_str!("fixtures/shapes.svg")).unwrap();
+        let s = f32::consts::FRAC_1_SQRT_2;
+
+        assert_coverage(
+            &batch,
+            &[
+                (8., 8., 42., 32.),
+                (50., 40., 70., 50.),
+                (60., 10., 80., 30.),
+                (9., 54., 31., 66.),
+                (50., 59., 90., 61.),
+                (10. - 2. * s, 80. - 2. * s, 30. + 2. * s, 90. + 2. * s),
+                (60., 80., 80., 95.),
+            ],
+        );
+        assert_eq!(fill(&batch.shapes()[0]), Some(Rgba::RED));
+        assert_eq!(
+            fill(&batch.shapes()[6]),
+            Some(Rgba::new(1., 0., 128. / 255., 1.))
+        );
+    }
+
+    #[test]
+    fn test_path() {
+        let batch = parse(include_str!("fixtures/path.svg")).unwrap();
+
+        assert_coverage(&batch, &[(10., 0., 170., 50.)]);
+    }
+
+    #[test]
+    fn test_style() {
+        let batch = parse(include_str!("fixtures/style.svg")).unwrap();
+
+        assert_coverage(
+            &batch,
+            &[
+                (0., 0., 48., 48.),
+                (14., 14., 34., 34.),
+                (22., 22., 26., 58.),
+            ],
+        );
+        assert_eq!(fill(&batch.shapes()[0]), Some(Rgba::new(1., 0., 0., 0.25)));
+
+        match &batch.shapes()[1] {
+            Shape::Circle(circle, _, _, stroke, Fill::Empty) => {
+                // Strokes are centered on the outline, rather than growing it.
+                assert_eq!(circle.radius, 8.);
+                assert_eq!(stroke.alignment, StrokeAlignment::Center);
+                assert_eq!(stroke.width(), 4.);
+                assert_eq!(stroke.color(), Rgba::new(0., 0., 1., 0.5));
+                assert_eq!(stroke.dashes(), &[4., 2.]);
+            }
+            other => panic!("unexpected shape {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let svg = |body: &str| parse(&format!("<svg>{}</svg>", body));
+
+        assert_eq!(
+            svg("<text>Hello</text>").unwrap_err(),
+            Error::UnsupportedElement("text".to_owned())
+        );
+        assert_eq!(
+            svg(r#"<rect width="8" height="8" fill="url(#g)"/>"#).unwrap_err(),
+            unsupported("fill", "url(#g)")
+        );
+        assert_eq!(
+            svg(r#"<rect width="50%" height="8"/>"#).unwrap_err(),
+            unsupported("width", "50%")
+        );
+        assert_eq!(
+            svg(r#"<circle r="4" clip-path="url(#c)"/>"#).unwrap_err(),
+            unsupported("clip-path", "url(#c)")
+        );
+        assert_eq!(
+            svg(r#"<path d="M0 0 L 4"/>"#).unwrap_err(),
+            invalid("d", "M0 0 L 4")
+        );
+        assert_eq!(
+            svg(r#"<path d="Z L1 1"/>"#).unwrap_err(),
+            invalid("d", "Z L1 1")
+        );
+        assert_eq!(
+            svg(r#"<rect fill="chartreuse" width="1" height="1"/>"#).unwrap_err(),
+            invalid("fill", "chartreuse")
+        );
+
+        // Rust parses non-finite numbers, which SVG doesn't have.
+        assert_eq!(
+            svg(r#"<rect width="NaN" height="1"/>"#).unwrap_err(),
+            invalid("width", "NaN")
+        );
+        assert_eq!(
+            svg(r#"<polygon points="0 0 inf 0 1 1"/>"#).unwrap_err(),
+            invalid("points", "0 0 inf 0 1 1")
+        );
+        assert_eq!(
+            svg(r#"<circle r="1" stroke-miterlimit="infinity"/>"#).unwrap_err(),
+            invalid("stroke-miterlimit", "infinity")
+        );
+        assert_eq!(
+            svg(r#"<path d="M0 0 L NaN 1"/>"#).unwrap_err(),
+            invalid("d", "M0 0 L NaN 1")
+        );
+        assert!(matches!(svg("<g><rect/></svg>"), Err(Error::Syntax(..))));
+        assert!(matches!(parse("<g/>"), Err(Error::Syntax(..))));
+        assert!(matches!(svg("<rect width='1"), Err(Error::Syntax(..))));
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(color("#abc"), color("#aabbcc"));
+        assert_eq!(color("rgb(100%, 0%, 0%)"), Some(Rgba::RED));
+        assert_eq!(unescape("a &amp; b &#x41;&#66;"), "a & b AB");
+
+        let m = transform("translate(4, 2) rotate(90) scale(2 1)").unwrap();
+        let p = m * Point2::new(1., 1.);
+        assert!(
+            (p.x - 3.).abs() < 1e-5 && (p.y - 4.).abs() < 1e-5,
+            "{:?}",
+            p
+        );
+
+        // Arc flags may be written without separators.
+        let path = path("M0 0a5 5 0 105 5").unwrap();
+        assert_eq!(path.commands().len(), 2);
+    }
+}
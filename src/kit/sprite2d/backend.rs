@@ -1,5 +1,7 @@
 use crate::core;
+use crate::core::transform::TransformBuffer;
 use crate::core::{Binding, BindingType, Set, ShaderStage};
+use crate::kit;
 use crate::math::*;
 
 ///////////////////////////////////////////////////////////////////////////
@@ -21,6 +23,7 @@ pub struct Pipeline {
     pipeline: core::Pipeline,
     bindings: core::BindingGroup,
     buf: core::UniformBuffer,
+    model: TransformBuffer,
 }
 
 impl Pipeline {
@@ -34,12 +37,24 @@ impl Pipeline {
             .device
             .create_binding_group(&self.pipeline.layout.sets[1], &[texture, sampler])
     }
+
+    /// Create a buffer of model transforms, to be bound per draw with
+    /// [`TransformBuffer::offset`]. Batches are drawn with the identity
+    /// transform unless one is bound.
+    pub fn transforms(&self, r: &core::Renderer, transforms: &[Matrix4<f32>]) -> TransformBuffer {
+        TransformBuffer::new(&self.pipeline.layout.sets[2], transforms, &r.device)
+    }
+
+    /// Create an empty buffer of model transforms with the given capacity.
+    pub fn transforms_with_capacity(&self, r: &core::Renderer, cap: usize) -> TransformBuffer {
+        TransformBuffer::with_capacity(cap, &self.pipeline.layout.sets[2], &r.device)
+    }
 }
 
 //////////////////////////////////////////////////////////////////////////
 
 impl<'a> core::AbstractPipeline<'a> for Pipeline {
-    type PrepareContext = Matrix4<f32>;
+    type PrepareContext = kit::View;
     type Uniforms = self::Uniforms;
 
     fn description() -> core::PipelineDescription<'a> {
@@ -65,6 +80,11 @@ impl<'a> core::AbstractPipeline<'a> for Pipeline {
                         stage: ShaderStage::Fragment,
                     },
                 ]),
+                // Model transform.
+                Set(&[Binding {
+                    binding: BindingType::UniformBufferDynamic,
+                    stage: ShaderStage::Vertex,
+                }]),
             ],
             // TODO: Use `env("CARGO_MANIFEST_DIR")`
             vertex_shader: include_bytes!("data/sprite.vert.spv"),
@@ -77,25 +97,33 @@ impl<'a> core::AbstractPipeline<'a> for Pipeline {
         let ortho = Matrix4::identity();
         let buf = dev.create_uniform_buffer(&[self::Uniforms { ortho, transform }]);
         let bindings = dev.create_binding_group(&pipeline.layout.sets[0], &[&buf]);
+        let model = TransformBuffer::new(&pipeline.layout.sets[2], &[Matrix4::identity()], dev);
 
         Self {
             pipeline,
             buf,
             bindings,
+            model,
         }
     }
 
     fn apply(&self, pass: &mut core::Pass) {
         pass.set_pipeline(&self.pipeline);
         pass.set_binding(&self.bindings, &[]);
+        pass.set_binding(&self.model.binding, &[0]);
     }
 
     fn prepare(
         &'a self,
-        ortho: Matrix4<f32>,
+        view: kit::View,
     ) -> Option<(&'a core::UniformBuffer, Vec<self::Uniforms>)> {
-        let transform = Matrix4::identity();
-        Some((&self.buf, vec![self::Uniforms { transform, ortho }]))
+        Some((
+            &self.buf,
+            vec![self::Uniforms {
+                ortho: view.projection,
+                transform: view.transform,
+            }],
+        ))
     }
 }
 
@@ -5,6 +5,10 @@ layout(set = 0, binding = 0) uniform Globals {
 	mat4 transform;
 } global;
 
+layout(set = 2, binding = 0) uniform Model {
+	mat4 transform;
+} model;
+
 layout(location = 0) in vec3  position;
 layout(location = 1) in vec2  uv;
 layout(location = 2) in vec4  color;
@@ -29,5 +33,5 @@ void main() {
 	f_uv = uv;
 	f_opacity = opacity;
 
-	gl_Position = global.ortho * global.transform * vec4(position, 1.0);
+	gl_Position = global.ortho * global.transform * model.transform * vec4(position, 1.0);
 }
@@ -5,7 +5,7 @@ pub use backend::*;
 
 use crate::color::Rgba;
 use crate::kit::ZDepth;
-use crate::kit::{Repeat, Rgba8};
+use crate::kit::{Geometry, Repeat, Rgba8};
 use crate::math::*;
 use crate::rect::Rect;
 
@@ -45,6 +45,9 @@ pub struct Sprite {
     pub color: Rgba,
     pub alpha: f32,
     pub repeat: Repeat,
+    /// Transform applied to the corners of `dst`, for transforms which can't
+    /// be represented by `dst` alone, such as rotations.
+    pub transform: Option<Matrix4<f32>>,
 }
 
 impl Sprite {
@@ -77,6 +80,18 @@ impl Sprite {
     }
 }
 
+impl Geometry for Sprite {
+    fn transform(mut self, m: Matrix4<f32>) -> Self {
+        match self.transform {
+            // Translations and axis-aligned scales can be applied to `dst` directly.
+            None if m.x.y == 0. && m.y.x == 0. => self.dst = self.dst.transform(m),
+            Some(t) => self.transform = Some(m * t),
+            None => self.transform = Some(m),
+        }
+        self
+    }
+}
+
 pub fn sprite(src: Rect<f32>, dst: Rect<f32>) -> Sprite {
     Sprite::new(src, dst)
 }
@@ -166,6 +181,7 @@ impl Batch {
             color,
             alpha,
             repeat,
+            transform,
         } in self.items.iter()
         {
             let ZDepth(z) = zdepth;
@@ -179,19 +195,37 @@ impl Batch {
 
             let c: Rgba8 = (*color).into();
 
+            // Corners of the destination quad.
+            let (mut p1, mut p2, mut p3, mut p4) = (
+                Point2::new(dst.x1, dst.y1),
+                Point2::new(dst.x2, dst.y1),
+                Point2::new(dst.x2, dst.y2),
+                Point2::new(dst.x1, dst.y2),
+            );
+            if let Some(m) = transform {
+                p1 = *m * p1;
+                p2 = *m * p2;
+                p3 = *m * p3;
+                p4 = *m * p4;
+            }
+
             // TODO: Use an index buffer
             buf.extend_from_slice(&[
-                Vertex::new(dst.x1, dst.y1, *z, rx1 * re.x, ry2 * re.y, c, *alpha),
-                Vertex::new(dst.x2, dst.y1, *z, rx2 * re.x, ry2 * re.y, c, *alpha),
-                Vertex::new(dst.x2, dst.y2, *z, rx2 * re.x, ry1 * re.y, c, *alpha),
-                Vertex::new(dst.x1, dst.y1, *z, rx1 * re.x, ry2 * re.y, c, *alpha),
-                Vertex::new(dst.x1, dst.y2, *z, rx1 * re.x, ry1 * re.y, c, *alpha),
-                Vertex::new(dst.x2, dst.y2, *z, rx2 * re.x, ry1 * re.y, c, *alpha),
+                Vertex::new(p1.x, p1.y, *z, rx1 * re.x, ry2 * re.y, c, *alpha),
+                Vertex::new(p2.x, p2.y, *z, rx2 * re.x, ry2 * re.y, c, *alpha),
+                Vertex::new(p3.x, p3.y, *z, rx2 * re.x, ry1 * re.y, c, *alpha),
+                Vertex::new(p1.x, p1.y, *z, rx1 * re.x, ry2 * re.y, c, *alpha),
+                Vertex::new(p4.x, p4.y, *z, rx1 * re.x, ry1 * re.y, c, *alpha),
+                Vertex::new(p3.x, p3.y, *z, rx2 * re.x, ry1 * re.y, c, *alpha),
             ]);
         }
         buf
     }
 
+    pub fn sprites(&self) -> &[Sprite] {
+        &self.items
+    }
+
     pub fn clear(&mut self) {
         self.items.clear();
         self.size = 0;
@@ -208,6 +242,13 @@ impl Batch {
     }
 }
 
+impl Geometry for Batch {
+    fn transform(mut self, m: Matrix4<f32>) -> Self {
+        self.items = self.items.into_iter().map(|s| s.transform(m)).collect();
+        self
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -223,4 +264,26 @@ mod test {
                 .repeat(8., 8.),
         );
     }
+
+    #[test]
+    fn test_transform() {
+        let sprite = Sprite::new(Rect::origin(8., 8.), Rect::new(0., 0., 8., 4.));
+
+        let scaled = sprite
+            .clone()
+            .transform(Matrix4::from_translation(Vector3::new(1., 1., 0.)))
+            .transform(Matrix4::from_scale(2.));
+        assert_eq!(scaled.dst, Rect::new(2., 2., 18., 10.));
+        assert!(scaled.transform.is_none());
+
+        let mut batch = Batch::new(8, 8);
+        batch.push(sprite);
+
+        let rotated = batch.transform(Matrix4::from_angle_z(std::f32::consts::FRAC_PI_2));
+        let verts = rotated.vertices();
+
+        // The quad is rotated, not just moved.
+        let p = verts[2].position;
+        assert!((p.x + 4.).abs() < 1e-5 && (p.y - 8.).abs() < 1e-5);
+    }
 }
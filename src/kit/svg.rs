@@ -0,0 +1,1056 @@
+//! Export of shape and sprite batches to SVG.
+//!
+//! Batches are written out as a standalone SVG document, which can be viewed
+//! in a browser or compared as text, without a GPU. Shapes become native SVG
+//! elements, and sprites either become rectangles of their color, or show
+//! their source rectangle of the texture image.
+//!
+//! The output follows what is drawn on the GPU, rather than how batches are
+//! built:
+//!
+//! * Elements are ordered by depth, with ties kept in insertion order.
+//! * SVG strokes are centered on their outline, so closed shapes are inset
+//!   or grown according to their [`StrokeAlignment`]. Paths with an aligned
+//!   stroke are flattened to inset their closed subpaths. Open shapes are
+//!   stroked on their outline, as in SVG.
+//! * Polylines with per-point widths are written with their uniform stroke
+//!   width, and sprite color tints are ignored when an image is given.
+//!
+//! [`StrokeAlignment`]: crate::kit::shape2d::StrokeAlignment
+//!
+//! ```
+//! use rgx::color::Rgba;
+//! use rgx::kit::shape2d::{Batch, Fill, Shape};
+//! use rgx::kit::svg::Document;
+//!
+//! let batch = Batch::singleton(
+//!     Shape::circle([8., 8.], 4., 0)
+//!         .stroke(0., Rgba::TRANSPARENT)
+//!         .fill(Fill::solid(Rgba::RED)),
+//! );
+//! let svg = Document::new(16., 16.).shapes(&batch).to_string();
+//!
+//! assert!(svg.contains(r##"<circle cx="8" cy="8" r="4" fill="#ff0000"/>"##));
+//! ```
+use crate::color::{Rgba, Rgba8};
+use crate::kit::shape2d::{
+    self, polygon, Arc, Circle, Command, Ellipse, Fill, FillRule, LineCap, LineJoin, Pattern,
+    Rotation, Shape, Stroke, Subpath,
+};
+use crate::kit::sprite2d::{self, Sprite};
+use crate::kit::ZDepth;
+use crate::math::*;
+use crate::rect::Rect;
+
+use std::f32;
+use std::fmt;
+
+///////////////////////////////////////////////////////////////////////////
+// Document
+///////////////////////////////////////////////////////////////////////////
+
+/// An SVG document, built from batches. The document is written out with
+/// [`fmt::Display`].
+#[derive(Debug)]
+pub struct Document {
+    width: f32,
+    height: f32,
+    defs: Vec<Element>,
+    elements: Vec<(f32, Element)>,
+}
+
+impl Document {
+    /// Create an empty document showing the region from the origin to
+    /// `(width, height)`.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            defs: Vec::new(),
+            elements: Vec::new(),
+        }
+    }
+
+    /// Add the shapes of a batch.
+    pub fn shapes(mut self, batch: &shape2d::Batch) -> Self {
+        for shape in batch.shapes() {
+            let ZDepth(z) = shape.depth();
+            let element = self.shape(shape);
+
+            self.elements.push((z, element));
+        }
+        self
+    }
+
+    /// Add the sprites of a batch. If `href` is given, it should point to the
+    /// image the batch texture was created from, and sprites show their source
+    /// rectangle of it. Otherwise, sprites are drawn as rectangles of their
+    /// color, with their source rectangle in a `data-src` attribute.
+    pub fn sprites(mut self, batch: &sprite2d::Batch, href: Option<&str>) -> Self {
+        let pattern = href.map(|href| {
+            let id = format!("texture-{}", self.defs.len());
+            let (w, h) = (Num(batch.w as f32), Num(batch.h as f32));
+
+            self.defs.push(
+                Element::new("pattern")
+                    .attr("id", &id)
+                    .attr("patternUnits", "userSpaceOnUse")
+                    .attr("width", w)
+                    .attr("height", h)
+                    .child(
+                        Element::new("image")
+                            .attr("href", escape(href))
+                            .attr("width", w)
+                            .attr("height", h),
+                    ),
+            );
+            id
+        });
+
+        for sprite in batch.sprites() {
+            let ZDepth(z) = sprite.zdepth;
+            let element = match pattern {
+                Some(ref id) => Self::image(sprite, id),
+                None => Self::placeholder(sprite),
+            };
+            if let Some(element) = element {
+                self.elements.push((z, element));
+            }
+        }
+        self
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+
+    fn shape(&mut self, shape: &Shape) -> Element {
+        match *shape {
+            Shape::Line(l, _, rotation, ref stroke) => Element::new("line")
+                .attr("x1", Num(l.p1.x))
+                .attr("y1", Num(l.p1.y))
+                .attr("x2", Num(l.p2.x))
+                .attr("y2", Num(l.p2.y))
+                .stroke(stroke)
+                .rotation(rotation),
+            Shape::Rectangle(r, _, rotation, ref stroke, ref fill) => {
+                let r = inset(r, stroke.inset());
+
+                Element::new("rect")
+                    .attr("x", Num(r.x1))
+                    .attr("y", Num(r.y1))
+                    .attr("width", Num(r.width()))
+                    .attr("height", Num(r.height()))
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Circle(circle, _, rotation, ref stroke, ref fill) => {
+                let Circle {
+                    position,
+                    radius,
+                    sides,
+                } = circle;
+                let radius = (radius - stroke.inset()).max(0.);
+
+                let element = if sides == Circle::AUTO {
+                    Element::new("circle")
+                        .attr("cx", Num(position.x))
+                        .attr("cy", Num(position.y))
+                        .attr("r", Num(radius))
+                } else {
+                    let points = (0..sides).map(|i| {
+                        let a = i as f32 * 2. * f32::consts::PI / sides as f32;
+                        position + Vector2::new(a.cos(), a.sin()) * radius
+                    });
+                    Element::new("polygon").attr("points", points_data(points))
+                };
+                element
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Polygon(ref p, _, rotation, ref stroke, ref fill) => {
+                let mut data = String::new();
+
+                for contour in p.contours() {
+                    let contour = if stroke.inset() != 0. {
+                        polygon::offset(&contour, stroke.inset(), stroke.miter_limit)
+                    } else {
+                        contour
+                    };
+                    for (i, p) in contour.iter().enumerate() {
+                        let cmd = if i == 0 { 'M' } else { 'L' };
+                        data.push_str(&format!("{} {} {} ", cmd, Num(p.x), Num(p.y)));
+                    }
+                    data.push_str("Z ");
+                }
+                Element::new("path")
+                    .attr("d", data.trim_end())
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::RoundedRectangle(r, radii, _, rotation, ref stroke, ref fill) => {
+                let w = stroke.inset();
+                let max = f32::min(r.width().abs(), r.height().abs()) / 2.;
+                let r = inset(r, w);
+
+                // Radii are clamped to fit the rectangle, then inset.
+                let mut radii = radii;
+                for radius in radii.iter_mut() {
+                    *radius = (radius.clamp(0., max) - w).max(0.);
+                }
+
+                let element = if radii.iter().all(|rad| *rad == radii[0]) {
+                    let element = Element::new("rect")
+                        .attr("x", Num(r.x1))
+                        .attr("y", Num(r.y1))
+                        .attr("width", Num(r.width()))
+                        .attr("height", Num(r.height()));
+
+                    if radii[0] > 0. {
+                        element.attr("rx", Num(radii[0]))
+                    } else {
+                        element
+                    }
+                } else {
+                    Element::new("path").attr("d", rounded_rect_data(r, radii))
+                };
+                element
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Ellipse(ellipse, _, rotation, ref stroke, ref fill) => {
+                let Ellipse { position, radii } = ellipse;
+                let w = stroke.inset();
+
+                Element::new("ellipse")
+                    .attr("cx", Num(position.x))
+                    .attr("cy", Num(position.y))
+                    .attr("rx", Num((radii.x - w).max(0.)))
+                    .attr("ry", Num((radii.y - w).max(0.)))
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Arc(arc, _, rotation, ref stroke, ref fill) => {
+                let Arc {
+                    position,
+                    radius,
+                    start,
+                    end,
+                } = arc;
+                let p = position + Vector2::new(start.cos(), start.sin()) * radius;
+                let data = format!(
+                    "M {} {} {}",
+                    Num(p.x),
+                    Num(p.y),
+                    arc_data(position, radius, start, end)
+                );
+
+                // The fill covers the region between the arc and its chord,
+                // which is how SVG fills open paths.
+                Element::new("path")
+                    .attr("d", data)
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Pie(arc, _, rotation, ref stroke, ref fill) => {
+                let Arc {
+                    position,
+                    radius,
+                    start,
+                    end,
+                } = inset_pie(arc, stroke.inset(), stroke.miter_limit);
+                let p = arc.position + Vector2::new(start.cos(), start.sin()) * radius;
+                let data = format!(
+                    "M {} {} L {} {} {} Z",
+                    Num(position.x),
+                    Num(position.y),
+                    Num(p.x),
+                    Num(p.y),
+                    arc_data(arc.position, radius, start, end)
+                );
+
+                Element::new("path")
+                    .attr("d", data)
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Path(ref path, _, rotation, ref stroke, ref fill) => {
+                // Closed subpaths can only be inset once flattened.
+                let data = if stroke.inset() != 0. {
+                    subpaths_data(&path.inset_with(
+                        stroke.inset(),
+                        stroke.miter_limit,
+                        shape2d::TOLERANCE,
+                    ))
+                } else {
+                    path_data(path.commands())
+                };
+                let element = Element::new("path").attr("d", data);
+                let element = if path.fill_rule == FillRule::EvenOdd {
+                    element.attr("fill-rule", "evenodd")
+                } else {
+                    element
+                };
+                element
+                    .fill(fill, &mut self.defs)
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Polyline(ref points, _, rotation, ref stroke, _, closed) => {
+                let name = if closed { "polygon" } else { "polyline" };
+                let points = if closed && stroke.inset() != 0. {
+                    let side = polygon::signed_area(points).signum();
+                    polygon::offset(points, side * stroke.inset(), stroke.miter_limit)
+                } else {
+                    points.clone()
+                };
+
+                Element::new(name)
+                    .attr("points", points_data(points.into_iter()))
+                    .attr("fill", "none")
+                    .stroke(stroke)
+                    .rotation(rotation)
+            }
+            Shape::Transformed(ref shape, m) => Element::new("g")
+                .attr("transform", matrix(&m))
+                .child(self.shape(shape)),
+        }
+    }
+
+    /// A sprite showing part of the pattern with the given id, which tiles the
+    /// texture image. Returns `None` if the source rectangle is empty.
+    fn image(sprite: &Sprite, pattern: &str) -> Option<Element> {
+        let Sprite {
+            src,
+            dst,
+            ref repeat,
+            alpha,
+            transform,
+            ..
+        } = *sprite;
+
+        // The region of the texture covered by the sprite, in texels.
+        let (u1, u2) = (src.x1 * repeat.x, src.x2 * repeat.x);
+        let (v1, v2) = (src.y1 * repeat.y, src.y2 * repeat.y);
+
+        if u1 == u2 || v1 == v2 {
+            return None;
+        }
+
+        // Map the region onto the destination. Like the texture coordinates of
+        // sprite vertices, the region is flipped vertically.
+        let (sx, sy) = ((dst.x2 - dst.x1) / (u2 - u1), (dst.y2 - dst.y1) / (v2 - v1));
+        let m = Matrix4::from_translation(Vector3::new(dst.x1 - u1 * sx, dst.y1 + v2 * sy, 0.))
+            * Matrix4::from_nonuniform_scale(sx, -sy, 1.);
+        let m = transform.map_or(m, |t| t * m);
+
+        let element = Element::new("rect")
+            .attr("x", Num(u1.min(u2)))
+            .attr("y", Num(v1.min(v2)))
+            .attr("width", Num((u2 - u1).abs()))
+            .attr("height", Num((v2 - v1).abs()))
+            .attr("fill", format!("url(#{})", pattern));
+        let element = if alpha < 1. {
+            element.attr("opacity", Num(alpha))
+        } else {
+            element
+        };
+        Some(
+            Element::new("g")
+                .attr("transform", matrix(&m))
+                .child(element),
+        )
+    }
+
+    /// A sprite drawn as a rectangle of its color.
+    fn placeholder(sprite: &Sprite) -> Option<Element> {
+        let Sprite {
+            src,
+            dst,
+            color,
+            alpha,
+            transform,
+            ..
+        } = *sprite;
+        let dst = inset(dst, 0.);
+
+        let element = Element::new("rect")
+            .attr("x", Num(dst.x1))
+            .attr("y", Num(dst.y1))
+            .attr("width", Num(dst.width()))
+            .attr("height", Num(dst.height()))
+            .attr("fill", hex(color));
+        let element = if alpha < 1. {
+            element.attr("fill-opacity", Num(alpha))
+        } else {
+            element
+        };
+        let element = element.attr(
+            "data-src",
+            format!(
+                "{} {} {} {}",
+                Num(src.x1),
+                Num(src.y1),
+                Num(src.width()),
+                Num(src.height())
+            ),
+        );
+
+        Some(match transform {
+            Some(m) => element.attr("transform", matrix(&m)),
+            None => element,
+        })
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut elements: Vec<&(f32, Element)> = self.elements.iter().collect();
+
+        // Elements with a larger depth are drawn on top. The sort is stable,
+        // so later elements stay on top of earlier ones at the same depth.
+        elements.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = Num(self.width),
+            h = Num(self.height)
+        )?;
+        if !self.defs.is_empty() {
+            writeln!(f, "  <defs>")?;
+            for def in self.defs.iter() {
+                def.write(f, 2)?;
+            }
+            writeln!(f, "  </defs>")?;
+        }
+        for (_, element) in elements {
+            element.write(f, 1)?;
+        }
+        writeln!(f, "</svg>")
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Element
+///////////////////////////////////////////////////////////////////////////
+
+/// An SVG element, with its attributes in the order they were added.
+#[derive(Debug)]
+struct Element {
+    name: &'static str,
+    attrs: Vec<(&'static str, String)>,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            attrs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn attr<T: fmt::Display>(mut self, name: &'static str, value: T) -> Self {
+        self.attrs.push((name, value.to_string()));
+        self
+    }
+
+    fn child(mut self, child: Element) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Add the fill attributes. Gradients and patterns are added to `defs`.
+    /// Since the texture of a textured fill isn't known, it is filled with its
+    /// color, with its texture transform in a `data-uv` attribute.
+    fn fill(self, fill: &Fill, defs: &mut Vec<Element>) -> Self {
+        match *fill {
+            Fill::Empty => self.attr("fill", "none"),
+            Fill::Solid(color) => {
+                let element = self.attr("fill", hex(color));
+
+                if color.a < 1. {
+                    element.attr("fill-opacity", Num(color.a))
+                } else {
+                    element
+                }
+            }
+            Fill::LinearGradient {
+                start,
+                end,
+                ref stops,
+            } => {
+                let id = format!("gradient-{}", defs.len());
+
+                defs.push(
+                    Element::new("linearGradient")
+                        .attr("id", &id)
+                        .attr("gradientUnits", "userSpaceOnUse")
+                        .attr("x1", Num(start.x))
+                        .attr("y1", Num(start.y))
+                        .attr("x2", Num(end.x))
+                        .attr("y2", Num(end.y))
+                        .stops(stops),
+                );
+                self.attr("fill", format!("url(#{})", id))
+            }
+            Fill::RadialGradient {
+                center,
+                radius,
+                ref stops,
+            } => {
+                let id = format!("gradient-{}", defs.len());
+
+                defs.push(
+                    Element::new("radialGradient")
+                        .attr("id", &id)
+                        .attr("gradientUnits", "userSpaceOnUse")
+                        .attr("cx", Num(center.x))
+                        .attr("cy", Num(center.y))
+                        .attr("r", Num(radius))
+                        .stops(stops),
+                );
+                self.attr("fill", format!("url(#{})", id))
+            }
+            Fill::Pattern {
+                pattern,
+                colors: [a, b],
+                transform,
+            } => {
+                let id = format!("pattern-{}", defs.len());
+                let cell = |x: f32, w: f32, y: f32, h: f32, color: Rgba| {
+                    Element::new("rect")
+                        .attr("x", Num(x))
+                        .attr("y", Num(y))
+                        .attr("width", Num(w))
+                        .attr("height", Num(h))
+                        .fill(&Fill::Solid(color), &mut Vec::new())
+                };
+                let (w, h, cells) = match pattern {
+                    Pattern::Checkerboard => (
+                        2.,
+                        2.,
+                        vec![
+                            cell(0., 1., 0., 1., a),
+                            cell(1., 1., 0., 1., b),
+                            cell(0., 1., 1., 1., b),
+                            cell(1., 1., 1., 1., a),
+                        ],
+                    ),
+                    Pattern::Stripes => (
+                        2.,
+                        1.,
+                        vec![cell(0., 1., 0., 1., a), cell(1., 1., 0., 1., b)],
+                    ),
+                    Pattern::Hatch(width) => (
+                        1.,
+                        1.,
+                        vec![cell(0., 1., 0., 1., b), cell(0., width, 0., 1., a)],
+                    ),
+                };
+                let mut element = Element::new("pattern")
+                    .attr("id", &id)
+                    .attr("patternUnits", "userSpaceOnUse")
+                    .attr("width", Num(w))
+                    .attr("height", Num(h));
+
+                if let Some(m) = shape2d::invert(&transform) {
+                    element = element.attr("patternTransform", matrix(&m));
+                }
+                defs.push(cells.into_iter().fold(element, Element::child));
+
+                self.attr("fill", format!("url(#{})", id))
+            }
+            Fill::Texture { color, transform } => self
+                .fill(&Fill::Solid(color), defs)
+                .attr("data-uv", matrix(&transform)),
+        }
+    }
+
+    fn stops(self, stops: &[(f32, Rgba)]) -> Self {
+        stops.iter().fold(self, |gradient, (offset, color)| {
+            let stop = Element::new("stop")
+                .attr("offset", Num(*offset))
+                .attr("stop-color", hex(*color));
+            let stop = if color.a < 1. {
+                stop.attr("stop-opacity", Num(color.a))
+            } else {
+                stop
+            };
+            gradient.child(stop)
+        })
+    }
+
+    /// Add the stroke attributes, leaving out those which have their default
+    /// value in SVG.
+    fn stroke(self, stroke: &Stroke) -> Self {
+        if stroke.width <= 0. || stroke.color.a <= 0. {
+            return self;
+        }
+        let mut e = self
+            .attr("stroke", hex(stroke.color))
+            .attr("stroke-width", Num(stroke.width));
+
+        if stroke.color.a < 1. {
+            e = e.attr("stroke-opacity", Num(stroke.color.a));
+        }
+        match stroke.cap {
+            LineCap::Butt => {}
+            LineCap::Round => e = e.attr("stroke-linecap", "round"),
+            LineCap::Square => e = e.attr("stroke-linecap", "square"),
+        }
+        match stroke.join {
+            LineJoin::Miter => {}
+            LineJoin::Round => e = e.attr("stroke-linejoin", "round"),
+            LineJoin::Bevel => e = e.attr("stroke-linejoin", "bevel"),
+        }
+        if stroke.miter_limit != Stroke::MITER_LIMIT {
+            e = e.attr("stroke-miterlimit", Num(stroke.miter_limit));
+        }
+        if !stroke.dashes().is_empty() {
+            let dashes: Vec<String> = stroke
+                .dashes()
+                .iter()
+                .map(|d| Num(*d).to_string())
+                .collect();
+
+            e = e.attr("stroke-dasharray", dashes.join(" "));
+
+            if stroke.dash_offset != 0. {
+                e = e.attr("stroke-dashoffset", Num(stroke.dash_offset));
+            }
+        }
+        e
+    }
+
+    fn rotation(self, rotation: Rotation) -> Self {
+        if rotation.angle == 0. {
+            return self;
+        }
+        // Shapes are rotated clockwise by their angle, in a y-down space.
+        self.attr(
+            "transform",
+            format!(
+                "rotate({} {} {})",
+                Num(-rotation.angle.to_degrees()),
+                Num(rotation.center.x),
+                Num(rotation.center.y)
+            ),
+        )
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}<{}", "", self.name, indent = depth * 2)?;
+
+        for (name, value) in self.attrs.iter() {
+            write!(f, r#" {}="{}""#, name, value)?;
+        }
+        if self.children.is_empty() {
+            return writeln!(f, "/>");
+        }
+        writeln!(f, ">")?;
+
+        for child in self.children.iter() {
+            child.write(f, depth + 1)?;
+        }
+        writeln!(f, "{:indent$}</{}>", "", self.name, indent = depth * 2)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Values
+///////////////////////////////////////////////////////////////////////////
+
+/// A number, written with at most three decimals and no trailing zeros.
+#[derive(Copy, Clone, Debug)]
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = format!("{:.3}", self.0);
+        let s = s.trim_end_matches('0').trim_end_matches('.');
+
+        if s == "-0" {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", s)
+        }
+    }
+}
+
+fn hex(color: Rgba) -> String {
+    let Rgba8 { r, g, b, .. } = color.into();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// The `matrix` transform of the 2D part of a matrix.
+fn matrix(m: &Matrix4<f32>) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        Num(m.x.x),
+        Num(m.x.y),
+        Num(m.y.x),
+        Num(m.y.y),
+        Num(m.w.x),
+        Num(m.w.y)
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A rectangle with positive width and height, inset on all sides.
+fn inset(r: Rect<f32>, d: f32) -> Rect<f32> {
+    let (x1, x2) = (r.x1.min(r.x2), r.x1.max(r.x2));
+    let (y1, y2) = (r.y1.min(r.y2), r.y1.max(r.y2));
+    let (dx, dy) = (d.min((x2 - x1) / 2.), d.min((y2 - y1) / 2.));
+
+    Rect::new(x1 + dx, y1 + dy, x2 - dx, y2 - dy)
+}
+
+/// Inset a pie by `d` on all sides, or grow it if `d` is negative, returning
+/// the new center, radius and angles. The arc of the result is still
+/// centered on the original center. The center moves by at most `limit`
+/// times `d`.
+fn inset_pie(arc: Arc, d: f32, limit: f32) -> Arc {
+    let Arc {
+        position,
+        radius,
+        start,
+        end,
+    } = arc;
+    let sweep = end - start;
+    let radius = (radius - d).max(0.);
+
+    if d == 0. || sweep.abs() >= 2. * f32::consts::PI {
+        return Arc { radius, ..arc };
+    }
+    let half = sweep.abs() / 2.;
+    let mid = (start + end) / 2.;
+
+    // The center moves along the bisector, like a mitered corner. The ends of
+    // the arc move along it, to stay clear of the straight edges.
+    let k = d / half.sin().max(1. / limit);
+    let delta = if radius > d.abs() {
+        (d / radius).asin().min(half)
+    } else {
+        half
+    };
+
+    Arc {
+        position: position + Vector2::new(mid.cos(), mid.sin()) * k,
+        radius,
+        start: start + delta * sweep.signum(),
+        end: end - delta * sweep.signum(),
+    }
+}
+
+fn points_data<I: Iterator<Item = Point2<f32>>>(points: I) -> String {
+    let points: Vec<String> = points
+        .map(|p| format!("{},{}", Num(p.x), Num(p.y)))
+        .collect();
+    points.join(" ")
+}
+
+/// Arc commands around `position`, from the point at angle `start` to the
+/// point at angle `end`. Arcs are split so that none spans more than half a
+/// turn.
+fn arc_data(position: Point2<f32>, radius: f32, start: f32, end: f32) -> String {
+    let sweep = end - start;
+    let n = (sweep.abs() / f32::consts::PI).ceil().max(1.) as u32;
+    let flag = if sweep > 0. { 1 } else { 0 };
+    let mut data = Vec::with_capacity(n as usize);
+
+    for i in 1..=n {
+        let a = start + sweep * i as f32 / n as f32;
+        let p = position + Vector2::new(a.cos(), a.sin()) * radius;
+
+        data.push(format!(
+            "A {r} {r} 0 0 {} {} {}",
+            flag,
+            Num(p.x),
+            Num(p.y),
+            r = Num(radius)
+        ));
+    }
+    data.join(" ")
+}
+
+/// Path data of a rounded rectangle, clockwise from the `(x1, y1)` corner.
+fn rounded_rect_data(r: Rect<f32>, radii: [f32; 4]) -> String {
+    let corners = [
+        (
+            Point2::new(r.x1, r.y1),
+            Vector2::new(0., 1.),
+            Vector2::new(1., 0.),
+        ),
+        (
+            Point2::new(r.x2, r.y1),
+            Vector2::new(-1., 0.),
+            Vector2::new(0., 1.),
+        ),
+        (
+            Point2::new(r.x2, r.y2),
+            Vector2::new(0., -1.),
+            Vector2::new(-1., 0.),
+        ),
+        (
+            Point2::new(r.x1, r.y2),
+            Vector2::new(1., 0.),
+            Vector2::new(0., -1.),
+        ),
+    ];
+    let mut data = Vec::new();
+
+    for (i, ((corner, from, to), radius)) in corners.iter().zip(radii.iter()).enumerate() {
+        let (a, b) = (*corner + *from * *radius, *corner + *to * *radius);
+        let cmd = if i == 0 { "M" } else { "L" };
+
+        data.push(format!("{} {} {}", cmd, Num(a.x), Num(a.y)));
+
+        if *radius > 0. {
+            data.push(format!(
+                "A {r} {r} 0 0 1 {} {}",
+                Num(b.x),
+                Num(b.y),
+                r = Num(*radius)
+            ));
+        }
+    }
+    data.push("Z".to_owned());
+    data.join(" ")
+}
+
+/// Path data for flattened subpaths.
+fn subpaths_data(subpaths: &[Subpath]) -> String {
+    let mut data = String::new();
+
+    for subpath in subpaths {
+        for (i, p) in subpath.points.iter().enumerate() {
+            let cmd = if i == 0 { 'M' } else { 'L' };
+            data.push_str(&format!("{} {} {} ", cmd, Num(p.x), Num(p.y)));
+        }
+        if subpath.closed {
+            data.push_str("Z ");
+        }
+    }
+    data.trim_end().to_owned()
+}
+
+fn path_data(commands: &[Command]) -> String {
+    let data: Vec<String> = commands
+        .iter()
+        .map(|cmd| match *cmd {
+            Command::MoveTo(p) => format!("M {} {}", Num(p.x), Num(p.y)),
+            Command::LineTo(p) => format!("L {} {}", Num(p.x), Num(p.y)),
+            Command::QuadTo(c, p) => {
+                format!("Q {} {} {} {}", Num(c.x), Num(c.y), Num(p.x), Num(p.y))
+            }
+            Command::CubicTo(c1, c2, p) => format!(
+                "C {} {} {} {} {} {}",
+                Num(c1.x),
+                Num(c1.y),
+                Num(c2.x),
+                Num(c2.y),
+                Num(p.x),
+                Num(p.y)
+            ),
+            Command::ArcTo {
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => format!(
+                "A {} {} {} {} {} {} {}",
+                Num(radii.x),
+                Num(radii.y),
+                Num(rotation.to_degrees()),
+                large_arc as u8,
+                sweep as u8,
+                Num(to.x),
+                Num(to.y)
+            ),
+            Command::Close => "Z".to_owned(),
+        })
+        .collect();
+    data.join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::kit::shape2d::{Batch, Path, Polygon, StrokeAlignment};
+
+    #[test]
+    fn test_shapes() {
+        let mut batch = Batch::new();
+
+        batch.add(
+            Shape::rect([0., 0.], [8., 4.])
+                .zdepth(0.5)
+                .stroke(2., Rgba::RED)
+                .fill(Fill::solid(Rgba::new(0., 0., 1., 0.5))),
+        );
+        batch.add(
+            Shape::line([0., 0.], [8., 8.])
+                .rotation(f32::consts::FRAC_PI_2, [4., 4.])
+                .stroke(1., Rgba::WHITE)
+                .cap(LineCap::Round),
+        );
+        batch.add(
+            Shape::polygon(Polygon::new(vec![[0., 0.], [4., 0.], [0., 4.]]))
+                .stroke(1., Rgba::BLACK)
+                .dash(&[2., 1.], 0.5)
+                .fill(Fill::linear_gradient(
+                    [0., 0.],
+                    [4., 0.],
+                    &[(0., Rgba::RED), (1., Rgba::BLUE)],
+                )),
+        );
+        batch.add(
+            Shape::arc([8., 8.], 4., 0., 2. * f32::consts::PI)
+                .stroke(1., Rgba::BLACK)
+                .fill(Fill::Empty),
+        );
+
+        batch.add(
+            Shape::polyline(vec![[0., 0.], [4., 0.], [4., 4.]])
+                .closed(true)
+                .stroke(2., Rgba::BLACK)
+                .alignment(StrokeAlignment::Outside),
+        );
+        batch.add(
+            Shape::path(
+                Path::new()
+                    .move_to([0., 0.])
+                    .line_to([0., 4.])
+                    .line_to([4., 4.])
+                    .close(),
+            )
+            .stroke(2., Rgba::BLACK)
+            .alignment(StrokeAlignment::Inside),
+        );
+
+        // The rectangle is on top, and strokes of closed shapes are inset.
+        assert_eq!(
+            Document::new(16., 16.).shapes(&batch).to_string(),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16" viewBox="0 0 16 16">
+  <defs>
+    <linearGradient id="gradient-0" gradientUnits="userSpaceOnUse" x1="0" y1="0" x2="4" y2="0">
+      <stop offset="0" stop-color="#ff0000"/>
+      <stop offset="1" stop-color="#0000ff"/>
+    </linearGradient>
+  </defs>
+  <line x1="0" y1="0" x2="8" y2="8" stroke="#ffffff" stroke-width="1" stroke-linecap="round" transform="rotate(-90 4 4)"/>
+  <path d="M 0.5 0.5 L 2.793 0.5 L 0.5 2.793 Z" fill="url(#gradient-0)" stroke="#000000" stroke-width="1" stroke-dasharray="2 1" stroke-dashoffset="0.5"/>
+  <path d="M 12 8 A 4 4 0 0 1 4 8 A 4 4 0 0 1 12 8" fill="none" stroke="#000000" stroke-width="1"/>
+  <polygon points="-2.414,-1 5,-1 5,6.414" fill="none" stroke="#000000" stroke-width="2"/>
+  <path d="M 1 2.414 L 1 3 L 1.586 3 Z" fill="none" stroke="#000000" stroke-width="2"/>
+  <rect x="1" y="1" width="6" height="2" fill="#0000ff" fill-opacity="0.5" stroke="#ff0000" stroke-width="2"/>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn test_sprites() {
+        let mut batch = sprite2d::Batch::new(32, 16);
+
+        batch.push(
+            Sprite::new(Rect::new(16., 0., 32., 16.), Rect::new(10., 10., 42., 42.))
+                .color(Rgba::RED)
+                .alpha(0.5),
+        );
+
+        assert_eq!(
+            Document::new(64., 64.)
+                .sprites(&batch, None)
+                .sprites(&batch, Some("tiles&more.png"))
+                .to_string(),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="64" height="64" viewBox="0 0 64 64">
+  <defs>
+    <pattern id="texture-0" patternUnits="userSpaceOnUse" width="32" height="16">
+      <image href="tiles&amp;more.png" width="32" height="16"/>
+    </pattern>
+  </defs>
+  <rect x="10" y="10" width="32" height="32" fill="#ff0000" fill-opacity="0.5" data-src="16 0 16 16"/>
+  <g transform="matrix(2 0 0 -2 -22 42)">
+    <rect x="16" y="0" width="16" height="16" fill="url(#texture-0)" opacity="0.5"/>
+  </g>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut batch = Batch::new();
+
+        batch.add(Shape::rect([1., 1.], [9., 5.]).stroke(2., Rgba::RED));
+        batch.add(Shape::circle([8., 8.], 4., Circle::AUTO).stroke(1., Rgba::RED));
+        batch.add(Shape::ellipse([8., 8.], 6., 3.).stroke(1., Rgba::RED));
+        batch.add(Shape::rounded_rect([2., 2.], [12., 8.], [2.; 4]).stroke(1., Rgba::RED));
+
+        // Imported closed shapes are grown by half their stroke width, so
+        // they should end up where they started.
+        let svg = Document::new(16., 16.).shapes(&batch).to_string();
+        let imported = shape2d::svg::parse(&svg).unwrap();
+
+        assert_eq!(imported.len(), batch.len());
+
+        for (a, b) in batch.shapes().iter().zip(imported.shapes()) {
+            let (a, b) = (a.bounds(), b.bounds());
+
+            assert!((a.x1 - b.x1).abs() < 0.01, "{:?} {:?}", a, b);
+            assert!((a.y1 - b.y1).abs() < 0.01, "{:?} {:?}", a, b);
+            assert!((a.x2 - b.x2).abs() < 0.01, "{:?} {:?}", a, b);
+            assert!((a.y2 - b.y2).abs() < 0.01, "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_patterns() {
+        let batch = Batch::singleton(
+            Shape::rect([0., 0.], [8., 8.])
+                .stroke(0., Rgba::TRANSPARENT)
+                .fill(Fill::checkerboard(2., Rgba::RED, Rgba::BLUE)),
+        );
+        let svg = Document::new(8., 8.).shapes(&batch).to_string();
+
+        assert!(svg.contains(
+            r#"<pattern id="pattern-0" patternUnits="userSpaceOnUse" width="2" height="2" patternTransform="matrix(2 0 0 2 0 0)">"#
+        ));
+        assert!(svg.contains(r##"<rect x="1" y="0" width="1" height="1" fill="#0000ff"/>"##));
+        assert!(svg.contains(r#"fill="url(#pattern-0)""#));
+
+        let batch = Batch::singleton(
+            Shape::rect([0., 0.], [4., 2.])
+                .stroke(0., Rgba::TRANSPARENT)
+                .fill(Fill::texture(Rect::new(0., 0., 4., 2.))),
+        );
+        let svg = Document::new(4., 2.).shapes(&batch).to_string();
+
+        assert!(svg.contains(r##"fill="#ffffff" data-uv="matrix(0.25 0 0 -0.5 0 1)""##));
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(Num(1.).to_string(), "1");
+        assert_eq!(Num(0.125).to_string(), "0.125");
+        assert_eq!(Num(2.50004).to_string(), "2.5");
+        assert_eq!(Num(-0.0001).to_string(), "0");
+        assert_eq!(hex(Rgba::new(1., 0.5, 0., 1.)), "#ff8000");
+    }
+}
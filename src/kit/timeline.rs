@@ -0,0 +1,635 @@
+use crate::color::Rgba;
+use crate::kit::{AnimationState, Lerp};
+use crate::math::{Point2, Vector2};
+use crate::rect::Rect;
+
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::time;
+
+///////////////////////////////////////////////////////////////////////////
+// Easing
+///////////////////////////////////////////////////////////////////////////
+
+/// Easing curve applied to a segment between two keyframes.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Hold the previous value until the keyframe is reached.
+    Hold,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Map a linear progress `t` in `[0, 1]` onto the easing curve.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Self::Linear => t,
+            Self::Hold => {
+                if t < 1. {
+                    0.
+                } else {
+                    1.
+                }
+            }
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => {
+                let u = 1. - t;
+                1. - u * u * u
+            }
+            Self::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Track
+///////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug)]
+pub struct Keyframe<T> {
+    pub time: time::Duration,
+    pub value: T,
+    /// Easing used for the segment leading up to this keyframe.
+    pub easing: Easing,
+}
+
+/// A sequence of keyframes for a single property, sorted by time.
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Lerp + Copy> Track<T> {
+    pub fn new() -> Self {
+        Self {
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Add a keyframe reached linearly from the previous one.
+    pub fn key(self, time: time::Duration, value: T) -> Self {
+        self.eased(time, value, Easing::Linear)
+    }
+
+    /// Add a keyframe reached from the previous one using the given easing.
+    pub fn eased(mut self, time: time::Duration, value: T, easing: Easing) -> Self {
+        self.insert(Keyframe {
+            time,
+            value,
+            easing,
+        });
+        self
+    }
+
+    /// Insert a keyframe, replacing any existing keyframe at the same time.
+    pub fn insert(&mut self, key: Keyframe<T>) {
+        match self.keyframes.binary_search_by(|k| k.time.cmp(&key.time)) {
+            Ok(i) => self.keyframes[i] = key,
+            Err(i) => self.keyframes.insert(i, key),
+        }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> time::Duration {
+        self.keyframes
+            .last()
+            .map(|k| k.time)
+            .unwrap_or_else(|| time::Duration::from_secs(0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Sample the track at the given time. Values are held before the
+    /// first and after the last keyframe.
+    pub fn sample(&self, t: time::Duration) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+
+        if t <= first.time {
+            return Some(first.value);
+        }
+        if t >= last.time {
+            return Some(last.value);
+        }
+        // The index of the first keyframe strictly after `t`.
+        let next = self.keyframes.partition_point(|k| k.time <= t);
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+
+        let span = (b.time - a.time).as_nanos() as f64;
+        let progress = ((t - a.time).as_nanos() as f64 / span) as f32;
+
+        Some(a.value.lerp(b.value, b.easing.apply(progress)))
+    }
+}
+
+impl<T: Lerp + Copy> Default for Track<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Values
+///////////////////////////////////////////////////////////////////////////
+
+/// A sampled track value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value {
+    Float(f32),
+    Point(Point2<f32>),
+    Vector(Vector2<f32>),
+    Color(Rgba),
+    Rect(Rect<f32>),
+}
+
+/// Types which can be animated by a [`Timeline`] track.
+pub trait Keyable: Lerp + Copy + Sized {
+    fn track(track: Track<Self>) -> AnyTrack;
+    fn value(value: Value) -> Option<Self>;
+}
+
+/// A track of any [`Keyable`] type.
+#[derive(Clone, Debug)]
+pub enum AnyTrack {
+    Float(Track<f32>),
+    Point(Track<Point2<f32>>),
+    Vector(Track<Vector2<f32>>),
+    Color(Track<Rgba>),
+    Rect(Track<Rect<f32>>),
+}
+
+impl AnyTrack {
+    fn sample(&self, t: time::Duration) -> Option<Value> {
+        match self {
+            Self::Float(track) => track.sample(t).map(Value::Float),
+            Self::Point(track) => track.sample(t).map(Value::Point),
+            Self::Vector(track) => track.sample(t).map(Value::Vector),
+            Self::Color(track) => track.sample(t).map(Value::Color),
+            Self::Rect(track) => track.sample(t).map(Value::Rect),
+        }
+    }
+
+    fn duration(&self) -> time::Duration {
+        match self {
+            Self::Float(track) => track.duration(),
+            Self::Point(track) => track.duration(),
+            Self::Vector(track) => track.duration(),
+            Self::Color(track) => track.duration(),
+            Self::Rect(track) => track.duration(),
+        }
+    }
+}
+
+macro_rules! keyable {
+    ($t:ty, $variant:ident) => {
+        impl Keyable for $t {
+            fn track(track: Track<Self>) -> AnyTrack {
+                AnyTrack::$variant(track)
+            }
+
+            fn value(value: Value) -> Option<Self> {
+                match value {
+                    Value::$variant(v) => Some(v),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+keyable!(f32, Float);
+keyable!(Point2<f32>, Point);
+keyable!(Vector2<f32>, Vector);
+keyable!(Rgba, Color);
+keyable!(Rect<f32>, Rect);
+
+/// A typed handle to a track in a [`Timeline`].
+#[derive(Debug)]
+pub struct TrackId<T> {
+    index: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T> Clone for TrackId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TrackId<T> {}
+
+/// The state of every track of a [`Timeline`] at a point in time.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub time: time::Duration,
+    pub values: Vec<Value>,
+}
+
+impl Sample {
+    /// Get the value of the given track.
+    pub fn get<T: Keyable>(&self, id: TrackId<T>) -> T {
+        T::value(self.values[id.index]).expect("Sample::get: track type mismatch")
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////
+// Timeline
+///////////////////////////////////////////////////////////////////////////
+
+/// A named point in time, reported by [`Timeline::step`] when crossed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    pub name: String,
+    pub time: time::Duration,
+}
+
+/// A region of the timeline which is played repeatedly before moving on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    pub range: Range<time::Duration>,
+    /// Number of times the region is repeated, or `None` to loop forever.
+    pub repeat: Option<u32>,
+}
+
+/// A set of keyframe tracks played together.
+///
+/// Stepping follows the same semantics as [`crate::kit::Animation`]: the
+/// timeline advances while it is playing, and keeps its position while it
+/// is paused. The [`AnimationState`] holds the number of region loops taken
+/// and the current playhead position.
+///
+/// ```
+/// use rgx::kit::timeline::{Easing, Timeline, Track};
+/// use rgx::kit::Rgba;
+/// use std::time::Duration;
+///
+/// let mut timeline = Timeline::new();
+/// let opacity = timeline.track(
+///     Track::new()
+///         .key(Duration::from_secs(0), 0.0)
+///         .eased(Duration::from_secs(1), 1.0, Easing::EaseOut),
+/// );
+/// let color = timeline.track(
+///     Track::new()
+///         .key(Duration::from_secs(0), Rgba::BLACK)
+///         .key(Duration::from_secs(2), Rgba::WHITE),
+/// );
+///
+/// timeline.step(Duration::from_secs(1));
+/// let state = timeline.val();
+///
+/// assert_eq!(state.get(opacity), 1.0);
+/// assert_eq!(state.get(color), Rgba::new(0.5, 0.5, 0.5, 1.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Timeline {
+    pub state: AnimationState,
+    pub markers: Vec<Marker>,
+    pub regions: Vec<Region>,
+
+    tracks: Vec<AnyTrack>,
+    /// Loops taken in each region.
+    loops: Vec<u32>,
+    /// Whether markers at the playhead are yet to be reported, because it was
+    /// moved there rather than stepped onto it.
+    pending: bool,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            state: AnimationState::Playing(0, time::Duration::from_secs(0)),
+            markers: Vec::new(),
+            regions: Vec::new(),
+            tracks: Vec::new(),
+            loops: Vec::new(),
+            pending: true,
+        }
+    }
+
+    /// Add a track to the timeline, returning a handle to its sampled values.
+    pub fn track<T: Keyable>(&mut self, track: Track<T>) -> TrackId<T> {
+        assert!(
+            !track.is_empty(),
+            "Timeline::track: track must have at least one keyframe"
+        );
+        self.tracks.push(T::track(track));
+
+        TrackId {
+            index: self.tracks.len() - 1,
+            marker: PhantomData,
+        }
+    }
+
+    /// Add a named marker at the given time.
+    pub fn marker(&mut self, name: &str, time: time::Duration) {
+        let marker = Marker {
+            name: name.to_owned(),
+            time,
+        };
+        let i = self.markers.partition_point(|m| m.time <= time);
+        self.markers.insert(i, marker);
+    }
+
+    /// Loop over the given range, `repeat` times, or forever if `None`.
+    pub fn region(&mut self, range: Range<time::Duration>, repeat: Option<u32>) {
+        assert!(
+            range.start < range.end,
+            "Timeline::region: region must not be empty"
+        );
+        self.regions.push(Region { range, repeat });
+        self.loops.push(0);
+    }
+
+    /// Time of the last keyframe across all tracks.
+    pub fn duration(&self) -> time::Duration {
+        self.tracks
+            .iter()
+            .map(AnyTrack::duration)
+            .chain(self.markers.iter().map(|m| m.time))
+            .max()
+            .unwrap_or_else(|| time::Duration::from_secs(0))
+    }
+
+    /// Advance the playhead by `delta`, returning the markers that were
+    /// crossed, in order. A marker is crossed when the playhead moves past
+    /// or onto it, or starts from it after a seek, a restart or a region
+    /// loop. When the end of the timeline is reached, it is paused.
+    pub fn step(&mut self, delta: time::Duration) -> Vec<Marker> {
+        let mut crossed = Vec::new();
+
+        let (mut loops, mut t) = match self.state {
+            AnimationState::Playing(loops, t) => (loops, t),
+            _ => return crossed,
+        };
+        let duration = self.duration();
+        let mut remaining = delta;
+        let mut inclusive = std::mem::replace(&mut self.pending, false);
+
+        loop {
+            let region = self.active_region(t);
+            let limit = region.map_or(duration, |i| self.regions[i].range.end);
+
+            if t + remaining < limit {
+                self.cross(t, t + remaining, inclusive, &mut crossed);
+                t += remaining;
+                break;
+            }
+            // The playhead may be past the end after seeking there.
+            self.cross(t, limit, inclusive, &mut crossed);
+            remaining = remaining.saturating_sub(limit.saturating_sub(t));
+            t = t.max(limit);
+
+            match region {
+                Some(i) => {
+                    self.loops[i] += 1;
+                    loops += 1;
+                    t = self.regions[i].range.start;
+                    inclusive = true;
+                }
+                None => {
+                    self.state = AnimationState::Paused(loops, t);
+                    return crossed;
+                }
+            }
+        }
+        self.state = AnimationState::Playing(loops, t);
+
+        crossed
+    }
+
+    /// Sample every track at the given time.
+    pub fn sample(&self, t: time::Duration) -> Sample {
+        Sample {
+            time: t,
+            values: self
+                .tracks
+                .iter()
+                .map(|track| track.sample(t).expect("tracks are never empty"))
+                .collect(),
+        }
+    }
+
+    /// Sample every track at the current playhead position.
+    pub fn val(&self) -> Sample {
+        self.sample(self.elapsed())
+    }
+
+    /// Move the playhead to the given time, resetting region loops.
+    pub fn seek(&mut self, t: time::Duration) {
+        self.state = match self.state {
+            AnimationState::Playing(loops, _) => AnimationState::Playing(loops, t),
+            AnimationState::Paused(loops, _) => AnimationState::Paused(loops, t),
+            AnimationState::Stopped => AnimationState::Paused(0, t),
+        };
+        self.reset_loops();
+        self.pending = true;
+    }
+
+    pub fn pause(&mut self) {
+        if let AnimationState::Playing(loops, t) = self.state {
+            self.state = AnimationState::Paused(loops, t);
+        }
+    }
+
+    pub fn play(&mut self) {
+        match self.state {
+            AnimationState::Paused(loops, t) => {
+                // Restart if we were paused at the end of the timeline.
+                if t >= self.duration() && self.active_region(t).is_none() {
+                    self.reset_loops();
+                    self.pending = true;
+                    self.state = AnimationState::Playing(0, time::Duration::from_secs(0));
+                } else {
+                    self.state = AnimationState::Playing(loops, t);
+                }
+            }
+            AnimationState::Stopped => {
+                self.reset_loops();
+                self.pending = true;
+                self.state = AnimationState::Playing(0, time::Duration::from_secs(0));
+            }
+            _ => {}
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.state = AnimationState::Stopped;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state, AnimationState::Playing(_, _))
+    }
+
+    /// Whether the playhead has reached the end of the timeline.
+    pub fn is_finished(&self) -> bool {
+        match self.state {
+            AnimationState::Paused(_, t) => t >= self.duration(),
+            _ => false,
+        }
+    }
+
+    /// Current playhead position.
+    pub fn elapsed(&self) -> time::Duration {
+        match self.state {
+            AnimationState::Playing(_, t) => t,
+            AnimationState::Paused(_, t) => t,
+            AnimationState::Stopped => time::Duration::from_secs(0),
+        }
+    }
+
+    fn reset_loops(&mut self) {
+        for l in self.loops.iter_mut() {
+            *l = 0;
+        }
+    }
+
+    /// The region the playhead is looping in at time `t`, if any.
+    fn active_region(&self, t: time::Duration) -> Option<usize> {
+        self.regions.iter().enumerate().find_map(|(i, r)| {
+            let exhausted = match r.repeat {
+                Some(n) => self.loops[i] >= n,
+                None => false,
+            };
+
+            if !exhausted && r.range.start <= t && t < r.range.end {
+                Some(i)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Collect the markers in `(from, to]`, or `[from, to]` if inclusive.
+    fn cross(
+        &self,
+        from: time::Duration,
+        to: time::Duration,
+        inclusive: bool,
+        crossed: &mut Vec<Marker>,
+    ) {
+        crossed.extend(
+            self.markers
+                .iter()
+                .filter(|m| (m.time > from || inclusive && m.time == from) && m.time <= to)
+                .cloned(),
+        );
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_track_sample() {
+        let track =
+            Track::new()
+                .key(ms(100), 0.0)
+                .key(ms(200), 10.0)
+                .eased(ms(300), 20.0, Easing::Hold);
+
+        assert_eq!(track.sample(ms(0)), Some(0.0));
+        assert_eq!(track.sample(ms(150)), Some(5.0));
+        assert_eq!(track.sample(ms(200)), Some(10.0));
+        assert_eq!(track.sample(ms(299)), Some(10.0));
+        assert_eq!(track.sample(ms(300)), Some(20.0));
+        assert_eq!(track.sample(ms(900)), Some(20.0));
+        assert_eq!(Track::<f32>::new().sample(ms(0)), None);
+    }
+
+    #[test]
+    fn test_markers_and_regions() {
+        let mut timeline = Timeline::new();
+        let x = timeline.track(
+            Track::new()
+                .key(ms(0), Point2::new(0., 0.))
+                .key(ms(1000), Point2::new(100., 0.)),
+        );
+        timeline.marker("start", ms(0));
+        timeline.marker("half", ms(500));
+        timeline.marker("end", ms(1000));
+        timeline.region(ms(400)..ms(600), Some(2));
+
+        let names = |ms: Vec<Marker>| ms.into_iter().map(|m| m.name).collect::<Vec<_>>();
+
+        assert_eq!(names(timeline.step(ms(450))), vec!["start"]);
+        assert_eq!(timeline.val().get(x), Point2::new(45., 0.));
+
+        // Loops twice over the region, crossing the "half" marker each time.
+        assert_eq!(names(timeline.step(ms(400))), vec!["half", "half"]);
+        assert_eq!(timeline.elapsed(), ms(450));
+        assert_eq!(names(timeline.step(ms(150))), vec!["half"]);
+        assert_eq!(timeline.elapsed(), ms(600));
+
+        assert_eq!(names(timeline.step(ms(1000))), vec!["end"]);
+        assert!(timeline.is_finished());
+        assert_eq!(timeline.val().get(x), Point2::new(100., 0.));
+
+        timeline.play();
+        assert_eq!(timeline.elapsed(), ms(0));
+        assert_eq!(names(timeline.step(ms(100))), vec!["start"]);
+
+        // Markers at the playhead are reported once after seeking, but not
+        // again after pausing and resuming.
+        timeline.seek(ms(500));
+        assert_eq!(names(timeline.step(ms(0))), vec!["half"]);
+        timeline.pause();
+        timeline.play();
+        assert!(timeline.step(ms(10)).is_empty());
+    }
+
+    #[test]
+    fn test_seek_past_end() {
+        let mut timeline = Timeline::new();
+        timeline.track(Track::new().key(ms(0), 0.0).key(ms(100), 1.0));
+
+        timeline.seek(ms(500));
+        assert!(timeline.step(ms(10)).is_empty());
+        assert!(timeline.is_finished());
+        assert_eq!(timeline.elapsed(), ms(500));
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut timeline = Timeline::new();
+        let r = timeline.track(
+            Track::new()
+                .key(ms(0), Rect::new(0., 0., 0., 0.))
+                .key(ms(100), Rect::new(0., 0., 10., 10.)),
+        );
+        timeline.step(ms(50));
+        timeline.pause();
+        timeline.step(ms(50));
+
+        assert_eq!(timeline.val().get(r), Rect::new(0., 0., 5., 5.));
+
+        timeline.play();
+        timeline.step(ms(25));
+
+        assert_eq!(timeline.val().get(r), Rect::new(0., 0., 7.5, 7.5));
+    }
+}
@@ -476,6 +476,31 @@ where
     }
 }
 
+impl<S: Float> Matrix4<S> {
+    /// Create a homogeneous transformation matrix from a rotation around the
+    /// `z` axis, in radians.
+    ///
+    /// ```
+    /// use rgx::math::*;
+    /// let m = Matrix4::from_angle_z(std::f32::consts::FRAC_PI_2);
+    /// let p = m * Point2::new(1., 0.);
+    ///
+    /// assert!(p.x.abs() < 1e-6 && (p.y - 1.).abs() < 1e-6);
+    /// ```
+    #[inline]
+    #[rustfmt::skip]
+    pub fn from_angle_z(theta: S) -> Matrix4<S> {
+        let (s, c) = theta.sin_cos();
+
+        Matrix4::new(
+            c,         s,         S::zero(), S::zero(),
+            -s,        c,         S::zero(), S::zero(),
+            S::zero(), S::zero(), S::one(),  S::zero(),
+            S::zero(), S::zero(), S::zero(), S::one(),
+        )
+    }
+}
+
 /// Transform a [`Vector3`] with a [`Matrix4`].
 ///
 /// ```